@@ -0,0 +1,19 @@
+use std::path::Path;
+
+/// Copies of Chart.js and its date adapter that the `bundled-charts` feature embeds.
+const CHART_ASSETS: [&str; 2] = ["src/assets/chart.umd.js", "src/assets/chartjs-adapter-date-fns.bundle.min.js"];
+
+/// Flags a `bundled-charts` build without the vendored scripts, so `html.rs` can stop it
+/// with a `compile_error!` pointing at `scripts/vendor-charts.sh`.
+fn main() {
+    println!("cargo::rustc-check-cfg=cfg(chart_assets_missing)");
+    if std::env::var_os("CARGO_FEATURE_BUNDLED_CHARTS").is_none() {
+        return;
+    }
+    for asset in CHART_ASSETS {
+        println!("cargo::rerun-if-changed={}", asset);
+    }
+    if CHART_ASSETS.iter().any(|asset| !Path::new(asset).exists()) {
+        println!("cargo::rustc-cfg=chart_assets_missing");
+    }
+}
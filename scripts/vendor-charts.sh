@@ -0,0 +1,16 @@
+#!/bin/sh
+# Fetches the Chart.js and date adapter builds that `--features bundled-charts` embeds
+# into HTML reports, or into DIR for `--inline-assets` with a default build. Versions are
+# pinned, as in CHART_ASSET_FILES in src/html.rs; bump both and rerun to update.
+set -eu
+
+CHART_JS_VERSION=4.4.1
+DATE_ADAPTER_VERSION=3.0.0
+
+assets="${1:-$(dirname "$0")/../src/assets}"
+mkdir -p "$assets"
+curl -fsSL -o "$assets/chart.umd.js" \
+    "https://cdn.jsdelivr.net/npm/chart.js@$CHART_JS_VERSION/dist/chart.umd.js"
+curl -fsSL -o "$assets/chartjs-adapter-date-fns.bundle.min.js" \
+    "https://cdn.jsdelivr.net/npm/chartjs-adapter-date-fns@$DATE_ADAPTER_VERSION/dist/chartjs-adapter-date-fns.bundle.min.js"
+echo "Chart.js $CHART_JS_VERSION and chartjs-adapter-date-fns $DATE_ADAPTER_VERSION saved in $assets"
@@ -0,0 +1,880 @@
+// Colors of the page theme (--theme), from the CSS variables of the report's container
+function themeColors() {
+    const style = getComputedStyle(document.querySelector('.container'));
+    const color = (name) => style.getPropertyValue(name).trim();
+    return {
+        surface: color('--surface'), border: color('--border'), grid: color('--subtle'),
+        text: color('--text'), muted: color('--muted'), added: color('--added')
+    };
+}
+
+// Applies the theme's colors to a chart's tooltip and axes (and to the growth line),
+// since canvas drawing can't use CSS variables; called again when the theme changes
+function styleChart(chart) {
+    const colors = themeColors();
+    Object.assign(chart.options.plugins.tooltip, {
+        backgroundColor: colors.surface, borderColor: colors.border, titleColor: colors.text, bodyColor: colors.text
+    });
+    Object.values(chart.options.scales || {}).forEach(scale => {
+        scale.grid.color = colors.grid;
+        scale.ticks.color = colors.muted;
+    });
+    if (chart.canvas.id === 'growthChart') {
+        Object.assign(chart.data.datasets[0], { borderColor: colors.added, backgroundColor: colors.added + '20' });
+    }
+    chart.update('none');
+    return chart;
+}
+
+// Dashed vertical lines at release tags (the chart's releaseLines option), with the tag
+// name at the top where it doesn't overlap the previous one
+const releaseLines = {
+    id: 'releaseLines',
+    afterDatasetsDraw(chart, _, options) {
+        const { ctx, chartArea, scales: { x } } = chart;
+        const colors = themeColors();
+        let labelEnd = -Infinity;
+        ctx.save();
+        ctx.font = '10px -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif';
+        ctx.strokeStyle = colors.muted;
+        ctx.fillStyle = colors.muted;
+        ctx.setLineDash([4, 3]);
+        ctx.lineWidth = 1;
+        (options.tags || []).forEach(tag => {
+            const px = x.getPixelForValue(x.parse(tag.date));
+            if (px < chartArea.left || px > chartArea.right) {
+                return;
+            }
+            ctx.beginPath();
+            ctx.moveTo(px, chartArea.top);
+            ctx.lineTo(px, chartArea.bottom);
+            ctx.stroke();
+            const width = ctx.measureText(tag.name).width;
+            if (px + 3 > labelEnd && px + 3 + width <= chartArea.right) {
+                ctx.fillText(tag.name, px + 3, chartArea.top + 10);
+                labelEnd = px + 3 + width + 6;
+            }
+        });
+        ctx.restore();
+    }
+};
+
+// The toggle switches the page between light and dark, starting from the theme in effect
+// (with --theme auto, the viewer's system setting)
+function setupThemeToggle() {
+    const themed = document.querySelector('.container').closest('[data-theme]');
+    const button = document.getElementById('themeToggle');
+    if (!themed) {
+        button.hidden = true;
+        return;
+    }
+    const isLight = () => getComputedStyle(themed).colorScheme === 'light';
+    const label = () => {
+        button.textContent = isLight() ? 'Dark theme' : 'Light theme';
+    };
+    const restyle = () => {
+        label();
+        Object.values(Chart.instances || {}).forEach(styleChart);
+    };
+    button.addEventListener('click', () => {
+        themed.dataset.theme = isLight() ? 'dark' : 'light';
+        restyle();
+    });
+    window.matchMedia('(prefers-color-scheme: light)').addEventListener('change', restyle);
+    label();
+}
+
+// Export buttons: the per-contributor totals (named as in --format json) as a CSV or JSON
+// download, or a markdown table on the clipboard
+function setupExport(report) {
+    const rows = report.contributors.map(c => ({ name: c.name, id: c.id, ...c.stats }));
+    const columns = [...new Set(rows.flatMap(row => Object.keys(row)))];
+    const slug = report.branch.replace(/[^A-Za-z0-9._-]+/g, '-');
+    const csvCell = (value) => {
+        const text = value === undefined ? '' : String(value);
+        return /[",\n]/.test(text) ? `"${text.replace(/"/g, '""')}"` : text;
+    };
+    const totalCommits = rows.reduce((sum, row) => sum + row.commits, 0);
+    const markdownCell = (value) => String(value).replace(/\|/g, '\\|');
+    const exports = {
+        csv: () => [columns, ...rows.map(row => columns.map(key => row[key]))].map(line => line.map(csvCell).join(',')).join('\n') + '\n',
+        json: () => JSON.stringify({ branch: report.branch, since: report.since, until: report.until, contributors: rows }, null, 2) + '\n',
+        markdown: () => [
+            '| Contributor | Commits | Lines added | Lines deleted | Files | Net | % |',
+            '|---|---:|---:|---:|---:|---:|---:|',
+            ...rows.map(row => `| ${markdownCell(row.name)} | ${row.commits} | ${row.lines_added} | ${row.lines_deleted} | ` +
+                `${row.files_changed} | ${row.net_lines > 0 ? '+' : ''}${row.net_lines} | ` +
+                `${(totalCommits > 0 ? row.commits / totalCommits * 100 : 0).toFixed(1)}% |`)
+        ].join('\n') + '\n'
+    };
+    const download = (text, type, extension) => {
+        const link = document.createElement('a');
+        link.href = URL.createObjectURL(new Blob([text], { type }));
+        link.download = `contributors-${slug}.${extension}`;
+        link.click();
+        URL.revokeObjectURL(link.href);
+    };
+    document.querySelectorAll('.export-buttons button').forEach(button => {
+        const label = button.textContent;
+        button.addEventListener('click', () => {
+            const text = exports[button.dataset.export]();
+            if (button.dataset.export === 'csv') {
+                download(text, 'text/csv', 'csv');
+            } else if (button.dataset.export === 'json') {
+                download(text, 'application/json', 'json');
+            } else {
+                (navigator.clipboard ? navigator.clipboard.writeText(text) : Promise.reject()).then(
+                    () => { button.textContent = 'Copied'; },
+                    () => { button.textContent = 'Copy failed'; });
+                setTimeout(() => { button.textContent = label; }, 1500);
+            }
+        });
+    });
+}
+
+// Renders the contributor report from its data object (embedded in the page or loaded from data.json).
+function renderReport(report) {
+    const contributors = report.contributors;
+    const growth = report.growth;
+    // Period toggle values, finest first; every series is pre-bucketed per granularity
+    const granularities = report.granularities;
+    // --with-previous: "+25%", "-10%", "new" or "=" against the previous period
+    const change = (now, before) => now === before ? '=' : before === 0 ? 'new' :
+        `${now > before ? '+' : ''}${((now - before) / Math.abs(before) * 100).toFixed(0)}%`;
+
+    document.getElementById('subtitle').textContent =
+        `Contributions to ${report.branch} · ${report.since || 'beginning'} to ${report.until || 'now'} · ` +
+        `merge commits ${report.mergesIncluded ? 'included' : 'excluded'}` +
+        (report.firstParent ? ' · first-parent history' : '') +
+        (report.busFactor ? ` · bus factor ${report.busFactor.names.length}` : '') +
+        (report.previous ? ` · ${change(contributors.reduce((sum, c) => sum + c.commits, 0), report.previous.commits)} commits ` +
+            `vs ${report.previous.since} to ${report.previous.until}` : '');
+    if (report.busFactor) {
+        document.getElementById('subtitle').title =
+            `${report.busFactor.names.join(', ')} account for ${report.busFactor.share.toFixed(0)}% of commits ` +
+            `(more than ${report.busFactorThreshold}%)`;
+    }
+
+    setupThemeToggle();
+    setupExport(report);
+
+    // Calculate global bounds from both datasets
+    const allCommitDates = report.totalCommitSeries[granularities[0]].map(d => d.date);
+    const allLinesDates = report.totalLineSeries[granularities[0]].map(d => d.date);
+    const allDates = [...new Set([...allCommitDates, ...allLinesDates])].sort();
+    const globalMinDate = allDates[0];
+    const globalMaxDate = allDates[allDates.length - 1];
+
+    // Current metric state
+    let currentMetric = 'commits';
+    const getTotalSeries = (period) =>
+        (currentMetric === 'commits' ? report.totalCommitSeries : report.totalLineSeries)[period];
+    const getContribSeries = (contrib, period) =>
+        (currentMetric === 'commits' ? contrib.commitSeries : contrib.lineSeries)[period];
+
+    // Calculate totals for percentages
+    const totalCommits = contributors.reduce((sum, c) => sum + c.commits, 0);
+    // Lines follow --lines-metric; negative net lines get no share
+    const totalLines = contributors.reduce((sum, c) => sum + Math.max(c.lines, 0), 0);
+    const linesLabel = report.linesLabel;
+
+    const toPoints = (data) => data.map(d => ({ x: d.date, y: d.count }));
+
+    // ISO week label (2025-W14) for a week starting on `key`; Sunday weeks use the following Monday
+    function weekLabel(key) {
+        const d = new Date(key);
+        d.setUTCDate(d.getUTCDate() + (8 - d.getUTCDay()) % 7 + 3);
+        const yearStart = new Date(Date.UTC(d.getUTCFullYear(), 0, 1));
+        const week = Math.ceil(((d - yearStart) / 86400000 + 1) / 7);
+        return `${d.getUTCFullYear()}-W${String(week).padStart(2, '0')}`;
+    }
+
+    // Buckets are keyed by their first day; label them like the terminal output
+    function bucketTitle(item) {
+        return item ? periodLabel(item.raw.x) : '';
+    }
+
+    function periodLabel(key) {
+        switch (currentPeriod) {
+            case 'week': return weekLabel(key);
+            case 'month': return key.slice(0, 7);
+            case 'quarter': return `${key.slice(0, 4)}-Q${Math.floor((parseInt(key.slice(5, 7)) - 1) / 3) + 1}`;
+            case 'year': return key.slice(0, 4);
+            default: return key;
+        }
+    }
+
+    // Pad data with boundary points
+    function padData(data, minDate, maxDate) {
+        const result = [...data];
+        if (result.length === 0 || result[0].x > minDate) {
+            result.unshift({ x: minDate, y: 0 });
+        }
+        if (result.length === 0 || result[result.length - 1].x < maxDate) {
+            result.push({ x: maxDate, y: 0 });
+        }
+        return result;
+    }
+
+    // Fill data to match all dates from reference (for proper stacking)
+    function fillToAllDates(data, referenceDates) {
+        const dataMap = new Map(data.map(d => [d.x, d.y]));
+        return referenceDates.map(date => ({
+            x: date,
+            y: dataMap.get(date) || 0
+        }));
+    }
+
+    // GitHub-style 53x7 grid of daily commits, one column per week, ending at the latest commit
+    const calendarColor = (level) => `var(--calendar-${level})`;
+    function renderCalendar(container, days) {
+        if (!report.calendarStart) return;
+        const counts = new Map(days.map(d => [d.date, d.count]));
+        const max = Math.max(...days.map(d => d.count), 1);
+        const cells = document.createElement('div');
+        cells.className = 'calendar-grid';
+        const total = days.reduce((sum, d) => sum + d.count, 0);
+        container.setAttribute('role', 'img');
+        container.setAttribute('aria-label',
+            `Contribution calendar: ${total} commits on ${days.length} days from ${report.calendarStart} to ${report.calendarEnd}`);
+        const day = new Date(report.calendarStart);
+        for (let i = 0; i < 53 * 7; i++) {
+            const key = day.toISOString().slice(0, 10);
+            const cell = document.createElement('div');
+            cell.className = 'calendar-day';
+            if (key > report.calendarEnd) {
+                cell.style.visibility = 'hidden';
+            } else {
+                const count = counts.get(key) || 0;
+                cell.style.background = calendarColor(count && Math.ceil(count / max * 4));
+                cell.title = `${count} commit${count === 1 ? '' : 's'} on ${key}`;
+            }
+            cells.appendChild(cell);
+            day.setUTCDate(day.getUTCDate() + 1);
+        }
+        container.appendChild(cells);
+    }
+
+    // Accessible fallback for each chart: a data table inside a <details>, built when
+    // opened and rebuilt on every toggle change while open
+    const dataTables = [];
+    function fillDataTable(details, [caption, headers, rows]) {
+        const previous = details.querySelector('table');
+        if (previous) previous.remove();
+        const table = document.createElement('table');
+        table.className = 'data-table';
+        table.createCaption().textContent = caption;
+        const head = table.createTHead().insertRow();
+        headers.forEach(text => {
+            const th = document.createElement('th');
+            th.scope = 'col';
+            th.textContent = text;
+            head.appendChild(th);
+        });
+        const body = table.createTBody();
+        rows.forEach(([label, ...values]) => {
+            const row = body.insertRow();
+            const th = document.createElement('th');
+            th.scope = 'row';
+            th.textContent = label;
+            row.appendChild(th);
+            values.forEach(value => { row.insertCell().textContent = value; });
+        });
+        details.appendChild(table);
+    }
+    function addDataTable(details, build) {
+        details.addEventListener('toggle', () => { if (details.open) fillDataTable(details, build()); });
+        dataTables.push({ details, build });
+    }
+    function refreshDataTables() {
+        dataTables.forEach(({ details, build }) => { if (details.open) fillDataTable(details, build()); });
+    }
+
+    const metricLabel = () => currentMetric === 'commits' ? 'commits' : linesLabel;
+    const formatValue = (y) => currentMode === 'share' ? `${y.toFixed(1)}%` : y.toLocaleString();
+
+    // Toggle buttons expose their state to assistive technology through aria-pressed
+    function activate(buttons, active) {
+        buttons.forEach(b => {
+            b.classList.toggle('active', b === active);
+            b.setAttribute('aria-pressed', String(b === active));
+        });
+    }
+
+    function renderPunchcard(container, name, grid, weekdays) {
+        container.innerHTML = '';
+        const max = Math.max(...grid.flat(), 1);
+        const total = grid.flat().reduce((sum, n) => sum + n, 0);
+        container.setAttribute('aria-label', `Punch card for ${name}: ${total} commits by weekday and hour of day`);
+        const cells = document.createElement('div');
+        cells.className = 'punchcard-grid';
+        cells.appendChild(document.createElement('div'));
+        for (let hour = 0; hour < 24; hour++) {
+            const label = document.createElement('div');
+            label.className = 'punchcard-label';
+            label.textContent = hour % 3 === 0 ? hour : '';
+            cells.appendChild(label);
+        }
+        grid.forEach((row, day) => {
+            const label = document.createElement('div');
+            label.className = 'punchcard-label';
+            label.textContent = weekdays[day];
+            cells.appendChild(label);
+            row.forEach((count, hour) => {
+                const cell = document.createElement('div');
+                cell.className = 'calendar-day';
+                cell.style.background = calendarColor(count && Math.ceil(count / max * 4));
+                cell.title = `${weekdays[day]} ${String(hour).padStart(2, '0')}:00 - ${count} commit${count === 1 ? '' : 's'}`;
+                cells.appendChild(cell);
+            });
+        });
+        container.appendChild(cells);
+    }
+
+    // Active-hours overlap (--hours-overlap): percent of shared commit hours, pair by pair
+    function renderOverlap(table, overlap) {
+        const hour = h => `${String(h % 24).padStart(2, '0')}:00`;
+        const head = table.createTHead().insertRow();
+        head.appendChild(document.createElement('th'));
+        overlap.contributors.forEach(name => {
+            const th = document.createElement('th');
+            th.scope = 'col';
+            th.textContent = name;
+            th.title = name;
+            head.appendChild(th);
+        });
+        const body = table.createTBody();
+        overlap.contributors.forEach((name, i) => {
+            const row = body.insertRow();
+            const th = document.createElement('th');
+            th.scope = 'row';
+            th.textContent = name;
+            row.appendChild(th);
+            overlap.percent[i].forEach((percent, j) => {
+                const cell = row.insertCell();
+                const start = overlap.window[i][j];
+                cell.textContent = i === j ? '—' : `${percent}%`;
+                cell.style.background = calendarColor(i === j ? 0 : Math.ceil(percent / 25));
+                cell.title = i === j ? name : (start === null
+                    ? `${name} & ${overlap.contributors[j]}: no shared hours`
+                    : `${name} & ${overlap.contributors[j]}: ${percent}% shared, most around ${hour(start)}-${hour(start + overlap.windowHours)} UTC`);
+            });
+        });
+    }
+
+    // Calculate max for current period
+    function getGlobalMax(period) {
+        return Math.max(...getTotalSeries(period).map(d => d.count), 1);
+    }
+
+    let currentPeriod = granularities[0];
+    let mainChart, contribCharts = [];
+
+    // Main chart - stacked area with all contributors
+    const mainCtx = document.getElementById('mainChart').getContext('2d');
+    const allDatesForPeriod = (period) => getTotalSeries(period).map(d => d.date);
+
+    // Contributors below this share (%) of the current metric are folded into one "Others" band
+    const othersThreshold = report.othersThreshold;
+    const othersColor = '#6e7681';
+    const metricTotal = () => currentMetric === 'commits' ? totalCommits : totalLines;
+    const metricValue = (c) => currentMetric === 'commits' ? c.commits : Math.max(c.lines, 0);
+    const isMinor = (c) => othersThreshold > 0 && metricTotal() > 0 && metricValue(c) / metricTotal() * 100 < othersThreshold;
+    function mainSeries(period) {
+        const series = contributors
+            .filter(c => !isMinor(c))
+            .map(c => ({ name: c.name, color: c.color, points: getContribSeries(c, period) }));
+        const minor = contributors.filter(isMinor);
+        if (minor.length > 0) {
+            const sums = {};
+            minor.forEach(c => getContribSeries(c, period).forEach(d => {
+                sums[d.date] = (sums[d.date] || 0) + d.count;
+            }));
+            series.push({
+                name: `Others (${minor.length})`,
+                color: othersColor,
+                points: Object.entries(sums).map(([date, count]) => ({ date, count }))
+            });
+        }
+        // Reversed: smallest at bottom, largest at top
+        return series.reverse();
+    }
+
+    // 'absolute' stacks raw counts; 'share' stacks each contributor's percentage of the bucket
+    let currentMode = 'absolute';
+    // 'area' and 'bar' stack each bucket; 'cumulative' draws each contributor's running total,
+    // as separate lines (or stacked shares of the running totals in 'share' mode)
+    let currentChart = 'area';
+    const isStacked = () => currentChart !== 'cumulative' || currentMode === 'share';
+
+    function buildMainDatasets(period) {
+        const dates = allDatesForPeriod(period);
+        const series = mainSeries(period);
+        let data = series.map(s => fillToAllDates(toPoints(s.points), dates));
+        if (currentChart === 'cumulative') {
+            data = data.map(points => {
+                let total = 0;
+                return points.map(p => ({ x: p.x, y: total += p.y }));
+            });
+        }
+        if (currentMode === 'share') {
+            const totals = dates.map((_, i) => data.reduce((sum, points) => sum + points[i].y, 0));
+            data = data.map(points => points.map((p, i) => ({ x: p.x, y: totals[i] ? p.y / totals[i] * 100 : 0 })));
+        }
+        const lines = !isStacked();
+        return series.map((series, index) => ({
+            type: currentChart === 'bar' ? 'bar' : 'line',
+            label: series.name,
+            data: data[index],
+            borderColor: series.color,
+            backgroundColor: series.color + (currentChart === 'bar' ? 'cc' : '80'),
+            fill: lines ? false : 'origin',
+            tension: 0.03,
+            pointRadius: 0,
+            pointHoverRadius: 4,
+            pointHoverBackgroundColor: series.color,
+            borderWidth: lines ? 2 : 1
+        }));
+    }
+
+    mainChart = styleChart(new Chart(mainCtx, {
+        type: 'line',
+        data: { datasets: buildMainDatasets(currentPeriod) },
+        plugins: [releaseLines],
+        options: {
+            responsive: true,
+            maintainAspectRatio: false,
+            interaction: {
+                intersect: false,
+                mode: 'index'
+            },
+            plugins: {
+                legend: { display: false },
+                releaseLines: { tags: report.tags || [] },
+                tooltip: {
+                    borderWidth: 1,
+                    padding: 12,
+                    displayColors: true,
+                    callbacks: {
+                        title: (items) => bucketTitle(items[0]),
+                        label: (item) => currentMode === 'share'
+                            ? `${item.dataset.label}: ${item.parsed.y.toFixed(1)}%`
+                            : `${item.dataset.label}: ${item.parsed.y} ${metricLabel()}${currentChart === 'cumulative' ? ' so far' : ''}`
+                    }
+                }
+            },
+            scales: {
+                x: {
+                    type: 'time',
+                    time: { unit: 'month' },
+                    min: globalMinDate,
+                    max: globalMaxDate,
+                    grid: {},
+                    ticks: {}
+                },
+                y: {
+                    stacked: true,
+                    beginAtZero: true,
+                    grid: {},
+                    ticks: {
+                        precision: 0,
+                        callback: (value) => currentMode === 'share' ? `${value}%` : value
+                    }
+                }
+            }
+        }
+    }));
+
+    // Share-of-total donut in the header, following the metric toggle with the same
+    // colors and "Others" grouping as the main chart
+    function shareSlices() {
+        const minor = contributors.filter(isMinor);
+        const slices = contributors.filter(c => !isMinor(c)).map(c => ({ name: c.name, color: c.color, value: metricValue(c) }));
+        if (minor.length > 0) {
+            slices.push({ name: `Others (${minor.length})`, color: othersColor, value: minor.reduce((sum, c) => sum + metricValue(c), 0) });
+        }
+        return slices;
+    }
+    const shareChart = styleChart(new Chart(document.getElementById('shareChart').getContext('2d'), {
+        type: 'doughnut',
+        data: { labels: [], datasets: [{ data: [], backgroundColor: [], borderWidth: 0 }] },
+        options: {
+            responsive: true,
+            maintainAspectRatio: false,
+            cutout: '60%',
+            plugins: {
+                legend: { display: false },
+                tooltip: {
+                    borderWidth: 1,
+                    padding: 12,
+                    callbacks: {
+                        label: (item) => {
+                            const total = metricTotal();
+                            const percent = total > 0 ? (item.parsed / total * 100).toFixed(1) : '0.0';
+                            return `${item.label}: ${item.parsed.toLocaleString()} ${metricLabel()} (${percent}%)`;
+                        }
+                    }
+                }
+            }
+        }
+    }));
+    function updateShareChart() {
+        const slices = shareSlices();
+        shareChart.data.labels = slices.map(s => s.name);
+        Object.assign(shareChart.data.datasets[0], { data: slices.map(s => s.value), backgroundColor: slices.map(s => s.color) });
+        shareChart.update();
+        document.getElementById('shareChart').setAttribute('aria-label',
+            `Share of ${metricLabel()}: ` + slices.map(s => `${s.name} ${metricTotal() > 0 ? (s.value / metricTotal() * 100).toFixed(1) : '0.0'}%`).join(', '));
+    }
+
+    function describeMainChart() {
+        document.getElementById('mainChart').setAttribute('aria-label',
+            `${isStacked() ? 'Stacked ' : 'Line '}${currentMode === 'share' ? 'percentage ' : ''}${currentChart === 'bar' ? 'bar ' : ''}chart of ` +
+            `${currentChart === 'cumulative' ? 'cumulative ' : ''}${metricLabel()} per ${currentPeriod} ` +
+            `for ${contributors.length} contributors` +
+            (report.tags ? `, with ${report.tags.length} release tags marked (${report.tags.map(t => `${t.name} on ${t.date}`).join(', ')})` : '') +
+            '; a data table follows');
+    }
+    addDataTable(document.getElementById('mainChartData'), () => {
+        const datasets = mainChart.data.datasets;
+        return [
+            `${currentMode === 'share' ? 'Share of ' : ''}${currentChart === 'cumulative' ? 'cumulative ' : ''}${metricLabel()} per ${currentPeriod}`,
+            [currentPeriod, ...datasets.map(d => d.label)],
+            allDatesForPeriod(currentPeriod).map((date, i) =>
+                [periodLabel(date), ...datasets.map(d => formatValue(d.data[i].y))])
+        ];
+    });
+    addDataTable(document.getElementById('growthChartData'), () => [
+        'Cumulative net lines',
+        ['date', 'net lines'],
+        growth.map(d => [d.date, d.count.toLocaleString()])
+    ]);
+
+    // Repository growth chart - cumulative net lines, independent of the metric toggle
+    styleChart(new Chart(document.getElementById('growthChart').getContext('2d'), {
+        type: 'line',
+        data: {
+            datasets: [{
+                data: growth.map(d => ({ x: d.date, y: d.count })),
+                // Colored by styleChart like the axes
+                fill: true,
+                stepped: true,
+                pointRadius: 0,
+                pointHoverRadius: 4,
+                borderWidth: 2
+            }]
+        },
+        options: {
+            responsive: true,
+            maintainAspectRatio: false,
+            interaction: {
+                intersect: false,
+                mode: 'index'
+            },
+            plugins: {
+                legend: { display: false },
+                tooltip: {
+                    borderWidth: 1,
+                    padding: 12,
+                    displayColors: false,
+                    callbacks: {
+                        title: (items) => items[0]?.label || '',
+                        label: (item) => `${item.parsed.y.toLocaleString()} net lines`
+                    }
+                }
+            },
+            scales: {
+                x: {
+                    type: 'time',
+                    time: { unit: 'month' },
+                    min: globalMinDate,
+                    max: globalMaxDate,
+                    grid: {},
+                    ticks: {}
+                },
+                y: {
+                    grid: {},
+                    ticks: { precision: 0 }
+                }
+            }
+        }
+    }));
+
+    // Contributor cards
+    const grid = document.getElementById('contributorsGrid');
+    const escapeHtml = (text) => String(text).replace(/[&<>"']/g, c =>
+        ({ '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' })[c]);
+    const link = (url, text) => `<a href="${escapeHtml(url)}" target="_blank" rel="noopener">${escapeHtml(text)}</a>`;
+
+    contributors.forEach((contrib, index) => {
+        const initials = contrib.initials;
+        const card = document.createElement('div');
+        card.className = 'contributor-card';
+        card.innerHTML = `
+            <div class="contributor-header">
+                <div class="avatar" style="background: ${contrib.color}; color: ${contrib.avatarText};" aria-hidden="true">${initials}${contrib.avatar
+                    ? `<img src="${escapeHtml(contrib.avatar)}" alt="" loading="lazy" referrerpolicy="no-referrer" onerror="this.remove()">` : ''}</div>
+                <div class="contributor-info">
+                    <h3>${contrib.url ? link(contrib.url, contrib.name) : escapeHtml(contrib.name)}</h3>
+                    <div class="contributor-stats">
+                        <span id="metric-${index}">${contrib.commits.toLocaleString()} commits</span>
+                        <span id="percent-${index}" style="color: var(--muted); margin-left: 8px;">${(contrib.commits / totalCommits * 100).toFixed(1)}%</span>
+                        ${contrib.previousCommits !== undefined ? `<span id="change-${index}" style="color: var(--muted); margin-left: 8px;" title="Change from the previous period">${change(contrib.commits, contrib.previousCommits)}</span>` : ''} &nbsp;
+                        <span class="added">${contrib.added.toLocaleString()} ++</span> &nbsp;
+                        <span class="deleted">${contrib.deleted.toLocaleString()} --</span>
+                        ${contrib.emptyCommits ? `&nbsp; <span style="color: var(--muted);">${contrib.emptyCommits} empty</span>` : ''}
+                    </div>
+                    ${contrib.page ? `<div class="details-link"><a href="${escapeHtml(contrib.page)}">Commits, files and aliases</a></div>` : ''}
+                    ${contrib.recentCommits ? `<details class="recent-commits"><summary>Latest commits</summary><ul>${contrib.recentCommits
+                        .map(c => `<li>${link(c.url, c.hash)} ${link(c.url, c.date)}</li>`).join('')}</ul></details>` : ''}
+                </div>
+                <span class="rank">#${index + 1}</span>
+            </div>
+            <div class="mini-chart">
+                <canvas id="chart-${index}" role="img"></canvas>
+            </div>
+            <details class="chart-data" id="chart-data-${index}"><summary>Data table</summary></details>
+            <div class="calendar" id="calendar-${index}"></div>
+        `;
+        grid.appendChild(card);
+        renderCalendar(document.getElementById(`calendar-${index}`), contrib.calendar);
+
+        const contribData = fillToAllDates(toPoints(getContribSeries(contrib, currentPeriod)), allDatesForPeriod(currentPeriod));
+
+        // Mini chart
+        const chart = styleChart(new Chart(document.getElementById(`chart-${index}`).getContext('2d'), {
+            type: 'line',
+            data: {
+                datasets: [{
+                    data: contribData,
+                    borderColor: contrib.color,
+                    backgroundColor: contrib.color + '20',
+                    fill: true,
+                    tension: 0.03,
+                    pointRadius: 0,
+                    pointHoverRadius: 5,
+                    pointHoverBackgroundColor: contrib.color,
+                    borderWidth: 2
+                }]
+            },
+            options: {
+                responsive: true,
+                maintainAspectRatio: false,
+                interaction: {
+                    intersect: false,
+                    mode: 'index'
+                },
+                plugins: {
+                    legend: { display: false },
+                    tooltip: {
+                        borderWidth: 1,
+                        padding: 10,
+                        displayColors: false,
+                        callbacks: {
+                            title: (items) => bucketTitle(items[0]),
+                            label: (item) => `${item.parsed.y} ${metricLabel()}`
+                        }
+                    }
+                },
+                scales: {
+                    x: {
+                        type: 'time',
+                        time: { unit: 'month' },
+                        min: globalMinDate,
+                        max: globalMaxDate,
+                        grid: { display: false },
+                        ticks: { maxTicksLimit: 4 }
+                    },
+                    y: {
+                        beginAtZero: true,
+                        min: -getGlobalMax(currentPeriod) * 0.05,
+                        max: getGlobalMax(currentPeriod),
+                        grid: { display: false },
+                        ticks: { display: false }
+                    }
+                }
+            }
+        }));
+        contribCharts.push({ chart, contrib, index });
+        addDataTable(document.getElementById(`chart-data-${index}`), () => [
+            `${contrib.name}: ${metricLabel()} per ${currentPeriod}`,
+            [currentPeriod, metricLabel()],
+            getContribSeries(contrib, currentPeriod).map(d => [periodLabel(d.date), d.count.toLocaleString()])
+        ]);
+    });
+
+    // Period toggle handler
+    function updateCharts(period) {
+        currentPeriod = period;
+        const globalMax = getGlobalMax(period);
+        const dates = allDatesForPeriod(period);
+
+        // Rebuild main chart datasets: the "Others" grouping depends on the current metric
+        mainChart.data.datasets = buildMainDatasets(period);
+        mainChart.options.scales.y.max = currentMode === 'share' ? 100 : undefined;
+        mainChart.options.scales.y.stacked = isStacked();
+        mainChart.options.scales.x.stacked = currentChart === 'bar';
+        // Bars need room on both ends of the time axis
+        mainChart.options.scales.x.offset = currentChart === 'bar';
+        mainChart.update();
+
+        describeMainChart();
+
+        // Update contributor charts
+        contribCharts.forEach(({ chart, contrib, index }) => {
+            document.getElementById(`chart-${index}`).setAttribute('aria-label',
+                `${contrib.name}: ${metricLabel()} per ${period}; a data table follows`);
+            const newData = fillToAllDates(toPoints(getContribSeries(contrib, period)), dates);
+            chart.data.datasets[0].data = newData;
+            chart.options.scales.y.max = globalMax;
+            chart.options.scales.y.min = -globalMax * 0.05;
+            chart.update();
+        });
+        refreshDataTables();
+    }
+
+    // Period toggle button click handlers
+    const periodButtons = '.period-toggle:not(.metric-toggle):not(.mode-toggle):not(.chart-toggle) button';
+    document.querySelectorAll(periodButtons).forEach(btn => {
+        if (!granularities.includes(btn.dataset.period)) {
+            btn.style.display = 'none';
+            return;
+        }
+        btn.addEventListener('click', () => {
+            activate(document.querySelectorAll(periodButtons), btn);
+            updateCharts(btn.dataset.period);
+        });
+    });
+
+    // Mode toggle: absolute volume vs. each contributor's share of the team per bucket
+    document.querySelectorAll('.mode-toggle button').forEach(btn => {
+        btn.addEventListener('click', () => {
+            activate(document.querySelectorAll('.mode-toggle button'), btn);
+            currentMode = btn.dataset.mode;
+            updateCharts(currentPeriod);
+        });
+    });
+
+    // Chart toggle: stacked areas, stacked bars or running totals
+    document.querySelectorAll('.chart-toggle button').forEach(btn => {
+        btn.addEventListener('click', () => {
+            activate(document.querySelectorAll('.chart-toggle button'), btn);
+            currentChart = btn.dataset.chart;
+            updateCharts(currentPeriod);
+        });
+    });
+
+    // Metric toggle button click handlers
+    document.querySelectorAll('.metric-toggle button').forEach(btn => {
+        btn.addEventListener('click', () => {
+            activate(document.querySelectorAll('.metric-toggle button'), btn);
+            currentMetric = btn.dataset.metric;
+            document.getElementById('chartTitle').textContent = currentMetric === 'commits' ? 'Commits over time' : `${linesLabel[0].toUpperCase()}${linesLabel.slice(1)} over time`;
+            // Update contributor card stats and percentages
+            contributors.forEach((contrib, index) => {
+                const value = currentMetric === 'commits' ? contrib.commits : contrib.lines;
+                const total = currentMetric === 'commits' ? totalCommits : totalLines;
+                const percent = (total > 0 ? Math.max(value, 0) / total * 100 : 0).toFixed(1);
+                const label = metricLabel();
+                document.getElementById(`metric-${index}`).textContent = `${value.toLocaleString()} ${label}`;
+                document.getElementById(`percent-${index}`).textContent = `${percent}%`;
+                if (contrib.previousCommits !== undefined) {
+                    const before = currentMetric === 'commits' ? contrib.previousCommits : contrib.previousLines;
+                    document.getElementById(`change-${index}`).textContent = change(value, before);
+                }
+            });
+            updateShareChart();
+            updateCharts(currentPeriod);
+        });
+    });
+
+    if (report.hoursOverlap) {
+        renderOverlap(document.getElementById('overlapMatrix'), report.hoursOverlap);
+        document.getElementById('overlapCard').hidden = false;
+    }
+
+    // Punch card (--punchcard): weekday x hour grid for the team or one contributor
+    if (report.punchcard) {
+        const select = document.getElementById('punchcardSelect');
+        [{ name: 'Team (all contributors)', grid: report.punchcard.team },
+            ...contributors.map(c => ({ name: c.name, grid: c.punchcard }))]
+            .forEach((entry, i) => {
+                const option = document.createElement('option');
+                option.value = i;
+                option.textContent = entry.name;
+                option.grid = entry.grid;
+                select.appendChild(option);
+            });
+        const draw = () => {
+            const { grid, textContent: name } = select.options[select.selectedIndex];
+            renderPunchcard(document.getElementById('punchcard'), name, grid, report.punchcard.weekdays);
+        };
+        select.addEventListener('change', draw);
+        document.getElementById('punchcardCard').hidden = false;
+        draw();
+    }
+
+    // Per-repository breakdown (--per-repo)
+    (report.repositories || []).forEach(repo => {
+        const card = document.createElement('div');
+        card.className = 'card';
+        const title = document.createElement('h2');
+        title.className = 'card-title';
+        title.textContent = repo.name;
+        const table = document.createElement('table');
+        table.className = 'repo-table';
+        table.innerHTML = '<tr><th>Contributor</th><th>Commits</th><th>Added</th><th>Deleted</th></tr>';
+        repo.contributors.forEach(c => {
+            const row = table.insertRow();
+            [c.name, c.commits.toLocaleString(), c.added.toLocaleString(), c.deleted.toLocaleString()]
+                .forEach(value => { row.insertCell().textContent = value; });
+        });
+        card.append(title, table);
+        document.getElementById('repositories').appendChild(card);
+    });
+
+    // Set default: Lines metric and weekly buckets (or the finest available with --by)
+    const defaultPeriod = granularities.includes('week') ? 'week' : granularities[0];
+    document.querySelector('.metric-toggle button[data-metric="lines"]').click();
+    document.querySelector(`${periodButtons}[data-period="${defaultPeriod}"]`).click();
+}
+
+// A report --max-report-size split across files beside the page: each contributor file
+// adds its share, then the last one renders the rest of the report with all of them.
+const splitContributors = [];
+function addReportContributors(chunk) {
+    chunk.forEach(contrib => splitContributors.push(contrib));
+}
+function renderSplitReport(report) {
+    renderReport(Object.assign(report, { contributors: splitContributors }));
+}
+
+// One report per branch or range, switched with tabs above the report. Each switch
+// rebuilds the report markup, so toggles and charts start fresh for that revision.
+function renderReportTabs(reports) {
+    const container = document.querySelector('.container');
+    const template = container.innerHTML;
+    const tabs = document.createElement('div');
+    tabs.className = 'range-tabs';
+    tabs.setAttribute('role', 'tablist');
+    tabs.setAttribute('aria-label', 'Branches and ranges');
+    container.parentNode.insertBefore(tabs, container);
+
+    const show = (index) => {
+        Object.values(Chart.instances || {}).forEach(chart => chart.destroy());
+        container.innerHTML = template;
+        tabs.querySelectorAll('button').forEach((b, i) => {
+            b.classList.toggle('active', i === index);
+            b.setAttribute('aria-selected', String(i === index));
+        });
+        renderReport(reports[index]);
+    };
+    reports.forEach((report, index) => {
+        const button = document.createElement('button');
+        button.textContent = report.branch;
+        button.setAttribute('role', 'tab');
+        button.addEventListener('click', () => show(index));
+        tabs.appendChild(button);
+    });
+    show(0);
+}
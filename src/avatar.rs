@@ -0,0 +1,160 @@
+use crate::forge::{noreply_login, percent_encode, ForgeLinks};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Per-round shift amounts of MD5 (RFC 1321).
+const SHIFTS: [u32; 64] = [
+    7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, 4, 11, 16,
+    23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+];
+
+/// MD5 as lowercase hex. Gravatar still identifies people by the MD5 of their email;
+/// it is not used for anything security-related.
+fn md5_hex(data: &[u8]) -> String {
+    let constants: Vec<u32> = (0..64).map(|i| ((i as f64 + 1.0).sin().abs() * 4294967296.0) as u32).collect();
+    let mut state: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    for block in message.chunks(64) {
+        let words: Vec<u32> = block.chunks(4).map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]])).collect();
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let rotated = a.wrapping_add(f).wrapping_add(constants[i]).wrapping_add(words[g]).rotate_left(SHIFTS[i]);
+            (a, d, c) = (d, c, b);
+            b = b.wrapping_add(rotated);
+        }
+        for (value, add) in state.iter_mut().zip([a, b, c, d]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+    state.iter().flat_map(|v| v.to_le_bytes()).map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Picture for `--avatars`: the GitHub avatar when the email is a GitHub private commit
+/// address (which reveals the login), otherwise the email's Gravatar. `d=404` makes
+/// Gravatar fail for people without one, so the report keeps their initials.
+pub fn avatar_url(email: &str) -> Option<String> {
+    let email = email.trim().to_lowercase();
+    if email.is_empty() {
+        return None;
+    }
+    if email.ends_with("@users.noreply.github.com") {
+        if let Some(login) = noreply_login(&email) {
+            return Some(format!("https://github.com/{}.png?size=96", login));
+        }
+    }
+    Some(format!("https://www.gravatar.com/avatar/{}?s=96&d=404", md5_hex(email.as_bytes())))
+}
+
+/// The GitHub REST API, authenticated with `GITHUB_TOKEN` when set. Without a token only
+/// public organization members are listed, and 60 requests an hour are allowed.
+struct GitHubApi {
+    agent: ureq::Agent,
+    token: Option<String>,
+}
+
+impl GitHubApi {
+    fn new() -> GitHubApi {
+        GitHubApi {
+            agent: ureq::AgentBuilder::new().timeout(Duration::from_secs(15)).build(),
+            token: std::env::var("GITHUB_TOKEN").ok().filter(|token| !token.trim().is_empty()),
+        }
+    }
+
+    fn get(&self, url: &str) -> Result<serde_json::Value, String> {
+        let mut request = self
+            .agent
+            .get(url)
+            .set("Accept", "application/vnd.github+json")
+            .set("User-Agent", concat!("git-contrib-stats/", env!("CARGO_PKG_VERSION")));
+        if let Some(token) = &self.token {
+            request = request.set("Authorization", &format!("Bearer {}", token.trim()));
+        }
+        let body = request.call().map_err(|e| e.to_string())?.into_string().map_err(|e| e.to_string())?;
+        serde_json::from_str(&body).map_err(|e| e.to_string())
+    }
+
+    /// Lowercased logins of the members of `org` on the GitHub at `api`.
+    fn org_members(&self, api: &str, org: &str) -> Result<HashSet<String>, String> {
+        let mut members = HashSet::new();
+        for page in 1.. {
+            let list = self.get(&format!("{}/orgs/{}/members?per_page=100&page={}", api, percent_encode(org), page))?;
+            let logins: Vec<String> = list
+                .as_array()
+                .map(|list| list.iter().filter_map(|member| member["login"].as_str()).map(str::to_lowercase).collect())
+                .unwrap_or_default();
+            let last = logins.len() < 100;
+            members.extend(logins);
+            if last {
+                break;
+            }
+        }
+        Ok(members)
+    }
+}
+
+/// GitHub avatars for `--github-org`, by contributor name. `commits` holds one commit of
+/// each contributor (name, repository, hash): GitHub tells which account authored it from
+/// the emails people verified, and the avatar is kept when that account is a member of
+/// `org`. Contributors of repositories not on GitHub are left out; the first failed
+/// request is reported and ends the lookups, leaving everyone else to `avatar_url`.
+pub fn github_org_avatars(org: &str, links: &ForgeLinks, commits: &[(&str, &str, &str)]) -> HashMap<String, String> {
+    let github = GitHubApi::new();
+    // Members per GitHub instance, fetched on first use
+    let mut members: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut avatars = HashMap::new();
+    for (name, repo, hash) in commits {
+        let Some(repo) = links.github_repo(repo) else { continue };
+        let mut lookup = || -> Result<Option<String>, String> {
+            if !members.contains_key(&repo.api) {
+                members.insert(repo.api.clone(), github.org_members(&repo.api, org)?);
+            }
+            let commit = github.get(&format!("{}/repos/{}/commits/{}", repo.api, repo.path, hash))?;
+            let login = commit["author"]["login"].as_str();
+            Ok(login.filter(|login| members[&repo.api].contains(&login.to_lowercase())).map(String::from))
+        };
+        match lookup() {
+            Ok(Some(login)) => {
+                avatars.insert(name.to_string(), format!("{}/{}.png?size=96", repo.web, login));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("Warning: could not look up members of --github-org {} on GitHub: {}", org, e);
+                break;
+            }
+        }
+    }
+    avatars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn md5_matches_known_digests() {
+        assert_eq!(md5_hex(b""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(md5_hex(b"The quick brown fox jumps over the lazy dog"), "9e107d9d372bb6826bd81d3542a419d6");
+    }
+
+    #[test]
+    fn gravatar_hashes_the_trimmed_lowercased_email() {
+        // The example from Gravatar's documentation
+        assert_eq!(
+            avatar_url(" MyEmailAddress@example.com ").as_deref(),
+            Some("https://www.gravatar.com/avatar/0bc83cb571cd1c50ba6f3e8a78ef1346?s=96&d=404")
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use crate::error::{Error, OrExit};
+use crate::{
+    canonical_name, display_width, handle_formatting, handle_imports, handle_initial_commits, handle_line_endings, load_commits,
+    open_sources, pad_display, repo_paths, requested_revisions, Args, CommitRecord, Progress,
+};
+use std::collections::{HashMap, HashSet};
+
+/// The commits of `rev`, with the same exclusions as the main report.
+fn analyzed_commits(args: &Args, rev: &str, progress: &Progress) -> Vec<CommitRecord> {
+    let sources = open_sources(args, Some(rev)).or_exit();
+    let mut commits = load_commits(&sources, args, args.since.clone(), args.until.clone(), progress).or_exit();
+    handle_initial_commits(&mut commits, args);
+    handle_line_endings(&mut commits, args);
+    handle_formatting(&mut commits, args);
+    handle_imports(&mut commits, args);
+    commits
+}
+
+fn authored_by<'a>(commits: &'a [CommitRecord], name: &str, author_mapping: &HashMap<String, String>) -> Vec<&'a CommitRecord> {
+    commits.iter().filter(|c| canonical_name(&c.author, author_mapping) == name).collect()
+}
+
+/// `--author-branches`: one contributor's commits on the analyzed branch, then on each
+/// other branch the commits it doesn't contain yet, so leads can see how much of their
+/// work is still unmerged. A commit on several branches counts once in the total.
+pub fn print_author_branches(args: &Args, author_mapping: &HashMap<String, String>, name: &str, progress: &Progress) {
+    if repo_paths(args).or_exit().len() > 1 {
+        Error::Usage("--author-branches works on one repository at a time".to_string()).exit();
+    }
+    let sources = open_sources(args, requested_revisions(args).first().map(String::as_str)).or_exit();
+    let source = &sources[0];
+    if source.rev.contains("..") {
+        Error::Usage(format!("--author-branches compares other branches with a branch, not a range ('{}')", source.label)).exit();
+    }
+    let merged = analyzed_commits(args, &source.rev, progress);
+    let branches: Vec<(String, Vec<CommitRecord>)> = source
+        .repo
+        .branches()
+        .into_iter()
+        .filter(|branch| *branch != source.label)
+        .map(|branch| {
+            let commits = analyzed_commits(args, &format!("{}..{}", source.rev, branch), progress);
+            (branch, commits)
+        })
+        .collect();
+
+    // Accept the contributor's name in any case, or one of their emails
+    let wanted = name.to_lowercase();
+    let Some(name) = merged
+        .iter()
+        .chain(branches.iter().flat_map(|(_, commits)| commits))
+        .find(|c| canonical_name(&c.author, author_mapping).to_lowercase() == wanted || c.email == wanted)
+        .map(|c| canonical_name(&c.author, author_mapping).clone())
+    else {
+        Error::Usage(format!("no commits by '{}' on any branch of {}", name, source.repo.name())).exit()
+    };
+    let merged = authored_by(&merged, &name, author_mapping);
+    let unmerged: Vec<(&String, Vec<&CommitRecord>)> = branches
+        .iter()
+        .map(|(branch, commits)| (branch, authored_by(commits, &name, author_mapping)))
+        .filter(|(_, commits)| !commits.is_empty())
+        .collect();
+
+    println!("Work of {} on the branches of {}, compared with {}", name, source.repo.name(), source.label);
+    println!();
+    let merged_label = format!("{} (merged)", source.label);
+    let name_width = unmerged
+        .iter()
+        .map(|(branch, _)| display_width(branch))
+        .chain([display_width(&merged_label)])
+        .max()
+        .unwrap_or(0)
+        .max(6);
+    println!(
+        "| {} | {:>8} | {:>11} | {:>13} | {:>11} |",
+        pad_display("Branch", name_width),
+        "Commits",
+        "Lines added",
+        "Lines deleted",
+        "Last commit"
+    );
+    println!("|{:-<width$}|{:-<10}|{:-<13}|{:-<15}|{:-<13}|", "", "", "", "", "", width = name_width + 2);
+    let row = |label: &str, commits: &[&CommitRecord]| {
+        println!(
+            "| {} | {:>8} | {:>11} | {:>13} | {:>11} |",
+            pad_display(label, name_width),
+            commits.len(),
+            commits.iter().map(|c| c.lines_added).sum::<u64>(),
+            commits.iter().map(|c| c.lines_deleted).sum::<u64>(),
+            commits.iter().map(|c| c.date.as_str()).max().unwrap_or("-")
+        );
+    };
+    row(&merged_label, &merged);
+    for (branch, commits) in &unmerged {
+        row(branch, commits);
+    }
+
+    let mut seen = HashSet::new();
+    let distinct: Vec<&CommitRecord> = unmerged
+        .iter()
+        .flat_map(|(_, commits)| commits.iter().copied())
+        .filter(|c| seen.insert(c.hash.as_str()))
+        .collect();
+    println!();
+    if distinct.is_empty() {
+        println!("Unmerged: nothing, all of {}'s commits are on {}", name, source.label);
+        return;
+    }
+    let lines = |commits: &mut dyn Iterator<Item = &CommitRecord>| commits.map(|c| c.lines_added + c.lines_deleted).sum::<u64>();
+    let unmerged_lines = lines(&mut distinct.iter().copied());
+    let all_commits = merged.len() + distinct.len();
+    let all_lines = lines(&mut merged.iter().copied()) + unmerged_lines;
+    println!(
+        "Unmerged: {} of {} commits ({:.0}%), {} of {} lines changed, on {} branch{}",
+        distinct.len(),
+        all_commits,
+        distinct.len() as f64 / all_commits as f64 * 100.0,
+        unmerged_lines,
+        all_lines,
+        unmerged.len(),
+        if unmerged.len() == 1 { "" } else { "es" }
+    );
+}
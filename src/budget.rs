@@ -0,0 +1,100 @@
+use crate::error::Error;
+use crate::repo::{PathFilter, RawCommit, Repo};
+use crate::Args;
+use git2::Oid;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+
+/// Parses `--max-memory` and `--max-report-size` sizes: bytes, or a number with a K, M or G suffix (powers of 1024).
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (number, unit) = match value.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((i, _)) => value.split_at(i),
+        None => (value, ""),
+    };
+    let shift = match unit.trim().to_ascii_uppercase().trim_end_matches(['B', 'I']) {
+        "" => 0,
+        "K" => 10,
+        "M" => 20,
+        "G" => 30,
+        _ => return Err(format!("unknown unit '{}' (expected K, M or G)", unit)),
+    };
+    let number: u64 = number.parse().map_err(|_| format!("'{}' is not a size like 512M or 2G", value))?;
+    number
+        .checked_mul(1 << shift)
+        .filter(|bytes| *bytes > 0)
+        .ok_or_else(|| format!("'{}' is not a size like 512M or 2G", value))
+}
+
+/// Diff threads per repository scan: `--max-parallel`, by default one per CPU.
+pub fn max_parallel(args: &Args) -> usize {
+    args.max_parallel
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+        .max(1)
+}
+
+/// Half of `--max-memory` goes to libgit2's object cache (256 MiB by default), a quarter
+/// to diff results waiting to be recorded (see `diff_commits`). Stats are appended to the
+/// cache journal rather than held (see `StatsCache`). Must run before any thread is started.
+pub fn apply_memory_limit(args: &Args) {
+    if let Some(budget) = args.max_memory {
+        let limit = isize::try_from(budget / 2).unwrap_or(isize::MAX);
+        // SAFETY: called from `main` before any thread exists
+        if let Err(e) = unsafe { git2::opts::set_cache_max_size(limit) } {
+            eprintln!("Warning: could not limit the git object cache: {}", e.message());
+        }
+    }
+}
+
+/// Diffs `ids` on up to `--max-parallel` threads, each with its own handle on `repo`, and
+/// passes each result to `on_result` on the calling thread, in completion order. With
+/// `--max-memory`, workers pause while a quarter of the budget is waiting to be recorded.
+/// The first commit that can't be read stops the scan.
+pub fn diff_commits(repo: &Repo, ids: &[Oid], args: &Args, mut on_result: impl FnMut(Oid, RawCommit)) -> Result<(), Error> {
+    let threads = max_parallel(args).min(ids.len());
+    if threads <= 1 {
+        let paths = PathFilter::new(&args.path, &args.exclude_path, &args.ext, !args.include_generated)?;
+        for &oid in ids {
+            on_result(oid, repo.raw_commit(oid, &paths, args.first_parent)?);
+        }
+        return Ok(());
+    }
+
+    // Results are a few hundred bytes each; bound the queue by count from the budget
+    let queue = args.max_memory.map_or(4096, |budget| (budget / 4 / 512).clamp(threads as u64, 65536) as usize);
+    let next = AtomicUsize::new(0);
+    let worker = repo.diff_worker();
+    std::thread::scope(|scope| {
+        let (sender, receiver) = mpsc::sync_channel(queue);
+        for _ in 0..threads {
+            let (sender, next, worker) = (sender.clone(), &next, &worker);
+            scope.spawn(move || {
+                let opened = worker.open().and_then(|repo| {
+                    let paths = PathFilter::new(&args.path, &args.exclude_path, &args.ext, !args.include_generated)?;
+                    Ok((repo, paths))
+                });
+                let (repo, paths) = match opened {
+                    Ok(opened) => opened,
+                    Err(e) => {
+                        let _ = sender.send(Err(e));
+                        return;
+                    }
+                };
+                while let Some(&oid) = ids.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let raw = repo.raw_commit(oid, &paths, args.first_parent);
+                    let failed = raw.is_err();
+                    if sender.send(raw.map(|raw| (oid, raw))).is_err() || failed {
+                        return;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        // Returning drops the receiver, which stops the other workers at their next result
+        for result in receiver {
+            let (oid, raw) = result?;
+            on_result(oid, raw);
+        }
+        Ok(())
+    })
+}
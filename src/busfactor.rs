@@ -0,0 +1,137 @@
+use crate::error::OrExit;
+use crate::ownership::{directory_of, surviving_lines, Owners};
+use crate::repo::PathFilter;
+use crate::{canonical_name, Args, CommitRecord, Progress, Source};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+/// The fewest contributors who together account for more than the threshold share.
+#[derive(Serialize)]
+pub struct BusFactor {
+    pub names: Vec<String>,
+    /// Their combined share, in percent
+    pub share: f64,
+}
+
+impl BusFactor {
+    /// Takes contributors from the largest amount down until their share exceeds
+    /// `threshold` percent; `None` when `owners` is empty.
+    pub fn of(owners: &Owners, threshold: f64) -> Option<BusFactor> {
+        let mut names = Vec::new();
+        let mut covered = 0;
+        for (name, amount) in owners.ranked() {
+            names.push(name.to_string());
+            covered += amount;
+            if owners.percent(covered) > threshold {
+                break;
+            }
+        }
+        (!names.is_empty()).then(|| BusFactor { names, share: owners.percent(covered) })
+    }
+
+    /// "3 (Alice, Bob, Carol: 61% of commits)" for `unit` "commits".
+    pub fn describe(&self, unit: &str) -> String {
+        format!("{} ({}: {:.0}% of {})", self.names.len(), self.names.join(", "), self.share, unit)
+    }
+}
+
+/// Commits per contributor (after `--merge`), the basis of the default bus factor.
+pub fn commit_owners(commits: &[CommitRecord], author_mapping: &HashMap<String, String>) -> Owners {
+    let mut owners = Owners::default();
+    for commit in commits {
+        owners.add(canonical_name(&commit.author, author_mapping), 1);
+    }
+    owners
+}
+
+/// Prints the bus factor overall and per top-level directory, by commits or, with
+/// `--bus-factor-by lines`, by surviving lines from blame (`commits` must then cover the
+/// full history, as for `--ownership`).
+pub fn print_bus_factor(
+    sources: &[Source],
+    args: &Args,
+    commits: &[CommitRecord],
+    author_mapping: &HashMap<String, String>,
+    progress: &Progress,
+) {
+    let threshold = args.bus_factor_threshold;
+    let by_lines = args.bus_factor_by.as_deref() == Some("lines");
+    let (overall, directories) = if by_lines {
+        surviving_lines(sources, args, commits, author_mapping, progress)
+    } else {
+        (commit_owners(commits, author_mapping), commits_by_directory(sources, args, commits, author_mapping, progress))
+    };
+
+    let unit = if by_lines { "surviving lines" } else { "commits" };
+    let title = format!("Bus factor (fewest contributors with more than {}% of {})", threshold, unit);
+    println!("{}", title);
+    println!("{}", "─".repeat(title.chars().count()));
+    match BusFactor::of(&overall, threshold) {
+        Some(bus) => println!("Overall: {}", bus.describe(unit)),
+        None => println!("Overall: no data"),
+    }
+
+    let mut directories: Vec<(String, Owners)> = directories.into_iter().collect();
+    // Most exposed first: lowest bus factor, then largest directory
+    let factors: HashMap<String, BusFactor> = directories
+        .iter()
+        .filter_map(|(dir, owners)| Some((dir.clone(), BusFactor::of(owners, threshold)?)))
+        .collect();
+    directories.retain(|(dir, _)| factors.contains_key(dir));
+    directories.sort_by(|a, b| {
+        factors[&a.0].names.len()
+            .cmp(&factors[&b.0].names.len())
+            .then_with(|| b.1.total.cmp(&a.1.total))
+            .then_with(|| a.0.cmp(&b.0))
+    });
+    if directories.is_empty() {
+        return;
+    }
+
+    let dir_width = directories.iter().map(|(d, _)| d.len()).max().unwrap_or(9).max(9);
+    println!();
+    println!("| {:<dir_width$} | {:>10} | Contributors", "Directory", "Bus factor", dir_width = dir_width);
+    println!("|{:-<width$}|{:-<12}|{:-<14}", "", "", "", width = dir_width + 2);
+    for (dir, _) in &directories {
+        let bus = &factors[dir];
+        println!(
+            "| {:<dir_width$} | {:>10} | {} ({:.0}%)",
+            dir, bus.names.len(), bus.names.join(", "), bus.share,
+            dir_width = dir_width
+        );
+    }
+}
+
+/// Commits per contributor for each top-level directory they touch.
+fn commits_by_directory(
+    sources: &[Source],
+    args: &Args,
+    commits: &[CommitRecord],
+    author_mapping: &HashMap<String, String>,
+    progress: &Progress,
+) -> HashMap<String, Owners> {
+    let repos: HashMap<&str, &Source> = sources.iter().map(|s| (s.repo.name(), s)).collect();
+    let paths = PathFilter::new(&args.path, &args.exclude_path, &args.ext, !args.include_generated).or_exit();
+    let multi_repo = sources.len() > 1;
+
+    let mut directories: HashMap<String, Owners> = HashMap::new();
+    for (done, commit) in commits.iter().enumerate() {
+        progress.emit("bus-factor", done as u64, commits.len() as u64, None);
+        let (Some(source), Ok(oid)) = (repos.get(commit.repo.as_str()), commit.hash.parse()) else {
+            continue;
+        };
+        let name = canonical_name(&commit.author, author_mapping);
+        let touched: BTreeSet<String> = source
+            .repo
+            .file_stats(oid, &paths, args.first_parent)
+            .or_exit()
+            .iter()
+            .map(|(path, _, _)| directory_of(path, &commit.repo, multi_repo))
+            .collect();
+        for dir in touched {
+            directories.entry(dir).or_default().add(name, 1);
+        }
+    }
+    progress.emit("bus-factor", commits.len() as u64, commits.len() as u64, None);
+    directories
+}
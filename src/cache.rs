@@ -0,0 +1,258 @@
+use crate::repo::RawCommit;
+use git2::Oid;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever `RawCommit` changes shape; older cache files are discarded.
+const CACHE_VERSION: u32 = 7;
+const CACHE_FILE: &str = "contrib-stats-cache.cbor";
+/// Stats appended by `--max-memory` runs, folded into `CACHE_FILE` by the next other run.
+const JOURNAL_FILE: &str = "contrib-stats-cache.journal";
+
+#[derive(Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    /// Commit stats per settings key, then per commit hash
+    sections: HashMap<String, HashMap<String, RawCommit>>,
+    /// Last walked commit list per revision (and first-parent mode), for `--offline`
+    walks: HashMap<String, Vec<String>>,
+}
+
+/// One change appended to the journal, after a leading `CACHE_VERSION`.
+#[derive(Serialize, Deserialize)]
+enum JournalEntry {
+    Commit { section: String, oid: String, raw: Box<RawCommit> },
+    Walk { key: String, ids: Vec<String> },
+}
+
+impl CacheFile {
+    fn load(path: &Path) -> Option<CacheFile> {
+        let file = File::open(path).ok()?;
+        ciborium::from_reader::<CacheFile, _>(BufReader::new(file))
+            .ok()
+            .filter(|f| f.version == CACHE_VERSION)
+    }
+
+    /// Applies the journal at `path`, only to `section` when given. Returns how many entries
+    /// it held and the length of their bytes: a run killed while appending leaves a
+    /// truncated last entry, which is ignored.
+    fn replay(&mut self, path: &Path, section: Option<&str>) -> (usize, u64) {
+        let Ok(file) = File::open(path) else {
+            return (0, 0);
+        };
+        let mut reader = BufReader::new(file);
+        if ciborium::from_reader::<u32, _>(&mut reader).ok() != Some(CACHE_VERSION) {
+            return (0, 0);
+        }
+        let mut count = 0;
+        let mut valid = reader.stream_position().unwrap_or(0);
+        while let Ok(entry) = ciborium::from_reader::<JournalEntry, _>(&mut reader) {
+            match entry {
+                JournalEntry::Commit { section: key, oid, raw } => {
+                    if section.is_none_or(|section| section == key) {
+                        self.sections.entry(key).or_default().insert(oid, *raw);
+                    }
+                }
+                JournalEntry::Walk { key, ids } => {
+                    self.walks.insert(key, ids);
+                }
+            }
+            count += 1;
+            valid = reader.stream_position().unwrap_or(valid);
+        }
+        (count, valid)
+    }
+}
+
+/// Per-commit stats persisted in the git directory, so repeated runs only diff new commits.
+/// Entries depend on path filters and first-parent mode, which form the section key;
+/// identity resolution happens after lookup, so `.mailmap` edits apply to cached commits.
+///
+/// A memory-bound cache (`--max-memory`) keeps only its own section, appends new stats to
+/// the journal instead of holding them, and hands cached stats over instead of copying
+/// them. It never rewrites the cache file; the next run without the budget does.
+pub struct StatsCache {
+    path: PathBuf,
+    journal_path: PathBuf,
+    key: String,
+    file: CacheFile,
+    dirty: bool,
+    /// Open journal of a memory-bound cache; `None` otherwise or when it can't be written
+    journal: Option<BufWriter<File>>,
+    bounded: bool,
+}
+
+impl StatsCache {
+    pub fn open(git_dir: &Path, key: String, bounded: bool) -> StatsCache {
+        let path = git_dir.join(CACHE_FILE);
+        let journal_path = git_dir.join(JOURNAL_FILE);
+        let mut file = CacheFile::load(&path).unwrap_or(CacheFile {
+            version: CACHE_VERSION,
+            ..CacheFile::default()
+        });
+        let journal = if bounded {
+            file.sections.retain(|section, _| *section == key);
+            let (_, valid) = file.replay(&journal_path, Some(&key));
+            open_journal(&journal_path, valid)
+                .map_err(|e| eprintln!("Warning: could not write stats cache {}: {}", journal_path.display(), e))
+                .ok()
+        } else {
+            file.replay(&journal_path, None);
+            None
+        };
+        StatsCache {
+            // Fold a journal left by a memory-bound run into the cache file
+            dirty: !bounded && journal_path.exists(),
+            path,
+            journal_path,
+            key,
+            file,
+            journal,
+            bounded,
+        }
+    }
+
+    pub fn get(&self, oid: Oid) -> Option<&RawCommit> {
+        self.file.sections.get(&self.key)?.get(&oid.to_string())
+    }
+
+    pub fn insert(&mut self, oid: Oid, raw: RawCommit) {
+        if self.bounded {
+            let section = self.key.clone();
+            self.append(&JournalEntry::Commit { section, oid: oid.to_string(), raw: Box::new(raw) });
+            return;
+        }
+        let section = self.file.sections.entry(self.key.clone()).or_default();
+        section.insert(oid.to_string(), raw);
+        self.dirty = true;
+    }
+
+    /// The cached stats of `oid`, moved out of a memory-bound cache rather than copied.
+    pub fn take(&mut self, oid: Oid) -> Option<RawCommit> {
+        if self.bounded {
+            self.file.sections.get_mut(&self.key)?.remove(&oid.to_string())
+        } else {
+            self.get(oid).cloned()
+        }
+    }
+
+    /// The commit list recorded for `walk_key` by the last online run.
+    pub fn walk(&self, walk_key: &str) -> Option<Vec<Oid>> {
+        let ids = self.file.walks.get(walk_key)?;
+        Some(ids.iter().filter_map(|id| Oid::from_str(id).ok()).collect())
+    }
+
+    pub fn record_walk(&mut self, walk_key: &str, ids: &[Oid]) {
+        let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+        if self.file.walks.get(walk_key) == Some(&ids) {
+            return;
+        }
+        if self.bounded {
+            self.append(&JournalEntry::Walk { key: walk_key.to_string(), ids: ids.clone() });
+        } else {
+            self.dirty = true;
+        }
+        self.file.walks.insert(walk_key.to_string(), ids);
+    }
+
+    fn append(&mut self, entry: &JournalEntry) {
+        let Some(journal) = &mut self.journal else {
+            return;
+        };
+        if let Err(e) = ciborium::into_writer(entry, journal) {
+            eprintln!("Warning: could not write stats cache {}: {}", self.journal_path.display(), e);
+            self.journal = None;
+        }
+    }
+
+    /// Writes the cache back if anything changed since the last save, or flushes the
+    /// journal of a memory-bound cache. Failures only cost speed, so they are reported as
+    /// warnings.
+    pub fn save(&mut self) {
+        if let Some(journal) = &mut self.journal {
+            if let Err(e) = journal.flush() {
+                eprintln!("Warning: could not write stats cache {}: {}", self.journal_path.display(), e);
+            }
+        }
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+        let tmp = self.path.with_extension("cbor.tmp");
+        let result = File::create(&tmp)
+            .map_err(|e| e.to_string())
+            .and_then(|f| ciborium::into_writer(&self.file, BufWriter::new(f)).map_err(|e| e.to_string()))
+            .and_then(|_| fs::rename(&tmp, &self.path).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => {
+                let _ = fs::remove_file(&self.journal_path);
+            }
+            Err(e) => eprintln!("Warning: could not write stats cache {}: {}", self.path.display(), e),
+        }
+    }
+}
+
+/// Opens the journal for appending after its first `valid` bytes, starting it with
+/// `CACHE_VERSION` when there are none.
+fn open_journal(path: &Path, valid: u64) -> std::io::Result<BufWriter<File>> {
+    let mut file = OpenOptions::new().create(true).write(true).truncate(false).open(path)?;
+    file.set_len(valid)?;
+    file.seek(SeekFrom::End(0))?;
+    let mut journal = BufWriter::new(file);
+    if valid == 0 {
+        ciborium::into_writer(&CACHE_VERSION, &mut journal).map_err(|e| std::io::Error::other(e.to_string()))?;
+    }
+    Ok(journal)
+}
+
+/// Prints what the cache in `git_dir` holds, for `cache info`.
+pub fn print_cache_info(git_dir: &Path) {
+    let path = git_dir.join(CACHE_FILE);
+    let journal = git_dir.join(JOURNAL_FILE);
+    println!("Cache: {}", path.display());
+    let sizes: Vec<u64> = [&path, &journal].iter().filter_map(|p| fs::metadata(p).ok()).map(|m| m.len()).collect();
+    if sizes.is_empty() {
+        println!("  (none)");
+        return;
+    }
+    println!("  Size: {} KiB", sizes.iter().sum::<u64>().div_ceil(1024));
+    let mut file = if path.exists() {
+        let Some(file) = CacheFile::load(&path) else {
+            println!("  Unreadable or from an older version; it will be rebuilt on the next run");
+            return;
+        };
+        file
+    } else {
+        CacheFile::default()
+    };
+    let (journaled, _) = file.replay(&journal, None);
+    if journaled > 0 {
+        println!("  {} entries appended by --max-memory runs, folded in by the next other run", journaled);
+    }
+    let mut sections: Vec<(&String, usize)> = file.sections.iter().map(|(k, v)| (k, v.len())).collect();
+    sections.sort();
+    for (key, count) in sections {
+        println!("  {} commits for {}", count, key);
+    }
+    let mut walks: Vec<(&String, usize)> = file.walks.iter().map(|(k, v)| (k, v.len())).collect();
+    walks.sort();
+    for (key, count) in walks {
+        println!("  {} commits walked from {}", count, key);
+    }
+}
+
+/// Deletes the cache in `git_dir` and its journal, returning whether there was one.
+pub fn clear_cache(git_dir: &Path) -> std::io::Result<bool> {
+    let mut found = false;
+    for file in [CACHE_FILE, JOURNAL_FILE] {
+        match fs::remove_file(git_dir.join(file)) {
+            Ok(()) => found = true,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(found)
+}
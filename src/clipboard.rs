@@ -0,0 +1,123 @@
+use crate::error::Error;
+use crate::{Args, Commands};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Set on the run `--copy` starts, whose output it captures.
+const COPY_RUN_ENV: &str = "GIT_CONTRIB_STATS_COPY_RUN";
+
+/// Whether this process is the run started by `--copy`, rather than the one copying.
+pub fn is_copy_run() -> bool {
+    std::env::var_os(COPY_RUN_ENV).is_some()
+}
+
+/// Clipboard tools to try in order, with their arguments.
+fn clipboard_commands() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        vec![("pbcopy", &[])]
+    } else if cfg!(target_os = "windows") {
+        vec![("clip", &[])]
+    } else {
+        let mut commands: Vec<(&str, &[&str])> = vec![("xclip", &["-selection", "clipboard"]), ("xsel", &["--clipboard", "--input"])];
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            commands.insert(0, ("wl-copy", &[]));
+        }
+        commands
+    }
+}
+
+/// Hands `text` to the first clipboard tool that runs, returning its name.
+fn copy_to_clipboard(text: &str) -> Option<&'static str> {
+    clipboard_commands().into_iter().find_map(|(program, args)| {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let written = child.stdin.take()?.write_all(text.as_bytes()).is_ok();
+        (child.wait().ok()?.success() && written).then_some(program)
+    })
+}
+
+/// Splits one CSV record as `csv_field` writes them: quoted fields may hold commas and
+/// doubled quotes.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().expect("one field at least").push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().expect("one field at least").push(c),
+        }
+    }
+    fields
+}
+
+/// What `--copy` puts on the clipboard from a run's output: the Markdown table alone
+/// (from its first row to its last) for the table format, the CSV as TSV so it pastes
+/// into spreadsheet cells, and the whole output otherwise.
+fn clipboard_text(format: &str, output: &str) -> String {
+    match format {
+        "table" => {
+            let lines: Vec<&str> = output.lines().collect();
+            let Some(first) = lines.iter().position(|l| l.starts_with('|')) else {
+                return output.to_string();
+            };
+            let last = lines.iter().rposition(|l| l.starts_with('|')).unwrap_or(first);
+            // Markdown allows one separator per table, under its header row; the one above
+            // TOTAL would render as a row of dashes
+            let is_row = |i: usize| i < lines.len() && lines[i].starts_with('|');
+            (first..=last)
+                .filter(|&i| {
+                    let separator = is_row(i) && lines[i].chars().all(|c| matches!(c, '|' | '-' | ':'));
+                    !separator || (i >= 1 && is_row(i - 1) && (i < 2 || !is_row(i - 2)))
+                })
+                .map(|i| format!("{}\n", lines[i]))
+                .collect()
+        }
+        "csv" => output
+            .lines()
+            .map(|line| {
+                let fields: Vec<String> = csv_fields(line).iter().map(|f| f.replace(['\t', '\r', '\n'], " ")).collect();
+                format!("{}\n", fields.join("\t"))
+            })
+            .collect(),
+        _ => output.to_string(),
+    }
+}
+
+/// Runs this command again with its output captured, prints that output and puts it on
+/// the system clipboard as well (see `clipboard_text`), then exits with the run's status.
+/// Nothing is copied when the run fails.
+pub fn copy_output(args: &Args) -> ! {
+    if matches!(args.command, Some(Commands::Serve { .. } | Commands::Cache { .. })) {
+        Error::Usage("--copy can't be combined with serve or cache".to_string()).exit();
+    }
+    let exe = std::env::current_exe().unwrap_or_else(|e| Error::Usage(format!("could not locate this executable: {}", e)).exit());
+    let output = Command::new(&exe)
+        .args(std::env::args_os().skip(1))
+        .env(COPY_RUN_ENV, "1")
+        .stderr(Stdio::inherit())
+        .output()
+        .unwrap_or_else(|e| Error::Usage(format!("could not run {}: {}", exe.display(), e)).exit());
+    let _ = std::io::stdout().write_all(&output.stdout);
+    if output.status.success() {
+        let text = clipboard_text(args.format.as_deref().unwrap_or("table"), &String::from_utf8_lossy(&output.stdout));
+        match copy_to_clipboard(&text) {
+            Some(program) => eprintln!("Copied {} lines to the clipboard ({})", text.lines().count(), program),
+            None => eprintln!(
+                "Warning: could not copy to the clipboard; install {}",
+                clipboard_commands().iter().map(|(program, _)| *program).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+    std::process::exit(output.status.code().unwrap_or(1))
+}
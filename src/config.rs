@@ -0,0 +1,203 @@
+use crate::error::{Error, OrExit};
+use crate::i18n::LANGUAGES;
+use crate::period::PERIODS;
+use crate::repo::Repo;
+use crate::velocity::{valid_holiday, ContributorMeta};
+use crate::Args;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the per-repository config file, looked up at the repository root.
+pub const REPO_CONFIG: &str = ".git-contrib-stats.toml";
+
+/// Defaults for command-line options, from `.git-contrib-stats.toml` at the repository
+/// root or `~/.config/git-contrib-stats/config.toml`. Keys are named like the options.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct Config {
+    branch: Option<String>,
+    merge: Vec<String>,
+    merge_fold_case: bool,
+    credit: Option<String>,
+    exclude_author: Vec<String>,
+    exclude_bots: bool,
+    path: Vec<String>,
+    exclude_path: Vec<String>,
+    no_merges: bool,
+    first_parent: bool,
+    sort: Option<String>,
+    lines_metric: Option<String>,
+    week_start: Option<String>,
+    lang: Option<String>,
+    theme: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    period: Option<String>,
+    top: Option<usize>,
+    format: Option<String>,
+    holidays: Vec<String>,
+    /// Availability per contributor name (after `--merge`), for `--velocity`
+    contributors: HashMap<String, ContributorMeta>,
+    /// Named sets of settings under `[profile.NAME]`, applied with `--profile NAME`
+    profile: HashMap<String, Config>,
+}
+
+impl Config {
+    fn load(path: &Path) -> Option<Config> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let config: Config = toml::from_str(&content)
+            .map_err(|e| Error::InvalidFile {
+                kind: "config file",
+                path: path.display().to_string(),
+                message: e.to_string(),
+            })
+            .or_exit();
+        config.validate(path, "");
+        for (name, profile) in &config.profile {
+            if !profile.profile.is_empty() {
+                invalid(path, format!("profile.{}: profiles cannot contain profiles", name));
+            }
+            profile.validate(path, &format!("profile.{}.", name));
+        }
+        Some(config)
+    }
+
+    /// Exits on a setting with no valid value; `prefix` locates the keys of a profile.
+    fn validate(&self, path: &Path, prefix: &str) {
+        let check = |key: &str, value: &Option<String>, allowed: &[&str]| {
+            if let Some(value) = value.as_deref().filter(|v| !allowed.contains(v)) {
+                Error::Usage(format!(
+                    "invalid {}{} '{}' in {} (expected one of: {})",
+                    prefix,
+                    key,
+                    value,
+                    path.display(),
+                    allowed.join(", ")
+                ))
+                .exit();
+            }
+        };
+        check("credit", &self.credit, &["author", "committer", "both"]);
+        check("sort", &self.sort, &["commits", "lines", "net", "files", "score"]);
+        check("lines-metric", &self.lines_metric, &["added", "deleted", "sum", "net"]);
+        check("week-start", &self.week_start, &["monday", "sunday"]);
+        check("lang", &self.lang, &LANGUAGES);
+        check("theme", &self.theme, &["dark", "light", "auto"]);
+        check("format", &self.format, &["table", "json", "csv", "digest"]);
+        check("period", &self.period, &PERIODS);
+        if self.period.is_some() && (self.since.is_some() || self.until.is_some()) {
+            invalid(path, format!("{}period cannot be combined with since or until", prefix));
+        }
+        if let Some(holiday) = self.holidays.iter().find(|h| !valid_holiday(h)) {
+            invalid(path, format!("{}holiday '{}' is not a YYYY-MM-DD date", prefix, holiday));
+        }
+        for (name, meta) in &self.contributors {
+            if let Err(message) = meta.validate() {
+                invalid(path, format!("{}contributors.\"{}\": {}", prefix, name, message));
+            }
+        }
+    }
+}
+
+fn invalid(path: &Path, message: String) -> ! {
+    Error::InvalidFile {
+        kind: "config file",
+        path: path.display().to_string(),
+        message,
+    }
+    .exit()
+}
+
+/// `$XDG_CONFIG_HOME/git-contrib-stats/config.toml`, falling back to `~/.config`.
+fn user_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("git-contrib-stats").join("config.toml"))
+}
+
+/// Fills in options not given on the command line from the config of the repository at
+/// `repo_path`, then from the user's config. With `--profile`, the settings of that
+/// `[profile.NAME]` table come first. `merge` and `exclude-author` entries add up across
+/// files, profiles and the command line; other settings come from the most specific source.
+pub fn apply_config(args: &mut Args, repo_path: &Path) {
+    let repo_config_path = Repo::discover(repo_path, false).workdir().map(|root| root.join(REPO_CONFIG));
+    let paths: Vec<PathBuf> = repo_config_path.into_iter().chain(user_config_path()).collect();
+    let mut configs: Vec<Config> = paths.iter().filter_map(|path| Config::load(path)).collect();
+
+    if let Some(name) = args.profile.clone() {
+        let profiles: Vec<Config> = configs.iter_mut().filter_map(|config| config.profile.remove(&name)).collect();
+        if profiles.is_empty() {
+            let mut known: Vec<&str> = configs.iter().flat_map(|config| config.profile.keys()).map(String::as_str).collect();
+            known.sort_unstable();
+            known.dedup();
+            let searched: Vec<String> = paths.iter().map(|path| path.display().to_string()).collect();
+            let message = if known.is_empty() {
+                format!("no profile '{}': no [profile.*] table in {}", name, searched.join(" or "))
+            } else {
+                format!("no profile '{}' (defined: {})", name, known.join(", "))
+            };
+            Error::Usage(message).exit();
+        }
+        configs.splice(0..0, profiles);
+    }
+
+    for config in configs {
+        apply(args, config);
+    }
+}
+
+/// Fills in the options still unset from one config file or profile.
+fn apply(args: &mut Args, config: Config) {
+    if args.branch.is_empty() && args.range.is_empty() && args.revspec.is_none() {
+        args.branch.extend(config.branch);
+    }
+    args.merge.extend(config.merge);
+    args.merge_fold_case |= config.merge_fold_case;
+    if args.credit.is_none() {
+        args.credit = config.credit;
+    }
+    args.exclude_author.extend(config.exclude_author);
+    args.exclude_bots |= config.exclude_bots;
+    if args.path.is_empty() {
+        args.path = config.path;
+    }
+    if args.exclude_path.is_empty() {
+        args.exclude_path = config.exclude_path;
+    }
+    args.no_merges |= config.no_merges;
+    args.first_parent |= config.first_parent;
+    if args.sort.is_none() {
+        args.sort = config.sort;
+    }
+    if args.lines_metric.is_none() {
+        args.lines_metric = config.lines_metric;
+    }
+    if args.week_start.is_none() {
+        args.week_start = config.week_start;
+    }
+    if args.lang.is_none() {
+        args.lang = config.lang;
+    }
+    if args.theme.is_none() {
+        args.theme = config.theme;
+    }
+    // The date range is taken whole, from the first source that sets one
+    if args.since.is_none() && args.until.is_none() && args.period.is_none() {
+        args.since = config.since;
+        args.until = config.until;
+        args.period = config.period;
+    }
+    if args.top.is_none() {
+        args.top = config.top;
+    }
+    if args.format.is_none() {
+        args.format = config.format;
+    }
+    args.holidays.extend(config.holidays);
+    for (name, meta) in config.contributors {
+        args.contributors.entry(name).or_insert(meta);
+    }
+}
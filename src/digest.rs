@@ -0,0 +1,144 @@
+use crate::error::OrExit;
+use crate::period::{change, PreviousPeriod};
+use crate::repo::parse_date;
+use crate::{
+    canonical_name, folded_count, get_commits_by_date, is_others, load_commits, revision_label, Args, CommitRecord, ContributorStats,
+    LinesMetric, Progress, Source,
+};
+use chrono::{Local, NaiveDate, TimeZone};
+use std::collections::{HashMap, HashSet};
+
+/// "1 commit", "2 commits".
+fn count(n: u64, noun: &str) -> String {
+    format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" })
+}
+
+/// "commits +25%", "commits unchanged" or "commits up from 0".
+fn trend(what: &str, now: i64, before: i64) -> String {
+    match (now, before) {
+        (n, b) if n == b => format!("{} unchanged", what),
+        (_, 0) => format!("{} up from 0", what),
+        (n, b) => format!("{} {}", what, change(n, b)),
+    }
+}
+
+/// What the digest covers: "This week" for `--period this-week`, else the dates given.
+fn range_label(args: &Args) -> String {
+    match (args.period.as_deref(), args.since.as_deref(), args.until.as_deref()) {
+        (Some(period), _, _) => {
+            let text = period.replace('-', " ");
+            let mut chars = text.chars();
+            chars.next().map_or(text.clone(), |first| first.to_uppercase().chain(chars).collect())
+        }
+        (None, Some(since), Some(until)) => format!("{} to {}", since, until),
+        (None, Some(since), None) => format!("Since {}", since),
+        (None, None, Some(until)) => format!("Up to {}", until),
+        (None, None, None) => "All time".to_string(),
+    }
+}
+
+/// Contributors whose first commit ever is in the analyzed range, biggest first. The
+/// history before `--since` is scanned for this; without `--since` nobody is new.
+fn newcomers<'a>(
+    sources: &[Source],
+    args: &Args,
+    sorted_stats: &[(&'a String, &ContributorStats)],
+    author_mapping: &HashMap<String, String>,
+    progress: &Progress,
+) -> Vec<&'a String> {
+    let Some(since) = args.since.as_deref().and_then(|since| parse_date(since, false)) else {
+        return Vec::new();
+    };
+    let Some(day_before) = Local.timestamp_opt(since - 1, 0).single().map(|t| t.date_naive().to_string()) else {
+        return Vec::new();
+    };
+    let earlier = load_commits(sources, args, None, Some(day_before), progress).or_exit();
+    let seen: HashSet<&String> = earlier.iter().map(|c| canonical_name(&c.author, author_mapping)).collect();
+    sorted_stats
+        .iter()
+        .map(|(name, _)| *name)
+        .filter(|name| !is_others(args, name) && !seen.contains(name))
+        .collect()
+}
+
+/// Prints a few plain sentences on the analyzed range for a standup or newsletter: totals
+/// and their change from the previous period, the top contributor, the biggest riser by
+/// commits, first-time contributors and the busiest day.
+pub fn print_digest(
+    sources: &[Source],
+    args: &Args,
+    commits: &[CommitRecord],
+    sorted_stats: &[(&String, &ContributorStats)],
+    author_mapping: &HashMap<String, String>,
+    previous: Option<&PreviousPeriod>,
+    progress: &Progress,
+) {
+    let metric = LinesMetric::from_args(args);
+    let mut label = range_label(args);
+    if args.period.is_some() {
+        if let (Some(since), Some(until)) = (&args.since, &args.until) {
+            label = format!("{} ({} to {})", label, since, until);
+        }
+    }
+    let people: usize = sorted_stats.iter().map(|(name, _)| folded_count(args, name)).sum();
+    let total_commits = commits.len() as u64;
+    let total_lines: i64 = sorted_stats.iter().map(|(_, stats)| metric.of(stats)).sum();
+    if commits.is_empty() {
+        println!("{} on {}: no commits", label, revision_label(sources));
+    } else {
+        println!(
+            "{} on {}: {} by {}, {} {}",
+            label,
+            revision_label(sources),
+            count(total_commits, "commit"),
+            if people == 1 { "1 person".to_string() } else { format!("{} people", people) },
+            total_lines,
+            metric.label()
+        );
+    }
+    if let Some(previous) = previous {
+        let previous_commits: u64 = previous.stats.values().map(|s| s.commits).sum();
+        let previous_lines: i64 = previous.stats.values().map(|s| metric.of(s)).sum();
+        println!(
+            "Compared with {} to {} ({}, {} {}): {}, {}",
+            previous.since,
+            previous.until,
+            count(previous_commits, "commit"),
+            previous_lines,
+            metric.label(),
+            trend("commits", total_commits as i64, previous_commits as i64),
+            trend(metric.label(), total_lines, previous_lines)
+        );
+    }
+    if commits.is_empty() {
+        return;
+    }
+
+    let ranked: Vec<(&String, &ContributorStats)> = sorted_stats.iter().copied().filter(|(name, _)| !is_others(args, name)).collect();
+    if let Some((name, stats)) = ranked.first() {
+        println!("Top contributor: {}, {} and {} {}", name, count(stats.commits, "commit"), metric.of(stats), metric.label());
+    }
+    if let Some(previous) = previous {
+        let mover = ranked
+            .iter()
+            .filter_map(|(name, stats)| {
+                let before = previous.stats.get(*name).map_or(0, |s| s.commits);
+                (before > 0 && stats.commits > before).then_some((*name, stats.commits, before))
+            })
+            .max_by(|a, b| (a.1 * b.2).cmp(&(b.1 * a.2)).then_with(|| a.1.cmp(&b.1)));
+        if let Some((name, now, before)) = mover {
+            println!("Top mover: {}, {} commits ({} to {})", name, change(now as i64, before as i64), before, now);
+        }
+    }
+    let new = newcomers(sources, args, sorted_stats, author_mapping, progress);
+    if !new.is_empty() {
+        let names: Vec<&str> = new.iter().map(|name| name.as_str()).collect();
+        println!("New contributor{}: {}", if names.len() == 1 { "" } else { "s" }, names.join(", "));
+    }
+    // Reversed so ties go to the earliest day
+    let by_day = get_commits_by_date(commits);
+    if let Some((date, commits)) = by_day.iter().rev().max_by_key(|(_, c)| **c) {
+        let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").map(|d| d.format("%A ").to_string()).unwrap_or_default();
+        println!("Busiest day: {}{}, {}", day, date, count(*commits, "commit"));
+    }
+}
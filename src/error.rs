@@ -0,0 +1,58 @@
+use thiserror::Error;
+
+/// Exit status when `--fail-on-violation` finds commits breaking the policy.
+pub const EXIT_VIOLATIONS: i32 = 1;
+
+/// Failures reported to the user. Each kind exits with its own status so scripts can
+/// tell a bad invocation from a missing repository or a failed write.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("invalid {option} date '{value}' (expected YYYY-MM-DD, YYYY-MM-DD HH:MM, RFC 3339 or 'N days ago')")]
+    InvalidDate { option: &'static str, value: String },
+    #[error("{0}")]
+    Usage(String),
+    #[error("invalid {kind} {path}: {message}")]
+    InvalidFile { kind: &'static str, path: String, message: String },
+    #[error("{path} is not a git repository (or any of the parent directories); pass -C <DIR> to analyze another one")]
+    NotARepository { path: String },
+    #[error("unknown revision '{rev}' in {repo}; check the branch, tag or range name")]
+    UnknownRevision { rev: String, repo: String },
+    #[error("could not read {path}: {source}")]
+    Read { path: String, source: std::io::Error },
+    #[error("could not write {path}: {source}")]
+    Write { path: String, source: std::io::Error },
+    #[error("could not listen on {addr}: {message}")]
+    Listen { addr: String, message: String },
+    #[error("git: {}", .0.message())]
+    Git(#[from] git2::Error),
+}
+
+impl Error {
+    /// Process exit status for this kind of failure (2 for usage errors, like clap's own).
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::InvalidDate { .. } | Error::Usage(_) | Error::InvalidFile { .. } => 2,
+            Error::NotARepository { .. } => 3,
+            Error::UnknownRevision { .. } => 4,
+            Error::Read { .. } | Error::Write { .. } | Error::Listen { .. } => 5,
+            Error::Git(_) => 6,
+        }
+    }
+
+    /// Prints the error and exits with its status.
+    pub fn exit(self) -> ! {
+        eprintln!("Error: {}", self);
+        std::process::exit(self.exit_code())
+    }
+}
+
+pub trait OrExit<T> {
+    /// Unwraps the value, or reports the error and exits with its status.
+    fn or_exit(self) -> T;
+}
+
+impl<T, E: Into<Error>> OrExit<T> for Result<T, E> {
+    fn or_exit(self) -> T {
+        self.unwrap_or_else(|e| e.into().exit())
+    }
+}
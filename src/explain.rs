@@ -0,0 +1,217 @@
+use crate::error::{Error, OrExit};
+use crate::labels::IssueLabels;
+use crate::repo::{parse_option_date, CommitRecord, PathFilter, Repo};
+use crate::{
+    author_matches, canonical_name, contributor_stats, fold_others, load_commits, revision_label, Args, ContributorStats, Progress, Roster,
+    Source, BOT_PATTERNS,
+};
+use git2::Oid;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Whether `commit` belongs to the contributor being explained: by name after `--merge`,
+/// or by one of the emails their counted commits use.
+fn is_theirs(commit: &CommitRecord, name: &str, emails: &HashSet<String>, author_mapping: &HashMap<String, String>) -> bool {
+    canonical_name(&commit.author, author_mapping) == name || emails.contains(&commit.email)
+}
+
+/// The commit's id and the repository it was read from.
+fn locate<'a>(commit: &CommitRecord, sources: &'a [Source]) -> Option<(Oid, &'a Repo)> {
+    let source = sources.iter().find(|s| s.repo.name() == commit.repo)?;
+    Some((Oid::from_str(&commit.hash).ok()?, &source.repo))
+}
+
+/// The rule that left out a commit found in the unfiltered history, checked in the order
+/// the pipeline applies them.
+fn exclusion_reason(commit: &CommitRecord, sources: &[Source], args: &Args, paths: &PathFilter) -> String {
+    if commit.is_merge && args.no_merges {
+        return "merge commit (--no-merges)".to_string();
+    }
+    let path_filtered = !args.path.is_empty() || !args.exclude_path.is_empty() || !args.ext.is_empty();
+    if path_filtered && locate(commit, sources).is_some_and(|(oid, repo)| repo.raw_commit(oid, paths, args.first_parent).or_exit().files_changed == 0) {
+        return "touches no file matching --path/--exclude-path/--ext".to_string();
+    }
+    if args.exclude_bots && BOT_PATTERNS.iter().any(|pattern| author_matches(commit, pattern)) {
+        return "bot account (--exclude-bots)".to_string();
+    }
+    if let Some(pattern) = args.exclude_author.iter().find(|pattern| author_matches(commit, pattern)) {
+        return format!("matches --exclude-author '{}'", pattern);
+    }
+    if let Some(path) = &args.authors_file {
+        if !args.fold_external && !Roster::load(path).or_exit().contains(commit) {
+            return format!("not listed in --authors-file {}", path);
+        }
+    }
+    if let (Some(path), false) = (&args.issue_labels, args.label.is_empty()) {
+        let wanted: HashSet<String> = args.label.iter().map(|l| l.to_lowercase()).collect();
+        if !IssueLabels::load(path).or_exit().matches(commit, &wanted) {
+            return format!("references no issue labelled {}", args.label.join(", "));
+        }
+    }
+    if args.exclude_imports {
+        return "part of a burst of commits that looks like an import (--exclude-imports)".to_string();
+    }
+    "left out by another filter".to_string()
+}
+
+/// Why a counted commit contributes fewer lines than it changes, if it does.
+fn lines_note(counted: &CommitRecord, full: Option<&CommitRecord>, args: &Args) -> Option<String> {
+    let full = full?;
+    let (lines, all) = (counted.lines_added + counted.lines_deleted, full.lines_added + full.lines_deleted);
+    if lines == all {
+        return None;
+    }
+    if lines == 0 && counted.is_root && args.exclude_initial {
+        return Some("lines dropped: initial commit (--exclude-initial)".to_string());
+    }
+    if lines == 0 && args.exclude_line_endings {
+        return Some("lines dropped: mostly line-ending changes (--exclude-line-endings)".to_string());
+    }
+    if lines == 0 && args.detect_format_only {
+        return Some("lines dropped: mostly reformatting (--detect-format-only)".to_string());
+    }
+    Some(format!("{} of {} lines are in files matching the path filters", lines, all))
+}
+
+fn print_commit(commit: &CommitRecord, sources: &[Source], detail: &str) {
+    let summary = locate(commit, sources).map(|(oid, repo)| repo.summary(oid)).unwrap_or_default();
+    let repo = if sources.len() > 1 { format!("{}  ", commit.repo) } else { String::new() };
+    println!("  {}  {}  {}{}  {}", &commit.hash[..commit.hash.len().min(10)], commit.date, repo, detail, summary);
+}
+
+/// `--explain`: lists the commits counted for one contributor with the lines each adds to
+/// their totals, and the commits of theirs left out with the rule responsible.
+/// `commits` are the analyzed commits; the history is scanned again without the filters
+/// to find the rest.
+pub fn print_explanation(
+    sources: &[Source],
+    args: &Args,
+    commits: &[CommitRecord],
+    author_mapping: &HashMap<String, String>,
+    name: &str,
+    progress: &Progress,
+) {
+    let mut unfiltered_args = args.clone();
+    unfiltered_args.no_merges = false;
+    unfiltered_args.path.clear();
+    unfiltered_args.exclude_path.clear();
+    unfiltered_args.ext.clear();
+    unfiltered_args.exclude_bots = false;
+    unfiltered_args.exclude_author.clear();
+    unfiltered_args.authors_file = None;
+    unfiltered_args.label.clear();
+    let history = load_commits(sources, &unfiltered_args, None, None, progress).or_exit();
+
+    // Accept the contributor's name in any case, or one of their emails
+    let wanted = name.to_lowercase();
+    let Some(name) = commits
+        .iter()
+        .chain(&history)
+        .find(|c| canonical_name(&c.author, author_mapping).to_lowercase() == wanted || c.email == wanted)
+        .map(|c| canonical_name(&c.author, author_mapping).clone())
+    else {
+        Error::Usage(format!("no commits by '{}' in {}", name, revision_label(sources))).exit()
+    };
+    let mut emails: HashSet<String> = commits
+        .iter()
+        .filter(|c| canonical_name(&c.author, author_mapping) == &name)
+        .map(|c| c.email.clone())
+        .filter(|email| !email.is_empty())
+        .collect();
+    if wanted.contains('@') {
+        emails.insert(wanted);
+    }
+
+    let counted: Vec<&CommitRecord> = commits.iter().filter(|c| canonical_name(&c.author, author_mapping) == &name).collect();
+    let counted_hashes: HashSet<&str> = counted.iter().map(|c| c.hash.as_str()).collect();
+    let theirs: Vec<&CommitRecord> = history.iter().filter(|c| is_theirs(c, &name, &emails, author_mapping)).collect();
+    let by_hash: HashMap<&str, &CommitRecord> = theirs.iter().map(|c| (c.hash.as_str(), *c)).collect();
+
+    println!("Explaining the numbers of {} in {}", name, revision_label(sources));
+    println!();
+    let identities: BTreeSet<String> = counted
+        .iter()
+        .copied()
+        .chain(theirs.iter().copied())
+        .map(|c| if c.email.is_empty() { c.author.clone() } else { format!("{} <{}>", c.author, c.email) })
+        .collect();
+    println!("Identities: {}", identities.into_iter().collect::<Vec<_>>().join(", "));
+    let aliases: BTreeSet<&str> = author_mapping
+        .iter()
+        .filter(|(alias, to)| **to == name && **alias != name)
+        .map(|(alias, _)| alias.as_str())
+        .collect();
+    if !aliases.is_empty() {
+        println!("Merged with --merge: {}", aliases.into_iter().collect::<Vec<_>>().join(", "));
+    }
+    if !args.no_mailmap {
+        println!("Names sharing an email are unified, and .mailmap is applied (--no-mailmap to disable)");
+    }
+    if !args.include_generated {
+        println!("Lines in generated or vendored files and .statsignore entries are not counted (--include-generated to count them)");
+    }
+
+    println!();
+    println!("Counted commits ({}):", counted.len());
+    for commit in &counted {
+        let mut detail = format!("{:>+7} {:>7}", commit.lines_added as i64, -(commit.lines_deleted as i64));
+        if commit.is_merge {
+            detail.push_str("  merge");
+        }
+        if let Some(weight) = commit.weight {
+            detail.push_str(&format!("  weight {}", weight));
+        }
+        if let Some(note) = lines_note(commit, by_hash.get(commit.hash.as_str()).copied(), args) {
+            detail.push_str(&format!("  ({})", note));
+        }
+        print_commit(commit, sources, &detail);
+    }
+
+    let since = args.since.as_deref().map(|s| parse_option_date("--since", s, false));
+    let until = args.until.as_deref().map(|u| parse_option_date("--until", u, true));
+    let (mut before, mut after) = (0, 0);
+    let paths = PathFilter::new(&args.path, &args.exclude_path, &args.ext, !args.include_generated).or_exit();
+    let mut excluded = Vec::new();
+    for commit in theirs.iter().filter(|c| !counted_hashes.contains(c.hash.as_str())) {
+        if since.is_some_and(|s| commit.committer_time < s) {
+            before += 1;
+        } else if until.is_some_and(|u| commit.committer_time > u) {
+            after += 1;
+        } else {
+            excluded.push((*commit, exclusion_reason(commit, sources, args, &paths)));
+        }
+    }
+    println!();
+    println!("Excluded commits ({}):", excluded.len());
+    for (commit, reason) in &excluded {
+        print_commit(commit, sources, reason);
+    }
+    if before > 0 {
+        println!("  {} earlier commits are before --since {}", before, args.since.as_deref().unwrap_or(""));
+    }
+    if after > 0 {
+        println!("  {} later commits are after --until {}", after, args.until.as_deref().unwrap_or(""));
+    }
+
+    let stats = contributor_stats(commits, author_mapping);
+    let none = ContributorStats::default();
+    let total = stats.get(&name).unwrap_or(&none);
+    println!();
+    println!(
+        "Total: {} commits, {} lines added, {} deleted ({} changed, {:+} net), {} files changed",
+        total.commits,
+        total.lines_added,
+        total.lines_deleted,
+        total.lines_added + total.lines_deleted,
+        total.net_lines,
+        total.files_changed
+    );
+    if let Some(weighted) = total.weighted_commits {
+        println!("Weighted commits: {}", weighted);
+    }
+    if let Some(top) = args.top {
+        let folded = fold_others(commits, author_mapping, args);
+        if let Some(others) = counted.first().map(|c| canonical_name(&c.author, &folded)).filter(|n| **n != name) {
+            println!("With --top {}, these are shown in the \"{}\" row", top, others);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::error::OrExit;
+use crate::repo::PathFilter;
+use crate::{canonical_name, Args, CommitRecord, LinesMetric, Progress, Source};
+use std::collections::HashMap;
+
+/// Commits and churn on one file, in total and per contributor.
+#[derive(Default)]
+struct FileActivity {
+    commits: u64,
+    added: u64,
+    deleted: u64,
+    /// `(commits, lines per --lines-metric)` per contributor
+    contributors: HashMap<String, (u64, i64)>,
+}
+
+/// Prints the `limit` files with the most commits (or lines with `--sort lines`) over
+/// `commits`, with their top three contributors. Files are prefixed with the repository
+/// name when several repositories are analyzed.
+pub fn print_hotspots(
+    sources: &[Source],
+    args: &Args,
+    commits: &[CommitRecord],
+    author_mapping: &HashMap<String, String>,
+    limit: usize,
+    progress: &Progress,
+) {
+    let repos: HashMap<&str, &Source> = sources.iter().map(|s| (s.repo.name(), s)).collect();
+    let paths = PathFilter::new(&args.path, &args.exclude_path, &args.ext, !args.include_generated).or_exit();
+    let multi_repo = sources.len() > 1;
+    let by_lines = args.sort.as_deref() == Some("lines");
+    let metric = LinesMetric::from_args(args);
+
+    let mut files: HashMap<String, FileActivity> = HashMap::new();
+    for (done, commit) in commits.iter().enumerate() {
+        progress.emit("hotspots", done as u64, commits.len() as u64, None);
+        let (Some(source), Ok(oid)) = (repos.get(commit.repo.as_str()), commit.hash.parse()) else {
+            continue;
+        };
+        let name = canonical_name(&commit.author, author_mapping);
+        for (path, added, deleted) in source.repo.file_stats(oid, &paths, args.first_parent).or_exit() {
+            let key = if multi_repo { format!("{}:{}", commit.repo, path) } else { path };
+            let file = files.entry(key).or_default();
+            file.commits += 1;
+            file.added += added;
+            file.deleted += deleted;
+            let contributor = file.contributors.entry(name.clone()).or_insert((0, 0));
+            contributor.0 += 1;
+            contributor.1 += metric.value(added, deleted);
+        }
+    }
+    progress.emit("hotspots", commits.len() as u64, commits.len() as u64, None);
+
+    let rank = |commits: u64, lines: i64| if by_lines { (lines, commits as i64) } else { (commits as i64, lines) };
+    let lines = |file: &FileActivity| metric.value(file.added, file.deleted);
+    let mut files: Vec<(String, FileActivity)> = files.into_iter().collect();
+    files.sort_by(|a, b| {
+        rank(b.1.commits, lines(&b.1))
+            .cmp(&rank(a.1.commits, lines(&a.1)))
+            .then_with(|| a.0.cmp(&b.0))
+    });
+    files.truncate(limit);
+
+    let path_width = files.iter().map(|(p, _)| p.len()).max().unwrap_or(4).max(4);
+    println!("Hotspots");
+    println!("────────");
+    println!(
+        "| {:<path_width$} | {:>8} | {:>10} | {:>10} | Top contributors",
+        "File", "Commits", "Added", "Deleted",
+        path_width = path_width
+    );
+    println!("|{:-<width$}|{:-<10}|{:-<12}|{:-<12}|{:-<18}", "", "", "", "", "", width = path_width + 2);
+    for (path, file) in &files {
+        let mut contributors: Vec<(&String, &(u64, i64))> = file.contributors.iter().collect();
+        contributors.sort_by(|a, b| rank(b.1 .0, b.1 .1).cmp(&rank(a.1 .0, a.1 .1)).then_with(|| a.0.cmp(b.0)));
+        let mut top: Vec<String> = contributors
+            .iter()
+            .take(3)
+            .map(|(name, (commits, lines))| if by_lines { format!("{} ({} lines)", name, lines) } else { format!("{} ({})", name, commits) })
+            .collect();
+        if contributors.len() > 3 {
+            top.push(format!("+{} more", contributors.len() - 3));
+        }
+        println!(
+            "| {:<path_width$} | {:>8} | {:>10} | {:>10} | {}",
+            path, file.commits, file.added, file.deleted, top.join(", "),
+            path_width = path_width
+        );
+    }
+}
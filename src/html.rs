@@ -0,0 +1,1298 @@
+use crate::repo::PathFilter;
+use crate::{
+    bucket_series, canonical_name, commits_by_contributor, contributor_ids, contributor_stats, fold_others, get_commits_by_date, get_lines_by_date, get_net_lines_by_date, granularity,
+    is_others, primary_identities, punchcard, ranked_contributors, open_repos, repo_breakdowns, revision_label, to_points, week_start, week_start_day, weekday_names, Args, CommitRecord, ContributorStats, DatePoint, LinesMetric, Progress,
+    Punchcard, Source, GRANULARITIES,
+};
+use crate::avatar::{avatar_url, github_org_avatars};
+use crate::error::{Error, OrExit};
+use crate::busfactor::{commit_owners, BusFactor};
+use crate::forge::ForgeLinks;
+use crate::metadata::ReportMetadata;
+use crate::overlap::{hour_overlap, HourOverlap};
+use crate::period::previous_period;
+use crate::template::render;
+use chrono::{Duration, NaiveDate, Weekday};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const REPORT_CSS: &str = include_str!("assets/report.css");
+const REPORT_JS: &str = include_str!("assets/report.js");
+const REPORT_BODY: &str = include_str!("assets/report.html");
+/// Page around the report markup; `--template` replaces it
+const REPORT_TEMPLATE: &str = include_str!("assets/report.template.html");
+
+/// FNV-1a, used instead of `DefaultHasher` because its output must not change between
+/// Rust releases: colors are expected to stay the same across reports.
+pub fn stable_hash(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Gray of the "Others" row and band, matching the `--others-below` band in the report script.
+pub const OTHERS_COLOR: &str = "#6e7681";
+
+/// Hex color derived from the contributor's identity rather than their rank, so the
+/// same person keeps the same color across reports and periods.
+pub fn contributor_color(identity: &str) -> String {
+    let hue = (stable_hash(&identity.to_lowercase()) % 360) as f64;
+    let (s, l) = (0.65, 0.60);
+    let c = (1.0 - (2.0 * l - 1.0_f64).abs()) * s;
+    let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let m = l - c / 2.0;
+    let (r, g, b) = match hue as u32 / 60 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let to_byte = |v: f64| ((v + m) * 255.0).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", to_byte(r), to_byte(g), to_byte(b))
+}
+
+/// Text color for initials on a `#rrggbb` background: white or the page's near-black,
+/// whichever has the higher WCAG contrast ratio.
+fn readable_text_color(background: &str) -> &'static str {
+    // WCAG relative luminance of a `#rrggbb` color
+    let luminance = |hex: &str| {
+        let channel = |i: usize| {
+            let c = u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0) as f64 / 255.0;
+            if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        };
+        0.2126 * channel(1) + 0.7152 * channel(3) + 0.0722 * channel(5)
+    };
+    let background = luminance(background);
+    let on_white = 1.05 / (background + 0.05);
+    let on_dark = (background + 0.05) / (luminance("#0d1117") + 0.05);
+    if on_white >= on_dark { "#ffffff" } else { "#0d1117" }
+}
+
+/// Avatar initials for each name, lengthened where two contributors would otherwise
+/// share the same letters ("JD" for both John Doe and Jane Doe becomes "JoD"/"JaD").
+fn avatar_initials(names: &[&str]) -> Vec<String> {
+    let words = |name: &str| -> Vec<Vec<char>> {
+        name.split_whitespace()
+            .map(|w| w.chars().filter(|c| c.is_alphanumeric()).collect::<Vec<char>>())
+            .filter(|w| !w.is_empty())
+            .collect()
+    };
+    let initials_with = |name: &str, first_len: usize| -> String {
+        let words = words(name);
+        let mut out = String::new();
+        for (i, word) in words.iter().enumerate() {
+            let take = if i == 0 { first_len } else { 1 };
+            out.extend(word.iter().take(take));
+        }
+        let mut chars = out.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => "?".to_string(),
+        }
+    };
+
+    let mut result: Vec<String> = names
+        .iter()
+        .map(|name| initials_with(name, 1).to_uppercase())
+        .collect();
+
+    for first_len in 2..=3 {
+        let mut counts: HashMap<&String, usize> = HashMap::new();
+        for initials in &result {
+            *counts.entry(initials).or_insert(0) += 1;
+        }
+        let colliding: Vec<bool> = result.iter().map(|i| counts[i] > 1).collect();
+        if !colliding.contains(&true) {
+            return result;
+        }
+        for (i, collides) in colliding.into_iter().enumerate() {
+            if collides {
+                result[i] = initials_with(names[i], first_len);
+            }
+        }
+    }
+
+    // Still ambiguous (e.g. identical first names): number the duplicates
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for initials in result.iter_mut() {
+        let n = seen.entry(initials.clone()).or_insert(0);
+        *n += 1;
+        if *n > 1 {
+            initials.push_str(&n.to_string());
+        }
+    }
+    result
+}
+
+/// Chart.js and its date adapter, in load order: the file `--inline-assets` expects and
+/// where to get it. The versions match `scripts/vendor-charts.sh`, so CDN, `--inline-assets`
+/// and `bundled-charts` reports run the same code.
+const CHART_ASSET_FILES: [(&str, &str); 2] = [
+    ("chart.umd.js", "https://cdn.jsdelivr.net/npm/chart.js@4.4.1/dist/chart.umd.js"),
+    (
+        "chartjs-adapter-date-fns.bundle.min.js",
+        "https://cdn.jsdelivr.net/npm/chartjs-adapter-date-fns@3.0.0/dist/chartjs-adapter-date-fns.bundle.min.js",
+    ),
+];
+
+#[cfg(all(feature = "bundled-charts", chart_assets_missing))]
+compile_error!(
+    "the bundled-charts feature embeds src/assets/chart.umd.js and src/assets/chartjs-adapter-date-fns.bundle.min.js; \
+     run scripts/vendor-charts.sh to fetch them"
+);
+
+/// Chart.js and its date adapter as fetched by `scripts/vendor-charts.sh`, inlined into every
+/// report by builds with the `bundled-charts` feature so they work without network access.
+#[cfg(all(feature = "bundled-charts", not(chart_assets_missing)))]
+const BUNDLED_CHART_SCRIPTS: Option<[&str; 2]> = Some([
+    include_str!("assets/chart.umd.js"),
+    include_str!("assets/chartjs-adapter-date-fns.bundle.min.js"),
+]);
+#[cfg(not(all(feature = "bundled-charts", not(chart_assets_missing))))]
+const BUNDLED_CHART_SCRIPTS: Option<[&str; 2]> = None;
+
+/// `<script>` tags loading Chart.js and its date adapter from jsDelivr.
+fn cdn_chart_scripts() -> String {
+    CHART_ASSET_FILES
+        .iter()
+        .map(|(_, url)| format!("<script src=\"{}\"></script>", url))
+        .collect::<Vec<_>>()
+        .join("\n    ")
+}
+
+/// `<script>` tags running `sources` in order.
+fn inline_scripts<'a>(sources: impl IntoIterator<Item = &'a str>) -> String {
+    sources
+        .into_iter()
+        // A literal "</script" inside the library would end the inline tag early
+        .map(|source| format!("<script>{}</script>", source.replace("</script", "<\\/script")))
+        .collect::<Vec<_>>()
+        .join("\n    ")
+}
+
+/// Reads local copies of Chart.js and its date adapter so the report works without network access.
+fn inline_chart_scripts(dir: &Path) -> std::io::Result<String> {
+    let mut sources = Vec::new();
+    for (file, url) in CHART_ASSET_FILES {
+        sources.push(std::fs::read_to_string(dir.join(file)).map_err(|e| {
+            std::io::Error::new(
+                e.kind(),
+                format!("cannot read {} ({}); download it from {}", dir.join(file).display(), e, url),
+            )
+        })?);
+    }
+    Ok(inline_scripts(sources.iter().map(String::as_str)))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportContributor<'a> {
+    name: &'a str,
+    id: String,
+    commits: u64,
+    empty_commits: u64,
+    added: u64,
+    deleted: u64,
+    /// Lines per `--lines-metric`
+    lines: i64,
+    color: String,
+    /// Initials color with enough contrast against `color`
+    avatar_text: &'static str,
+    initials: String,
+    /// Picture drawn over the initials, with `--avatars`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    avatar: Option<String>,
+    commit_series: Series,
+    line_series: Series<i64>,
+    /// Daily commit counts inside the calendar heatmap window
+    calendar: Vec<DatePoint<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    punchcard: Option<Punchcard>,
+    /// Profile (or commit list) on the repository's forge
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    /// Latest commits, when they can be linked to the forge
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    recent_commits: Vec<ReportCommit>,
+    /// Commits and lines in the period before `--period`, with `--with-previous`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_commits: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_lines: Option<i64>,
+    /// The contributor's own page, with `--html-dir`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page: Option<String>,
+    /// Totals named as in `--format json`, for the page's export buttons
+    stats: &'a ContributorStats,
+}
+
+/// Team totals of the period before `--period`, with `--with-previous`.
+#[derive(Serialize)]
+struct ReportPrevious {
+    since: String,
+    until: String,
+    commits: u64,
+    lines: i64,
+}
+
+#[derive(Serialize)]
+struct ReportCommit {
+    hash: String,
+    date: String,
+    url: String,
+}
+
+/// Commits linked from each contributor card.
+const LINKED_COMMITS: usize = 10;
+
+/// One time series per available granularity (`day`, `week`, ...), bucketed server-side.
+type Series<T = u64> = BTreeMap<&'static str, Vec<DatePoint<T>>>;
+
+fn series_by_granularity<T: Copy + Default + std::ops::AddAssign>(
+    daily: &BTreeMap<String, T>,
+    granularities: &[&'static str],
+    week_start: Weekday,
+) -> Series<T> {
+    granularities
+        .iter()
+        .map(|by| (*by, to_points(&bucket_series(daily, by, week_start, false))))
+        .collect()
+}
+
+/// Everything the report script needs; embedded in the page or written as `data.json`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportData<'a> {
+    branch: &'a str,
+    since: Option<&'a str>,
+    until: Option<&'a str>,
+    merges_included: bool,
+    first_parent: bool,
+    others_threshold: f64,
+    /// Fewest contributors with more than `bus_factor_threshold` percent of commits
+    bus_factor: Option<BusFactor>,
+    bus_factor_threshold: f64,
+    /// Bucket sizes offered by the period toggle, from `--by` upwards
+    granularities: &'static [&'static str],
+    total_commit_series: Series,
+    total_line_series: Series<i64>,
+    /// What the lines toggle shows, e.g. "lines changed"
+    lines_label: &'static str,
+    growth: Vec<DatePoint<i64>>,
+    /// First and last day of the 53-week contribution calendar, ending at the latest commit
+    calendar_start: Option<String>,
+    calendar_end: Option<String>,
+    contributors: Vec<ReportContributor<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repositories: Option<Vec<ReportRepository>>,
+    /// Team commits by weekday and hour, with `--punchcard`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    punchcard: Option<ReportPunchcard>,
+    /// Pairwise overlap of the biggest contributors' active hours, with `--hours-overlap`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hours_overlap: Option<HourOverlap>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous: Option<ReportPrevious>,
+    /// Release tags dated within the report, drawn on the main chart
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<ReportTag>,
+}
+
+#[derive(Serialize)]
+struct ReportTag {
+    /// Prefixed with the repository name when several are analyzed
+    name: String,
+    date: String,
+}
+
+/// Tags of the analyzed repositories dated between the first and last commit of the report.
+fn release_tags(args: &Args, commits: &[CommitRecord]) -> Result<Vec<ReportTag>, Error> {
+    let (Some(first), Some(last)) = (commits.iter().map(|c| &c.date).min(), commits.iter().map(|c| &c.date).max()) else {
+        return Ok(Vec::new());
+    };
+    let repos = open_repos(args, false)?;
+    let multi_repo = repos.len() > 1;
+    let mut tags = Vec::new();
+    for (_, repo) in &repos {
+        let prefix = if multi_repo { format!("{}:", repo.name()) } else { String::new() };
+        tags.extend(
+            repo.tags()
+                .into_iter()
+                .filter(|(_, date)| date >= first && date <= last)
+                .map(|(name, date)| ReportTag { name: format!("{}{}", prefix, name), date }),
+        );
+    }
+    tags.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.name.cmp(&b.name)));
+    Ok(tags)
+}
+
+#[derive(Serialize)]
+struct ReportPunchcard {
+    /// Row labels, starting at `--week-start`
+    weekdays: Vec<String>,
+    team: Punchcard,
+}
+
+/// Per-repository breakdown shown with `--per-repo`.
+#[derive(Serialize)]
+struct ReportRepository {
+    name: String,
+    contributors: Vec<ReportRepoContributor>,
+}
+
+#[derive(Serialize)]
+struct ReportRepoContributor {
+    name: String,
+    id: String,
+    commits: u64,
+    added: u64,
+    deleted: u64,
+}
+
+fn build_report_data<'a>(
+    args: &'a Args,
+    branch: &'a str,
+    commits: &[CommitRecord],
+    sorted_stats: &[(&'a String, &'a ContributorStats)],
+    author_mapping: &HashMap<String, String>,
+    progress: &Progress,
+) -> Result<ReportData<'a>, Error> {
+    let finest = GRANULARITIES.iter().position(|g| *g == granularity(args)).unwrap_or(0);
+    let granularities = &GRANULARITIES[finest..];
+    let week_start = week_start_day(args);
+    let metric = LinesMetric::from_args(args);
+
+    let names: Vec<&str> = sorted_stats.iter().map(|(name, _)| name.as_str()).collect();
+    let initials = avatar_initials(&names);
+
+    // Like a GitHub profile: 53 week columns, the last one holding the latest commit
+    let calendar_end = commits
+        .iter()
+        .filter_map(|c| NaiveDate::parse_from_str(&c.date, "%Y-%m-%d").ok())
+        .max();
+    let calendar_start = calendar_end.map(|end| crate::week_start(end - Duration::weeks(52), week_start));
+    let in_calendar = |date: &String| calendar_start.is_some_and(|start| *date >= start.to_string());
+
+    // Grouped once up front; filtering the whole history per contributor is quadratic
+    let by_contributor = commits_by_contributor(commits, author_mapping);
+    let mut ids = contributor_ids(commits, author_mapping, args);
+    let identities = primary_identities(commits, author_mapping);
+    let links = ForgeLinks::load(args)?;
+    let github_avatars = match &args.github_org {
+        Some(org) if args.avatars => {
+            // Each contributor's latest commit tells GitHub who they are
+            let latest: Vec<(&str, &str, &str)> = sorted_stats
+                .iter()
+                .filter(|(name, _)| !is_others(args, name))
+                .filter_map(|(name, _)| {
+                    let commit = by_contributor.get(*name)?.iter().max_by(|a, b| a.date.cmp(&b.date).then(a.committer_time.cmp(&b.committer_time)))?;
+                    Some((name.as_str(), commit.repo.as_str(), commit.hash.as_str()))
+                })
+                .collect();
+            github_org_avatars(org, &links, &latest)
+        }
+        _ => HashMap::new(),
+    };
+    let previous = previous_period(args, author_mapping, progress)?;
+    let before = |name: &str| previous.as_ref().map(|p| p.stats.get(name).copied().unwrap_or_default());
+    let contributors = sorted_stats
+        .iter()
+        .zip(initials)
+        .enumerate()
+        .map(|(i, ((name, stats), initials))| {
+            progress.emit("render", i as u64, sorted_stats.len() as u64, Some(name.as_str()));
+            let own = by_contributor.get(*name).map_or(&[][..], Vec::as_slice);
+            let color = if is_others(args, name) { OTHERS_COLOR.to_string() } else { contributor_color(name) };
+            let mut latest: Vec<&CommitRecord> = own.to_vec();
+            latest.sort_by(|a, b| b.date.cmp(&a.date).then(b.committer_time.cmp(&a.committer_time)));
+            let email = identities.get(*name).filter(|key| !key.starts_with("name:")).map_or("", String::as_str);
+            let url = match latest.first() {
+                Some(commit) if !is_others(args, name) => links.author_url(&commit.repo, name, email),
+                _ => None,
+            };
+            let recent_commits = latest
+                .iter()
+                .filter_map(|commit| {
+                    links.commit_url(&commit.repo, &commit.hash).map(|url| ReportCommit {
+                        hash: commit.hash[..commit.hash.len().min(7)].to_string(),
+                        date: commit.date.clone(),
+                        url,
+                    })
+                })
+                .take(LINKED_COMMITS)
+                .collect();
+            ReportContributor {
+                name,
+                id: ids.remove(*name).unwrap_or_default(),
+                commits: stats.commits,
+                empty_commits: stats.empty_commits,
+                added: stats.lines_added,
+                deleted: stats.lines_deleted,
+                lines: metric.of(stats),
+                color: color.clone(),
+                avatar_text: readable_text_color(&color),
+                initials,
+                avatar: if args.avatars && !is_others(args, name) {
+                    github_avatars.get(*name).cloned().or_else(|| avatar_url(email))
+                } else {
+                    None
+                },
+                commit_series: series_by_granularity(&get_commits_by_date(own.iter().copied()), granularities, week_start),
+                line_series: series_by_granularity(&get_lines_by_date(own.iter().copied(), metric), granularities, week_start),
+                calendar: to_points(&get_commits_by_date(own.iter().copied().filter(|c| in_calendar(&c.date)))),
+                punchcard: args.punchcard.then(|| punchcard(own.iter().copied(), week_start)),
+                url,
+                recent_commits,
+                previous_commits: before(name).map(|b| b.commits),
+                previous_lines: before(name).map(|b| metric.of(&b)),
+                page: None,
+                stats,
+            }
+        })
+        .collect();
+
+    let mut owners = commit_owners(commits, author_mapping);
+    if let Some((others, _)) = sorted_stats.iter().find(|(name, _)| is_others(args, name)) {
+        owners.hide(others);
+    }
+
+    // Running total of added - deleted approximates repository size over time
+    let mut running: i64 = 0;
+    let growth = get_net_lines_by_date(commits)
+        .into_iter()
+        .map(|(date, net)| {
+            running += net;
+            DatePoint { date, count: running }
+        })
+        .collect();
+
+    Ok(ReportData {
+        branch,
+        since: args.since.as_deref(),
+        until: args.until.as_deref(),
+        merges_included: !args.no_merges,
+        first_parent: args.first_parent,
+        others_threshold: args.others_below.unwrap_or(0.0),
+        bus_factor: BusFactor::of(&owners, args.bus_factor_threshold),
+        bus_factor_threshold: args.bus_factor_threshold,
+        granularities,
+        total_commit_series: series_by_granularity(&get_commits_by_date(commits), granularities, week_start),
+        total_line_series: series_by_granularity(&get_lines_by_date(commits, metric), granularities, week_start),
+        lines_label: metric.label(),
+        growth,
+        calendar_start: calendar_start.map(|d| d.to_string()),
+        calendar_end: calendar_end.map(|d| d.to_string()),
+        contributors,
+        previous: previous.map(|previous| ReportPrevious {
+            commits: previous.stats.values().map(|s| s.commits).sum(),
+            lines: previous.stats.values().map(|s| metric.of(s).max(0)).sum(),
+            since: previous.since,
+            until: previous.until,
+        }),
+        tags: release_tags(args, commits)?,
+        punchcard: args.punchcard.then(|| ReportPunchcard {
+            weekdays: weekday_names(week_start),
+            team: punchcard(commits, week_start),
+        }),
+        hours_overlap: args.hours_overlap.then(|| {
+            let names: Vec<&String> = sorted_stats.iter().map(|(name, _)| *name).filter(|name| !is_others(args, name)).collect();
+            hour_overlap(commits, author_mapping, &names)
+        }),
+        repositories: args.per_repo.then(|| {
+            repo_breakdowns(commits, author_mapping, args)
+                .into_iter()
+                .map(|repo| ReportRepository {
+                    name: repo.name,
+                    contributors: repo
+                        .contributors
+                        .into_iter()
+                        .map(|c| ReportRepoContributor {
+                            name: c.name,
+                            id: c.id,
+                            commits: c.stats.commits,
+                            added: c.stats.lines_added,
+                            deleted: c.stats.lines_deleted,
+                        })
+                        .collect(),
+                })
+                .collect()
+        }),
+    })
+}
+
+pub fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `--theme`: dark (default), light, or auto to follow the viewer's system setting.
+fn theme(args: &Args) -> &str {
+    args.theme.as_deref().unwrap_or("dark")
+}
+
+/// The sections of `REPORT_BODY`, by the name of the `<!-- section: name -->` comment
+/// opening each, for templates that lay them out one by one.
+fn report_sections() -> Vec<(&'static str, &'static str)> {
+    // The last section ends where the container does
+    let body = REPORT_BODY.trim_end().strip_suffix("</div>").unwrap_or(REPORT_BODY);
+    body.split("<!-- section: ").skip(1).filter_map(|part| part.split_once(" -->")).map(|(name, markup)| (name, markup.trim())).collect()
+}
+
+/// The full page around the report markup, from `--template` or the built-in template.
+/// A template places either the whole `report` or each of its sections, inside a
+/// `container` element, and the `footer`.
+fn html_page(args: &Args, title: &str, head: &str, scripts: &str) -> Result<String, Error> {
+    let custom = match &args.template {
+        Some(path) => Some((path, fs::read_to_string(path).map_err(|source| Error::Read { path: path.clone(), source })?)),
+        None => None,
+    };
+    let template = custom.as_ref().map_or(REPORT_TEMPLATE, |(_, template)| template.as_str());
+    // How the report was made, so an archived copy can be reproduced
+    let footer = format!("<footer class=\"report-meta\">{}</footer>", escape_html(&ReportMetadata::new(args)?.summary()));
+    let report = format!("{}\n{}", REPORT_BODY.trim_end(), footer);
+    let sections = report_sections();
+    let mut values = vec![
+        ("title", title),
+        ("theme", theme(args)),
+        ("head", head),
+        ("report", report.as_str()),
+        ("footer", footer.as_str()),
+        ("scripts", scripts),
+    ];
+    values.extend(sections.iter().copied());
+    let mut required: Vec<[&str; 2]> = sections.iter().map(|(name, _)| ["report", *name]).collect();
+    required.push(["report", "footer"]);
+    let mut required: Vec<&[&str]> = required.iter().map(|names| names.as_slice()).collect();
+    required.push(&["scripts"]);
+    render(template, &values, &required).map_err(|message| Error::InvalidFile {
+        kind: "--template",
+        path: custom.as_ref().map_or_else(|| "built-in template".to_string(), |(path, _)| path.to_string()),
+        message,
+    })
+}
+
+fn chart_scripts(args: &Args) -> Result<String, Error> {
+    match &args.inline_assets {
+        Some(dir) => inline_chart_scripts(Path::new(dir)).map_err(|source| Error::Read { path: dir.clone(), source }),
+        None => Ok(BUNDLED_CHART_SCRIPTS.map_or_else(cdn_chart_scripts, inline_scripts)),
+    }
+}
+
+/// Writes a single self-contained HTML file with data, styles and script inlined.
+pub fn generate_html_report(
+    args: &Args,
+    branch: &str,
+    commits: &[CommitRecord],
+    sorted_stats: &[(&String, &ContributorStats)],
+    author_mapping: &HashMap<String, String>,
+    output_path: &str,
+    progress: &Progress,
+) -> std::io::Result<()> {
+    let data = build_report_data(args, branch, commits, sorted_stats, author_mapping, progress).or_exit();
+    let page = report_page(args, branch, &data, "").or_exit();
+    match args.max_report_size {
+        Some(limit) if page.len() as u64 > limit => {
+            drop(data);
+            write_fitted_report(args, branch, commits, author_mapping, output_path, progress)?;
+        }
+        _ => fs::write(output_path, page)?,
+    }
+    progress.emit("render", 1, 1, None);
+    Ok(())
+}
+
+/// `--size-strategy` steps, in the order `--max-report-size` tries them.
+pub const SIZE_STRATEGIES: [&str; 3] = ["daily", "fold", "split"];
+
+/// Contributors `--max-report-size` keeps when folding the rest into "Others".
+const MIN_FITTED_CONTRIBUTORS: usize = 10;
+
+/// Rebuilds a report over `--max-report-size` until the page fits, with each allowed step
+/// in turn: week then month buckets without the daily calendar, then half as many
+/// contributors at a time (down to `MIN_FITTED_CONTRIBUTORS`, the rest folded into
+/// "Others"), then the data moved to scripts beside the page (see `write_split_data`).
+/// Explains the steps taken on stderr, and writes the smallest page it got when none is
+/// enough.
+fn write_fitted_report(
+    args: &Args,
+    branch: &str,
+    commits: &[CommitRecord],
+    author_mapping: &HashMap<String, String>,
+    output_path: &str,
+    progress: &Progress,
+) -> std::io::Result<()> {
+    let limit = args.max_report_size.unwrap_or(u64::MAX);
+    let allowed = |step: &str| args.size_strategy.is_empty() || args.size_strategy.iter().any(|s| s == step);
+    let mut args = args.clone();
+    let mut mapping = author_mapping.clone();
+    let mut daily = true;
+    let mut steps = Vec::new();
+    loop {
+        let stats = contributor_stats(commits, &mapping);
+        let sorted_stats = ranked_contributors(&stats, &args);
+        let mut data = build_report_data(&args, branch, commits, &sorted_stats, &mapping, progress).or_exit();
+        if !daily {
+            data.calendar_start = None;
+            data.calendar_end = None;
+            data.contributors.iter_mut().for_each(|c| c.calendar.clear());
+        }
+        let page = report_page(&args, branch, &data, "").or_exit();
+        if page.len() as u64 <= limit {
+            fs::write(output_path, page)?;
+            break;
+        }
+
+        let finest = GRANULARITIES.iter().position(|g| *g == granularity(&args)).unwrap_or(0);
+        let month = GRANULARITIES.iter().position(|g| *g == "month").unwrap_or(0);
+        let shown = sorted_stats.iter().filter(|(name, _)| !is_others(&args, name)).count();
+        if allowed("daily") && finest < month {
+            args.by = Some(GRANULARITIES[finest + 1].to_string());
+            let calendar = if daily { " and no calendar" } else { "" };
+            daily = false;
+            steps.push(format!("{}ly buckets{}", GRANULARITIES[finest + 1], calendar));
+        } else if allowed("fold") && shown > MIN_FITTED_CONTRIBUTORS {
+            let top = (shown / 2).max(MIN_FITTED_CONTRIBUTORS);
+            args.top = Some(top);
+            mapping = fold_others(commits, &mapping, &args);
+            steps.push(format!("the top {} contributors only", top));
+        } else if allowed("split") {
+            let files = write_split_data(&data, Path::new(output_path), limit)?;
+            let page = split_report_page(&args, branch, &files).or_exit();
+            fs::write(output_path, &page)?;
+            steps.push(format!("the data in {} scripts beside it", files.len()));
+            if page.len() as u64 > limit {
+                eprintln!(
+                    "Warning: the page alone (styles and scripts) is {} KiB, over --max-report-size {} KiB",
+                    page.len() / 1024,
+                    limit / 1024
+                );
+            }
+            break;
+        } else {
+            fs::write(output_path, &page)?;
+            eprintln!(
+                "Warning: the report is {} KiB, over --max-report-size {} KiB even after the allowed --size-strategy steps",
+                page.len() / 1024,
+                limit / 1024
+            );
+            break;
+        }
+    }
+    if !steps.is_empty() {
+        eprintln!("Note: the report would exceed --max-report-size; it shows {}", steps.join(", then "));
+    }
+    Ok(())
+}
+
+/// The self-contained report page for `serve`, with `extra_scripts` after the report's.
+pub fn report_html(
+    args: &Args,
+    branch: &str,
+    commits: &[CommitRecord],
+    sorted_stats: &[(&String, &ContributorStats)],
+    author_mapping: &HashMap<String, String>,
+    extra_scripts: &str,
+) -> Result<String, Error> {
+    let data = build_report_data(args, branch, commits, sorted_stats, author_mapping, &Progress::new(args))?;
+    report_page(args, branch, &data, extra_scripts)
+}
+
+/// Writes `data` as scripts beside `page` for `split_report_page`: the contributors in
+/// `<page>.data-N.js` files of up to `limit` bytes each (one contributor at least), then
+/// the rest of the report in `<page>.data.js`. Returns their file names in loading order.
+fn write_split_data(data: &ReportData, page: &Path, limit: u64) -> std::io::Result<Vec<String>> {
+    let mut report = serde_json::to_value(data)?;
+    let contributors = match report.get_mut("contributors").map(serde_json::Value::take) {
+        Some(serde_json::Value::Array(contributors)) => contributors,
+        _ => Vec::new(),
+    };
+    if let Some(report) = report.as_object_mut() {
+        report.remove("contributors");
+    }
+    let mut chunks: Vec<Vec<String>> = Vec::new();
+    let mut chunk_len = 0;
+    for contributor in &contributors {
+        let json = serde_json::to_string(contributor)?;
+        match chunks.last_mut() {
+            Some(chunk) if (chunk_len + json.len()) as u64 <= limit => {
+                chunk_len += json.len() + 1;
+                chunk.push(json);
+            }
+            _ => {
+                chunk_len = json.len() + 1;
+                chunks.push(vec![json]);
+            }
+        }
+    }
+    let mut scripts: Vec<(PathBuf, String)> = chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| (page.with_extension(format!("data-{}.js", i + 1)), format!("addReportContributors([{}]);\n", chunk.join(","))))
+        .collect();
+    scripts.push((page.with_extension("data.js"), format!("renderSplitReport({});\n", report)));
+    let mut files = Vec::new();
+    for (path, script) in scripts {
+        fs::write(&path, script)?;
+        files.push(path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default());
+    }
+    Ok(files)
+}
+
+/// The self-contained page for `data`.
+fn report_page(args: &Args, branch: &str, data: &ReportData, extra_scripts: &str) -> Result<String, Error> {
+    // "</" inside a JSON string would otherwise be able to close the script tag
+    let render = format!(
+        "<script>renderReport({});</script>",
+        serde_json::to_string(data).expect("Failed to serialize report").replace("</", "<\\/")
+    );
+    page_around(args, branch, &render, extra_scripts)
+}
+
+/// The page for a report split by `write_split_data`, loading `data_files` in order.
+fn split_report_page(args: &Args, branch: &str, data_files: &[String]) -> Result<String, Error> {
+    let render: Vec<String> = data_files.iter().map(|file| format!("<script src=\"{}\"></script>", escape_html(file))).collect();
+    page_around(args, branch, &render.join("\n    "), "")
+}
+
+/// The report page with `render`, the scripts handing it the data, after the report's own.
+fn page_around(args: &Args, branch: &str, render: &str, extra_scripts: &str) -> Result<String, Error> {
+    let mut head = format!("<style>\n{}    </style>\n    {}", REPORT_CSS, chart_scripts(args)?);
+    if args.watch {
+        // --watch rewrites the file as commits land; pick up each version
+        head.push_str(&format!("\n    <meta http-equiv=\"refresh\" content=\"{}\">", args.watch_interval));
+    }
+    let scripts = format!("<script>\n{}    </script>\n    {}{}", REPORT_JS, render, extra_scripts);
+    html_page(args, branch, &head, &scripts)
+}
+
+const SPARKLINE_WIDTH: f64 = 120.0;
+const SPARKLINE_HEIGHT: f64 = 24.0;
+
+/// Inline SVG line of weekly commit counts over `weeks` weeks starting at `first_week`.
+fn sparkline(commits: &[CommitRecord], first_week: NaiveDate, weeks: usize, first_day: Weekday) -> String {
+    let mut counts = vec![0u64; weeks.max(1)];
+    for commit in commits {
+        let Ok(date) = NaiveDate::parse_from_str(&commit.date, "%Y-%m-%d") else { continue };
+        if let Some(count) = counts.get_mut((week_start(date, first_day) - first_week).num_weeks() as usize) {
+            *count += 1;
+        }
+    }
+    let peak = counts.iter().copied().max().unwrap_or(0);
+    if counts.len() == 1 {
+        // A single point draws nothing, so stretch it across the width
+        counts.push(counts[0]);
+    }
+    let step = SPARKLINE_WIDTH / (counts.len() - 1) as f64;
+    let points = counts
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| {
+            let y = SPARKLINE_HEIGHT - 1.0 - count as f64 / peak.max(1) as f64 * (SPARKLINE_HEIGHT - 2.0);
+            format!("{:.1},{:.1}", i as f64 * step, y)
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        r#"<svg class="sparkline" width="{w}" height="{h}" viewBox="0 0 {w} {h}" role="img" aria-label="Weekly commits, peak {peak}"><polyline points="{points}"/></svg>"#,
+        w = SPARKLINE_WIDTH,
+        h = SPARKLINE_HEIGHT,
+        peak = peak,
+        points = points,
+    )
+}
+
+/// File name for a repository's report: its name reduced to safe characters, with a
+/// numeric suffix when two repositories share a name.
+fn report_file_name(name: &str, used: &mut HashSet<String>) -> String {
+    let base: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '-' })
+        .collect();
+    let base = if base.is_empty() || base == "index" { format!("repo-{}", base) } else { base };
+    let mut file = format!("{}.html", base);
+    let mut n = 2;
+    while !used.insert(file.clone()) {
+        file = format!("{}-{}.html", base, n);
+        n += 1;
+    }
+    file
+}
+
+/// Writes a report per repository into `dir`, plus an `index.html` listing each one with
+/// its totals, top contributor and a sparkline of weekly commits. Sparklines share one
+/// date range so repositories can be compared at a glance.
+pub fn generate_html_index(
+    args: &Args,
+    repos: &[(String, String, Vec<CommitRecord>)],
+    author_mapping: &HashMap<String, String>,
+    dir: &str,
+    progress: &Progress,
+) -> std::io::Result<()> {
+    let dir = Path::new(dir);
+    fs::create_dir_all(dir)?;
+
+    let first_day = week_start_day(args);
+    let dates: Vec<NaiveDate> = repos
+        .iter()
+        .flat_map(|(_, _, commits)| commits)
+        .filter_map(|commit| NaiveDate::parse_from_str(&commit.date, "%Y-%m-%d").ok())
+        .collect();
+    let first_week = dates.iter().min().map(|&date| week_start(date, first_day));
+    let weeks = match (first_week, dates.iter().max()) {
+        (Some(first), Some(&last)) => (last - first).num_weeks() as usize + 1,
+        _ => 1,
+    };
+    let first_week = first_week.unwrap_or_default();
+
+    let mut used = HashSet::new();
+    let mut everyone = HashSet::new();
+    let mut rows = String::new();
+    for (name, label, commits) in repos {
+        let file = report_file_name(name, &mut used);
+        let title = format!("{} ({})", name, label);
+        let mapping = fold_others(commits, author_mapping, args);
+        let stats = contributor_stats(commits, &mapping);
+        let sorted_stats = ranked_contributors(&stats, args);
+        generate_html_report(args, &title, commits, &sorted_stats, &mapping, &dir.join(&file).to_string_lossy(), progress)?;
+
+        let contributors: HashSet<&String> =
+            commits.iter().map(|commit| canonical_name(&commit.author, author_mapping)).collect();
+        everyone.extend(contributors.iter().copied());
+        let top = sorted_stats
+            .iter()
+            .find(|(name, _)| !is_others(args, name))
+            .map(|(name, stats)| format!("{} ({})", escape_html(name), stats.commits))
+            .unwrap_or_else(|| "-".to_string());
+        rows.push_str(&format!(
+            "                <tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td>+{}</td><td>-{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape_html(&file),
+            escape_html(&title),
+            commits.len(),
+            contributors.len(),
+            commits.iter().map(|commit| commit.lines_added).sum::<u64>(),
+            commits.iter().map(|commit| commit.lines_deleted).sum::<u64>(),
+            top,
+            sparkline(commits, first_week, weeks, first_day),
+        ));
+    }
+
+    let page = format!(
+        r#"<!DOCTYPE html>
+<html lang="en" data-theme="{theme}">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>Contributors - {count} repositories</title>
+    <style>
+{css}    </style>
+</head>
+<body>
+    <div class="container">
+        <h1>Repositories</h1>
+        <div class="subtitle">{count} repositories &middot; {commits} commits &middot; {contributors} contributors</div>
+        <div class="card">
+            <table class="repo-table">
+                <tr><th>Repository</th><th>Commits</th><th>Contributors</th><th>Added</th><th>Deleted</th><th>Top contributor</th><th>Weekly commits</th></tr>
+{rows}            </table>
+        </div>
+    </div>
+</body>
+</html>
+"#,
+        count = repos.len(),
+        theme = theme(args),
+        css = REPORT_CSS,
+        commits = repos.iter().map(|(_, _, commits)| commits.len()).sum::<usize>(),
+        contributors = everyone.len(),
+        rows = rows,
+    );
+    fs::write(dir.join("index.html"), page)?;
+    Ok(())
+}
+
+/// Writes one self-contained HTML file with a tab per branch or range. Colors derive
+/// from identities, so a contributor keeps the same color in every tab.
+pub fn generate_html_tabs(
+    args: &Args,
+    tabs: &[(String, Vec<CommitRecord>, HashMap<String, String>)],
+    output_path: &str,
+    progress: &Progress,
+) -> std::io::Result<()> {
+    let mut reports = Vec::new();
+    for (label, commits, author_mapping) in tabs {
+        let stats = contributor_stats(commits, author_mapping);
+        let sorted_stats = ranked_contributors(&stats, args);
+        let data = build_report_data(args, label, commits, &sorted_stats, author_mapping, progress).or_exit();
+        reports.push(serde_json::to_value(&data)?);
+    }
+    let data_json = serde_json::to_string(&reports)?.replace("</", "<\\/");
+
+    let title: Vec<&str> = tabs.iter().map(|(label, _, _)| label.as_str()).collect();
+    let head = format!("<style>\n{}    </style>\n    {}", REPORT_CSS, chart_scripts(args).or_exit());
+    let scripts = format!(
+        "<script>\n{}    </script>\n    <script>renderReportTabs({});</script>",
+        REPORT_JS, data_json
+    );
+
+    fs::write(output_path, html_page(args, &title.join(" | "), &head, &scripts).or_exit())?;
+    progress.emit("render", 1, 1, None);
+    Ok(())
+}
+
+/// Class wrapping an embedded fragment; every report style is scoped under it.
+const FRAGMENT_SCOPE: &str = ".gcs-report";
+
+/// Prefixes each selector of the stylesheet with `scope`, so the report's styles can't
+/// leak into a host page. `body`/`html`/`:root` rules apply to the wrapper itself, and
+/// at-rules such as `@media` keep their one-level block.
+fn scope_css(css: &str, scope: &str) -> String {
+    let mut out = String::new();
+    for rule in css.split_inclusive('}') {
+        let Some((selectors, body)) = rule.split_once('{') else {
+            out.push_str(rule);
+            continue;
+        };
+        if selectors.trim_start().starts_with('@') {
+            // The block's first rule came along; the block's closing brace follows on its own
+            out.push_str(&format!("{}{{{}", selectors, scope_css(body, scope)));
+            continue;
+        }
+        let leading = &selectors[..selectors.len() - selectors.trim_start().len()];
+        let scoped: Vec<String> = selectors
+            .split(',')
+            .map(|sel| match sel.trim() {
+                "body" | "html" => scope.to_string(),
+                sel if sel.starts_with(":root") => format!("{}{}", scope, &sel[":root".len()..]),
+                sel => format!("{} {}", scope, sel),
+            })
+            .collect();
+        out.push_str(&format!("{}{} {{{}", leading, scoped.join(", "), body));
+    }
+    out
+}
+
+/// Writes the report body and scripts without `<html>`/`<head>`, with styles scoped to a
+/// wrapping `.gcs-report` element, for inclusion in an existing page. `-` writes to stdout.
+pub fn generate_html_fragment(
+    args: &Args,
+    branch: &str,
+    commits: &[CommitRecord],
+    sorted_stats: &[(&String, &ContributorStats)],
+    author_mapping: &HashMap<String, String>,
+    output_path: &str,
+    progress: &Progress,
+) -> std::io::Result<()> {
+    let data = build_report_data(args, branch, commits, sorted_stats, author_mapping, progress).or_exit();
+    let data_json = serde_json::to_string(&data)?.replace("</", "<\\/");
+
+    let fragment = format!(
+        "<div class=\"{scope}\" data-theme=\"{theme}\">\n<style>\n{css}</style>\n{body}\n</div>\n{charts}\n<script>\n{js}</script>\n<script>renderReport({data});</script>\n",
+        scope = &FRAGMENT_SCOPE[1..],
+        theme = theme(args),
+        css = scope_css(REPORT_CSS, FRAGMENT_SCOPE),
+        body = REPORT_BODY.trim_end(),
+        charts = chart_scripts(args).or_exit(),
+        js = REPORT_JS,
+        data = data_json,
+    );
+    if output_path == "-" {
+        print!("{}", fragment);
+    } else {
+        fs::write(output_path, fragment)?;
+    }
+    progress.emit("render", 1, 1, None);
+    Ok(())
+}
+
+/// Writes the report as `index.html` plus `data.json`, `app.js` and `style.css` in `dir`,
+/// so data can be refreshed independently of the markup. The page must be served over
+/// HTTP since browsers block `fetch` from `file://` URLs.
+pub fn generate_html_split(
+    args: &Args,
+    branch: &str,
+    commits: &[CommitRecord],
+    sorted_stats: &[(&String, &ContributorStats)],
+    author_mapping: &HashMap<String, String>,
+    dir: &str,
+    progress: &Progress,
+) -> std::io::Result<()> {
+    let dir = Path::new(dir);
+    fs::create_dir_all(dir)?;
+
+    let data = build_report_data(args, branch, commits, sorted_stats, author_mapping, progress).or_exit();
+    fs::write(dir.join("data.json"), serde_json::to_string_pretty(&data)?)?;
+    fs::write(dir.join("app.js"), REPORT_JS)?;
+    fs::write(dir.join("style.css"), REPORT_CSS)?;
+
+    let head = format!("<link rel=\"stylesheet\" href=\"style.css\">\n    {}", chart_scripts(args).or_exit());
+    let scripts = "<script src=\"app.js\"></script>\n    \
+        <script>fetch('data.json').then(r => r.json()).then(renderReport);</script>";
+    fs::write(dir.join("index.html"), html_page(args, branch, &head, scripts).or_exit())?;
+    progress.emit("render", 1, 1, None);
+    Ok(())
+}
+
+/// Files listed on each `--html-dir` contributor page.
+const PAGE_FILES: usize = 20;
+
+/// What every `--html-dir` contributor page is rendered from.
+struct ContributorPages<'a> {
+    repos: HashMap<&'a str, &'a Source>,
+    paths: PathFilter,
+    links: ForgeLinks,
+    first_parent: bool,
+    /// First and last day of the whole report, so daily charts line up across pages
+    days: Option<(NaiveDate, NaiveDate)>,
+    theme: &'a str,
+}
+
+impl ContributorPages<'_> {
+    /// Aliases (names and emails resolved to `name`), a bar per day, the most-touched
+    /// files and every commit, newest first.
+    fn render(&self, name: &str, color: &str, stats: &ContributorStats, own: &[&CommitRecord]) -> String {
+        let multi_repo = self.repos.len() > 1;
+        let mut latest: Vec<&CommitRecord> = own.to_vec();
+        latest.sort_by(|a, b| b.date.cmp(&a.date).then(b.committer_time.cmp(&a.committer_time)));
+
+        let mut aliases: BTreeMap<(&str, &str), u64> = BTreeMap::new();
+        for commit in own {
+            *aliases.entry((commit.author.as_str(), commit.email.as_str())).or_insert(0) += 1;
+        }
+        let mut aliases: Vec<((&str, &str), u64)> = aliases.into_iter().collect();
+        aliases.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let alias_rows: String = aliases
+            .iter()
+            .map(|((author, email), count)| {
+                format!(
+                    "                <tr><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    escape_html(author),
+                    escape_html(email),
+                    count
+                )
+            })
+            .collect();
+
+        let mut files: HashMap<String, (u64, u64, u64)> = HashMap::new();
+        let mut commit_rows = String::new();
+        for commit in &latest {
+            let source = self.repos.get(commit.repo.as_str());
+            let oid = commit.hash.parse().ok();
+            if let (Some(source), Some(oid)) = (source, oid) {
+                for (path, added, deleted) in source.repo.file_stats(oid, &self.paths, self.first_parent).or_exit() {
+                    let key = if multi_repo { format!("{}:{}", commit.repo, path) } else { path };
+                    let file = files.entry(key).or_default();
+                    file.0 += 1;
+                    file.1 += added;
+                    file.2 += deleted;
+                }
+            }
+            let summary = match (source, oid) {
+                (Some(source), Some(oid)) => source.repo.summary(oid),
+                _ => String::new(),
+            };
+            let short = &commit.hash[..commit.hash.len().min(7)];
+            let (date, hash) = match self.links.commit_url(&commit.repo, &commit.hash) {
+                Some(url) => (
+                    format!("<a href=\"{}\">{}</a>", escape_html(&url), commit.date),
+                    format!("<a href=\"{}\"><code>{}</code></a>", escape_html(&url), short),
+                ),
+                None => (commit.date.clone(), format!("<code>{}</code>", short)),
+            };
+            let repo = if multi_repo { format!("{} ", escape_html(&commit.repo)) } else { String::new() };
+            commit_rows.push_str(&format!(
+                "                <tr><td>{}</td><td>{}{}</td><td class=\"message\">{}</td><td>{}</td><td class=\"added\">+{}</td><td class=\"deleted\">-{}</td></tr>\n",
+                date,
+                repo,
+                hash,
+                escape_html(&summary),
+                commit.files_changed,
+                commit.lines_added,
+                commit.lines_deleted,
+            ));
+        }
+        let mut files: Vec<(String, (u64, u64, u64))> = files.into_iter().collect();
+        files.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then((b.1 .1 + b.1 .2).cmp(&(a.1 .1 + a.1 .2))).then_with(|| a.0.cmp(&b.0)));
+        let file_rows: String = files
+            .iter()
+            .take(PAGE_FILES)
+            .map(|(path, (commits, added, deleted))| {
+                format!(
+                    "                <tr><td>{}</td><td>{}</td><td class=\"added\">+{}</td><td class=\"deleted\">-{}</td></tr>\n",
+                    escape_html(path),
+                    commits,
+                    added,
+                    deleted
+                )
+            })
+            .collect();
+
+        let daily = get_commits_by_date(own.iter().copied());
+        let mut daily_lines: BTreeMap<&str, (u64, u64)> = BTreeMap::new();
+        for commit in own {
+            let day = daily_lines.entry(commit.date.as_str()).or_default();
+            day.0 += commit.lines_added;
+            day.1 += commit.lines_deleted;
+        }
+        let day_rows: String = daily
+            .iter()
+            .rev()
+            .map(|(date, count)| {
+                let (added, deleted) = daily_lines.get(date.as_str()).copied().unwrap_or_default();
+                format!(
+                    "                    <tr><td>{}</td><td>{}</td><td class=\"added\">+{}</td><td class=\"deleted\">-{}</td></tr>\n",
+                    date, count, added, deleted
+                )
+            })
+            .collect();
+        let first_last = match (latest.last(), latest.first()) {
+            (Some(first), Some(last)) => format!(" &middot; {} to {}", first.date, last.date),
+            _ => String::new(),
+        };
+
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="en" data-theme="{theme}">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>Contributors - {name}</title>
+    <style>
+{css}    </style>
+</head>
+<body>
+    <div class="container">
+        <a class="back-link" href="index.html">&larr; All contributors</a>
+        <h1><span class="avatar" style="display: inline-flex; vertical-align: middle; width: 32px; height: 32px; background: {color};" aria-hidden="true"></span> {name}</h1>
+        <div class="subtitle">{commits} commits &middot; +{added} &middot; -{deleted} &middot; {files_changed} file changes &middot; active on {active_days} days{first_last}</div>
+        <div class="card">
+            <h2 class="card-title">Daily activity</h2>
+            {bars}
+            <details class="chart-data"><summary>Data table</summary>
+                <table class="repo-table">
+                    <tr><th>Date</th><th>Commits</th><th>Added</th><th>Deleted</th></tr>
+{day_rows}                </table>
+            </details>
+        </div>
+        <div class="card">
+            <h2 class="card-title">Most-touched files</h2>
+            <table class="repo-table">
+                <tr><th>File</th><th>Commits</th><th>Added</th><th>Deleted</th></tr>
+{file_rows}            </table>
+        </div>
+        <div class="card">
+            <h2 class="card-title">Aliases</h2>
+            <table class="repo-table">
+                <tr><th>Name</th><th>Email</th><th>Commits</th></tr>
+{alias_rows}            </table>
+        </div>
+        <div class="card">
+            <h2 class="card-title">Commits</h2>
+            <table class="repo-table">
+                <tr><th>Date</th><th>Commit</th><th class="message">Message</th><th>Files</th><th>Added</th><th>Deleted</th></tr>
+{commit_rows}            </table>
+        </div>
+    </div>
+</body>
+</html>
+"#,
+            name = escape_html(name),
+            theme = self.theme,
+            css = REPORT_CSS,
+            color = color,
+            commits = stats.commits,
+            added = stats.lines_added,
+            deleted = stats.lines_deleted,
+            files_changed = stats.files_changed,
+            active_days = daily.len(),
+            first_last = first_last,
+            bars = self.daily_bars(&daily),
+            day_rows = day_rows,
+            file_rows = file_rows,
+            alias_rows = alias_rows,
+            commit_rows = commit_rows,
+        )
+    }
+
+    /// Inline SVG with a bar per day of the report, scaled to the busiest day.
+    fn daily_bars(&self, daily: &BTreeMap<String, u64>) -> String {
+        let Some((first, last)) = self.days else { return String::new() };
+        let days = (last - first).num_days() + 1;
+        let peak = daily.values().copied().max().unwrap_or(0).max(1);
+        let bars: String = daily
+            .iter()
+            .filter_map(|(date, count)| {
+                let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+                let height = *count as f64 / peak as f64 * SPARKLINE_HEIGHT;
+                Some(format!(
+                    "<rect x=\"{}\" y=\"{:.1}\" width=\"1\" height=\"{:.1}\"><title>{}: {} commits</title></rect>",
+                    (day - first).num_days(),
+                    SPARKLINE_HEIGHT - height,
+                    height,
+                    date,
+                    count
+                ))
+            })
+            .collect();
+        format!(
+            r#"<svg class="daily-activity" viewBox="0 0 {days} {h}" preserveAspectRatio="none" role="img" aria-label="Commits per day from {first} to {last}, peak {peak}">{bars}</svg>"#,
+            days = days,
+            h = SPARKLINE_HEIGHT,
+            first = first,
+            last = last,
+            peak = peak,
+            bars = bars,
+        )
+    }
+}
+
+/// Writes the report as `index.html` in `dir` plus a page per contributor, linked from
+/// their card, for 1:1 reviews. Pages are named by contributor id, so links to them
+/// survive renames and `--merge` changes.
+pub fn generate_html_dir(
+    sources: &[Source],
+    args: &Args,
+    commits: &[CommitRecord],
+    sorted_stats: &[(&String, &ContributorStats)],
+    author_mapping: &HashMap<String, String>,
+    dir: &str,
+    progress: &Progress,
+) -> std::io::Result<()> {
+    let dir = Path::new(dir);
+    fs::create_dir_all(dir)?;
+
+    let branch = revision_label(sources);
+    let mut data = build_report_data(args, &branch, commits, sorted_stats, author_mapping, progress).or_exit();
+    let mut files: HashMap<&str, String> = HashMap::new();
+    for contributor in &mut data.contributors {
+        let file = format!("{}.html", contributor.id);
+        files.insert(contributor.name, file.clone());
+        contributor.page = Some(file);
+    }
+    fs::write(dir.join("index.html"), report_page(args, &branch, &data, "").or_exit())?;
+
+    let dates = commits.iter().filter_map(|c| NaiveDate::parse_from_str(&c.date, "%Y-%m-%d").ok());
+    let pages = ContributorPages {
+        repos: sources.iter().map(|s| (s.repo.name(), s)).collect(),
+        paths: PathFilter::new(&args.path, &args.exclude_path, &args.ext, !args.include_generated).or_exit(),
+        links: ForgeLinks::load(args).or_exit(),
+        first_parent: args.first_parent,
+        days: dates.clone().min().zip(dates.max()),
+        theme: theme(args),
+    };
+    let by_contributor = commits_by_contributor(commits, author_mapping);
+    for (i, (name, stats)) in sorted_stats.iter().enumerate() {
+        progress.emit("pages", i as u64, sorted_stats.len() as u64, Some(name.as_str()));
+        let own = by_contributor.get(*name).map_or(&[][..], Vec::as_slice);
+        let color = if is_others(args, name) { OTHERS_COLOR.to_string() } else { contributor_color(name) };
+        fs::write(dir.join(&files[name.as_str()]), pages.render(name, &color, stats, own))?;
+    }
+    progress.emit("pages", sorted_stats.len() as u64, sorted_stats.len() as u64, None);
+    Ok(())
+}
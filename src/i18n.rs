@@ -0,0 +1,70 @@
+use crate::Args;
+
+/// Output languages accepted by `--lang`.
+pub const LANGUAGES: [&str; 2] = ["en", "fr"];
+
+/// Headers of the terminal table in one language.
+pub struct Labels {
+    pub contributor: &'static str,
+    pub commits: &'static str,
+    pub empty: &'static str,
+    pub applied: &'static str,
+    pub weighted: &'static str,
+    pub score: &'static str,
+    pub functions: &'static str,
+    pub added: &'static str,
+    pub deleted: &'static str,
+    pub files: &'static str,
+    pub net: &'static str,
+    pub activity: &'static str,
+    pub last_30d: &'static str,
+    pub last_90d: &'static str,
+    pub languages: &'static str,
+    pub total: &'static str,
+}
+
+const EN: Labels = Labels {
+    contributor: "Contributor",
+    commits: "Commits",
+    empty: "Empty",
+    applied: "Applied",
+    weighted: "Weighted",
+    score: "Score",
+    functions: "Functions",
+    added: "Lines added",
+    deleted: "Lines deleted",
+    files: "Files",
+    net: "Net",
+    activity: "Activity",
+    last_30d: "Last 30d",
+    last_90d: "Last 90d",
+    languages: "Languages",
+    total: "TOTAL",
+};
+
+const FR: Labels = Labels {
+    contributor: "Contributeur",
+    commits: "Commits",
+    empty: "Vides",
+    applied: "Appliqués",
+    weighted: "Pondéré",
+    score: "Score",
+    functions: "Fonctions",
+    added: "Lignes ajoutées",
+    deleted: "Lignes supprimées",
+    files: "Fichiers",
+    net: "Net",
+    activity: "Activité",
+    last_30d: "30 j",
+    last_90d: "90 j",
+    languages: "Langages",
+    total: "TOTAL",
+};
+
+/// Labels for `--lang`, English by default.
+pub fn labels(args: &Args) -> &'static Labels {
+    match args.lang.as_deref() {
+        Some("fr") => &FR,
+        _ => &EN,
+    }
+}
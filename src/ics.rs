@@ -0,0 +1,112 @@
+use crate::canonical_name;
+use crate::html::stable_hash;
+use crate::repo::CommitRecord;
+use chrono::{NaiveDate, Utc};
+use std::collections::{BTreeMap, HashMap};
+
+/// One day of activity: commits and lines per contributor.
+#[derive(Default)]
+struct DayActivity {
+    by_contributor: BTreeMap<String, (u64, u64, u64)>,
+}
+
+impl DayActivity {
+    fn totals(&self) -> (u64, u64, u64) {
+        self.by_contributor
+            .values()
+            .fold((0, 0, 0), |(c, a, d), (commits, added, deleted)| (c + commits, a + added, d + deleted))
+    }
+}
+
+fn plural(count: u64, word: &str) -> String {
+    format!("{} {}{}", count, word, if count == 1 { "" } else { "s" })
+}
+
+/// Escapes TEXT values (RFC 5545 section 3.3.11).
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Folds a content line at 75 octets, continuing with a space, and terminates it with CRLF.
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn push_event(out: &mut String, stamp: &str, date: NaiveDate, uid: &str, summary: &str, description: &str) {
+    push_line(out, "BEGIN:VEVENT");
+    push_line(out, &format!("UID:{}@git-contrib-stats", uid));
+    push_line(out, &format!("DTSTAMP:{}", stamp));
+    push_line(out, &format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")));
+    push_line(out, &format!("DTEND;VALUE=DATE:{}", date.succ_opt().unwrap_or(date).format("%Y%m%d")));
+    push_line(out, &format!("SUMMARY:{}", escape_text(summary)));
+    push_line(out, &format!("DESCRIPTION:{}", escape_text(description)));
+    // All-day summaries shouldn't block anyone's free/busy time
+    push_line(out, "TRANSP:TRANSPARENT");
+    push_line(out, "END:VEVENT");
+}
+
+/// Builds an iCalendar file with one all-day event per active day, summarizing the team's
+/// commits, or one event per contributor and day when `per_contributor` is set.
+pub fn activity_calendar(
+    branch: &str,
+    commits: &[CommitRecord],
+    author_mapping: &HashMap<String, String>,
+    per_contributor: bool,
+) -> String {
+    let mut days: BTreeMap<NaiveDate, DayActivity> = BTreeMap::new();
+    for commit in commits {
+        let Ok(date) = NaiveDate::parse_from_str(&commit.date, "%Y-%m-%d") else { continue };
+        let name = canonical_name(&commit.author, author_mapping);
+        let entry = days.entry(date).or_default().by_contributor.entry(name.clone()).or_default();
+        entry.0 += 1;
+        entry.1 += commit.lines_added;
+        entry.2 += commit.lines_deleted;
+    }
+
+    // UIDs only depend on the day, branch and contributor, so re-importing updates events
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//git-contrib-stats//activity//EN");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(&format!("Contributions: {}", branch))));
+
+    for (date, day) in &days {
+        if per_contributor {
+            for (name, (commits, added, deleted)) in &day.by_contributor {
+                let summary = format!("{}: {} (+{}/-{})", name, plural(*commits, "commit"), added, deleted);
+                let uid = format!("{}-{:016x}-{:016x}", date.format("%Y%m%d"), stable_hash(branch), stable_hash(name));
+                push_event(&mut out, &stamp, *date, &uid, &summary, &summary);
+            }
+        } else {
+            let (commits, added, deleted) = day.totals();
+            let names: Vec<&str> = day.by_contributor.keys().map(String::as_str).collect();
+            let summary = format!("{} (+{}/-{}): {}", plural(commits, "commit"), added, deleted, names.join(", "));
+            let description = day
+                .by_contributor
+                .iter()
+                .map(|(name, (c, a, d))| format!("{}: {}, +{}/-{}", name, plural(*c, "commit"), a, d))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let uid = format!("{}-{:016x}-team", date.format("%Y%m%d"), stable_hash(branch));
+            push_event(&mut out, &stamp, *date, &uid, &summary, &description);
+        }
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
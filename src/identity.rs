@@ -0,0 +1,94 @@
+use crate::repo::CommitRecord;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// One identity drift finding: a key (email or name) seen with several spellings.
+struct Inconsistency<'a> {
+    key: &'a str,
+    variants: Vec<Variant<'a>>,
+}
+
+struct Variant<'a> {
+    value: &'a str,
+    commits: u64,
+    repos: BTreeSet<&'a str>,
+}
+
+/// Groups `value` spellings by `key`, keeping only keys with more than one spelling.
+fn find_inconsistencies<'a>(
+    commits: &'a [CommitRecord],
+    key: impl Fn(&'a CommitRecord) -> &'a str,
+    value: impl Fn(&'a CommitRecord) -> &'a str,
+) -> Vec<Inconsistency<'a>> {
+    let mut seen: BTreeMap<&str, BTreeMap<&str, (u64, BTreeSet<&str>)>> = BTreeMap::new();
+    for commit in commits {
+        if !key(commit).is_empty() && !value(commit).is_empty() {
+            let entry = seen.entry(key(commit)).or_default().entry(value(commit)).or_default();
+            entry.0 += 1;
+            entry.1.insert(&commit.repo);
+        }
+    }
+    seen.into_iter()
+        .filter(|(_, variants)| variants.len() > 1)
+        .map(|(key, variants)| {
+            let mut variants: Vec<Variant> = variants
+                .into_iter()
+                .map(|(value, (commits, repos))| Variant { value, commits, repos })
+                .collect();
+            // Most used first; that spelling is the suggested canonical one
+            variants.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.value.cmp(b.value)));
+            Inconsistency { key, variants }
+        })
+        .collect()
+}
+
+/// Lists each spelling with its commit count, and with the repositories it appears in
+/// when several were scanned.
+fn describe(variants: &[Variant], multi_repo: bool) -> String {
+    variants
+        .iter()
+        .map(|v| {
+            if multi_repo {
+                let repos: Vec<&str> = v.repos.iter().copied().collect();
+                format!("{} ({} in {})", v.value, v.commits, repos.join(", "))
+            } else {
+                format!("{} ({})", v.value, v.commits)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Prints emails used under several names and names used with several emails, with
+/// `.mailmap` lines that would unify them. `commits` must not have been unified yet.
+/// Across several repositories this surfaces identity drift between them.
+pub fn print_identity_report(commits: &[CommitRecord]) {
+    let multi_repo = commits.iter().map(|c| c.repo.as_str()).collect::<BTreeSet<_>>().len() > 1;
+    let by_email = find_inconsistencies(commits, |c| c.email.as_str(), |c| c.author.as_str());
+    let by_name = find_inconsistencies(commits, |c| c.author.as_str(), |c| c.email.as_str());
+
+    println!("Identity check");
+    println!("──────────────");
+    if by_email.is_empty() && by_name.is_empty() {
+        println!("No inconsistencies found");
+        return;
+    }
+
+    let mut mailmap: BTreeSet<String> = BTreeSet::new();
+    for found in &by_email {
+        println!("Email {} is used by {} names: {}", found.key, found.variants.len(), describe(&found.variants, multi_repo));
+        mailmap.insert(format!("{} <{}>", found.variants[0].value, found.key));
+    }
+    for found in &by_name {
+        println!("Name \"{}\" uses {} emails: {}", found.key, found.variants.len(), describe(&found.variants, multi_repo));
+        let canonical = found.variants[0].value;
+        for variant in &found.variants[1..] {
+            mailmap.insert(format!("{} <{}> <{}>", found.key, canonical, variant.value));
+        }
+    }
+
+    println!();
+    println!("Suggested .mailmap entries:");
+    for line in mailmap {
+        println!("  {}", line);
+    }
+}
@@ -0,0 +1,200 @@
+use crate::error::OrExit;
+use crate::repo::PathFilter;
+use crate::{canonical_name, display_width, pad_display, Args, CommitRecord, Progress, Source};
+use std::collections::HashMap;
+
+/// What a commit mostly does, as far as its message and diff tell.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Impact {
+    Feature,
+    Fix,
+    Refactor,
+}
+
+/// Conventional Commits types (`feat:`, `fix(parser)!:`) and what they count as.
+const CONVENTIONAL_TYPES: &[(&str, Impact)] = &[
+    ("feat", Impact::Feature),
+    ("feature", Impact::Feature),
+    ("fix", Impact::Fix),
+    ("bugfix", Impact::Fix),
+    ("hotfix", Impact::Fix),
+    ("revert", Impact::Fix),
+    ("refactor", Impact::Refactor),
+    ("perf", Impact::Refactor),
+    ("style", Impact::Refactor),
+    ("chore", Impact::Refactor),
+    ("build", Impact::Refactor),
+    ("ci", Impact::Refactor),
+    ("docs", Impact::Refactor),
+    ("test", Impact::Refactor),
+];
+
+/// Words of a commit summary that give its kind away; the first one found wins, so
+/// "Fix crash when adding a tag" is a fix.
+const KEYWORDS: &[(&str, Impact)] = &[
+    ("fix", Impact::Fix),
+    ("fixes", Impact::Fix),
+    ("fixed", Impact::Fix),
+    ("fixing", Impact::Fix),
+    ("bug", Impact::Fix),
+    ("bugfix", Impact::Fix),
+    ("hotfix", Impact::Fix),
+    ("resolve", Impact::Fix),
+    ("resolves", Impact::Fix),
+    ("crash", Impact::Fix),
+    ("regression", Impact::Fix),
+    ("broken", Impact::Fix),
+    ("typo", Impact::Fix),
+    ("revert", Impact::Fix),
+    ("add", Impact::Feature),
+    ("adds", Impact::Feature),
+    ("added", Impact::Feature),
+    ("adding", Impact::Feature),
+    ("implement", Impact::Feature),
+    ("implements", Impact::Feature),
+    ("introduce", Impact::Feature),
+    ("introduces", Impact::Feature),
+    ("support", Impact::Feature),
+    ("feature", Impact::Feature),
+    ("new", Impact::Feature),
+    ("create", Impact::Feature),
+    ("allow", Impact::Feature),
+    ("enable", Impact::Feature),
+    ("refactor", Impact::Refactor),
+    ("refactoring", Impact::Refactor),
+    ("cleanup", Impact::Refactor),
+    ("clean", Impact::Refactor),
+    ("rename", Impact::Refactor),
+    ("move", Impact::Refactor),
+    ("simplify", Impact::Refactor),
+    ("extract", Impact::Refactor),
+    ("restructure", Impact::Refactor),
+    ("reorganize", Impact::Refactor),
+    ("tidy", Impact::Refactor),
+    ("deprecate", Impact::Refactor),
+    ("remove", Impact::Refactor),
+    ("drop", Impact::Refactor),
+    ("format", Impact::Refactor),
+    ("lint", Impact::Refactor),
+];
+
+/// Whether `path` looks like a test file: under a `test`/`tests`/`spec`/`__tests__`
+/// directory, or named like `foo_test.go`, `foo.test.ts`, `foo.spec.js` or `test_foo.py`.
+fn is_test_path(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    let mut parts = lower.split('/').rev();
+    let name = parts.next().unwrap_or("");
+    parts.any(|dir| matches!(dir, "test" | "tests" | "spec" | "specs" | "__tests__"))
+        || name.starts_with("test_")
+        || ["_test.", ".test.", "_spec.", ".spec."].iter().any(|marker| name.contains(marker))
+}
+
+/// The kind named by the summary: a Conventional Commits type, else the first keyword.
+fn from_message(summary: &str) -> Option<Impact> {
+    let lower = summary.to_lowercase();
+    if let Some((prefix, _)) = lower.split_once(':') {
+        let kind = prefix.split('(').next().unwrap_or("").trim_end_matches('!').trim();
+        if let Some((_, impact)) = CONVENTIONAL_TYPES.iter().find(|(name, _)| *name == kind) {
+            return Some(*impact);
+        }
+    }
+    lower
+        .split(|c: char| !c.is_alphanumeric())
+        .find_map(|word| KEYWORDS.iter().find(|(keyword, _)| *keyword == word).map(|(_, impact)| *impact))
+}
+
+/// The kind suggested by the diff when the message says nothing: new files with mostly
+/// added lines are a feature; small changes, or small ones mostly to tests, a fix; and
+/// changes that remove about as much as they add, a refactor.
+fn from_shape(files: &[(String, u64, u64)], new_files: usize) -> Option<Impact> {
+    if files.is_empty() {
+        return None;
+    }
+    let added: u64 = files.iter().map(|(_, a, _)| a).sum();
+    let deleted: u64 = files.iter().map(|(_, _, d)| d).sum();
+    let lines = added + deleted;
+    let test_lines: u64 = files.iter().filter(|(path, _, _)| is_test_path(path)).map(|(_, a, d)| a + d).sum();
+    Some(if new_files > 0 && added >= 2 * deleted {
+        Impact::Feature
+    } else if lines <= 20 || (lines <= 100 && test_lines * 10 >= lines * 3) {
+        Impact::Fix
+    } else if added > 2 * deleted {
+        Impact::Feature
+    } else {
+        Impact::Refactor
+    })
+}
+
+/// Prints each contributor's mix of feature-, fix- and refactor-like commits. A commit's
+/// kind comes from its message (Conventional Commits type or keywords), falling back on
+/// the shape of its diff; merges and commits with neither are left out.
+pub fn print_impact(sources: &[Source], args: &Args, commits: &[CommitRecord], author_mapping: &HashMap<String, String>, progress: &Progress) {
+    let repos: HashMap<&str, &Source> = sources.iter().map(|s| (s.repo.name(), s)).collect();
+    let paths = PathFilter::new(&args.path, &args.exclude_path, &args.ext, !args.include_generated).or_exit();
+
+    // [features, fixes, refactors] per contributor
+    let mut mix: HashMap<String, [u64; 3]> = HashMap::new();
+    let mut unclassified = 0;
+    for (done, commit) in commits.iter().enumerate() {
+        progress.emit("impact", done as u64, commits.len() as u64, None);
+        let (Some(source), Ok(oid)) = (repos.get(commit.repo.as_str()), commit.hash.parse()) else {
+            continue;
+        };
+        if commit.is_merge {
+            continue;
+        }
+        let impact = from_message(&source.repo.summary(oid)).or_else(|| {
+            let files = source.repo.file_stats(oid, &paths, args.first_parent).or_exit();
+            from_shape(&files, source.repo.new_files(oid, &paths, args.first_parent).or_exit().len())
+        });
+        let Some(impact) = impact else {
+            unclassified += 1;
+            continue;
+        };
+        let counts = mix.entry(canonical_name(&commit.author, author_mapping).clone()).or_default();
+        counts[impact as usize] += 1;
+    }
+    progress.emit("impact", commits.len() as u64, commits.len() as u64, None);
+
+    let mut rows: Vec<(String, [u64; 3])> = mix.into_iter().collect();
+    rows.sort_by(|a, b| b.1.iter().sum::<u64>().cmp(&a.1.iter().sum::<u64>()).then_with(|| a.0.cmp(&b.0)));
+    let mut total = [0; 3];
+    for (_, counts) in &rows {
+        for (sum, count) in total.iter_mut().zip(counts) {
+            *sum += count;
+        }
+    }
+    rows.push(("TOTAL".to_string(), total));
+
+    let name_width = rows.iter().map(|(name, _)| display_width(name)).max().unwrap_or(11).max(11);
+    let cell = |count: u64, all: u64| {
+        let percent = if all > 0 { count as f64 / all as f64 * 100.0 } else { 0.0 };
+        format!("{} ({:.0}%)", count, percent)
+    };
+    println!("Commit impact");
+    println!("─────────────");
+    println!(
+        "| {} | {:>8} | {:>12} | {:>12} | {:>12} |",
+        pad_display("Contributor", name_width),
+        "Commits",
+        "Feature",
+        "Fix",
+        "Refactor"
+    );
+    println!("|{:-<width$}|{:-<10}|{:-<14}|{:-<14}|{:-<14}|", "", "", "", "", "", width = name_width + 2);
+    for (name, counts) in &rows {
+        let all: u64 = counts.iter().sum();
+        println!(
+            "| {} | {:>8} | {:>12} | {:>12} | {:>12} |",
+            pad_display(name, name_width),
+            all,
+            cell(counts[Impact::Feature as usize], all),
+            cell(counts[Impact::Fix as usize], all),
+            cell(counts[Impact::Refactor as usize], all)
+        );
+    }
+    if unclassified > 0 {
+        println!();
+        println!("{} commits with no telling message and no changed files are left out", unclassified);
+    }
+}
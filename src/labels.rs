@@ -0,0 +1,77 @@
+use crate::error::Error;
+use crate::repo::CommitRecord;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Labels of the issues referenced by commits, read from an export of the forge's
+/// issue list, so reports can be sliced by priority without network access.
+pub struct IssueLabels {
+    labels: HashMap<u64, Vec<String>>,
+}
+
+impl IssueLabels {
+    /// Reads a JSON array of `{ "number": 123, "labels": [...] }` objects, as printed by
+    /// `gh issue list --state all --json number,labels`. Labels may be strings or
+    /// objects with a `name`, which also covers GitLab and Gitea exports using `iid`.
+    pub fn load(path: &str) -> Result<IssueLabels, Error> {
+        let content = std::fs::read_to_string(path).map_err(|source| Error::Read { path: path.to_string(), source })?;
+        let issues: Vec<Value> = serde_json::from_str(&content).map_err(|e| Error::InvalidFile {
+            kind: "--issue-labels file (expected a JSON array)",
+            path: path.to_string(),
+            message: e.to_string(),
+        })?;
+        let labels = issues
+            .iter()
+            .filter_map(|issue| {
+                let number = issue.get("number").or_else(|| issue.get("iid"))?.as_u64()?;
+                let labels = issue
+                    .get("labels")?
+                    .as_array()?
+                    .iter()
+                    .filter_map(|label| label.as_str().or_else(|| label.get("name")?.as_str()))
+                    .map(|label| label.to_lowercase())
+                    .collect();
+                Some((number, labels))
+            })
+            .collect();
+        Ok(IssueLabels { labels })
+    }
+
+    /// Lowercased labels of every issue `commit` references.
+    fn of<'a>(&'a self, commit: &'a CommitRecord) -> impl Iterator<Item = &'a str> {
+        commit
+            .issues
+            .iter()
+            .filter_map(|number| self.labels.get(number))
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// Whether `commit` references an issue carrying one of `wanted` (lowercased).
+    pub fn matches(&self, commit: &CommitRecord, wanted: &HashSet<String>) -> bool {
+        self.of(commit).any(|label| wanted.contains(label))
+    }
+
+    /// The highest weight among the commit's labels, or 1 when none is weighted.
+    pub fn weight(&self, commit: &CommitRecord, weights: &HashMap<String, u64>) -> u64 {
+        self.of(commit).filter_map(|label| weights.get(label)).copied().max().unwrap_or(1)
+    }
+}
+
+/// Parses `--label-weight LABEL=WEIGHT` values, keyed by lowercased label.
+pub fn parse_label_weights(values: &[String]) -> Result<HashMap<String, u64>, Error> {
+    values
+        .iter()
+        .map(|value| {
+            let parsed = value
+                .rsplit_once('=')
+                .and_then(|(label, weight)| Some((label.trim().to_lowercase(), weight.trim().parse().ok()?)));
+            parsed.ok_or_else(|| {
+                Error::Usage(format!(
+                    "invalid --label-weight '{}', expected LABEL=WEIGHT with a whole number",
+                    value
+                ))
+            })
+        })
+        .collect()
+}
@@ -1,897 +1,3112 @@
-use clap::Parser;
-use std::collections::{BTreeMap, HashMap};
-use std::fs::File;
-use std::io::Write;
+mod avatar;
+mod branches;
+mod budget;
+mod busfactor;
+mod cache;
+mod clipboard;
+mod config;
+mod digest;
+mod error;
+mod explain;
+mod forge;
+mod hotspots;
+mod impact;
+mod i18n;
+mod html;
+mod ics;
+mod identity;
+mod labels;
+mod metadata;
+mod overlap;
+mod ownership;
+mod period;
+mod policy;
+mod repo;
+mod selftest;
+mod serve;
+mod svg;
+mod template;
+mod velocity;
+mod watch;
+
+use branches::print_author_branches;
+use budget::{apply_memory_limit, diff_commits, parse_size};
+use busfactor::{commit_owners, print_bus_factor, BusFactor};
+use cache::{clear_cache, print_cache_info, StatsCache};
+use chrono::{Datelike, Local, Months, NaiveDate, TimeZone, Weekday};
+use clipboard::{copy_output, is_copy_run};
+use git2::Oid;
+use clap::{Parser, Subcommand};
+use config::apply_config;
+use digest::print_digest;
+use hotspots::print_hotspots;
+use impact::print_impact;
+use i18n::{labels, Labels, LANGUAGES};
+use html::{generate_html_dir, generate_html_fragment, generate_html_index, generate_html_report, generate_html_split, generate_html_tabs, stable_hash, SIZE_STRATEGIES};
+use ics::activity_calendar;
+use identity::print_identity_report;
+use labels::{parse_label_weights, IssueLabels};
+use metadata::ReportMetadata;
+use ownership::print_ownership_report;
+use period::{apply_period, previous_period, print_comparison, PreviousPeriod, PERIODS};
+use policy::{print_policy_report, Policy};
+use error::{Error, OrExit, EXIT_VIOLATIONS};
+use explain::print_explanation;
+use repo::{disambiguate_names, parse_date, parse_option_date, CommitRecord, PathFilter, Repo, WalkOptions, DEFAULT_RENAME_THRESHOLD};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::Serialize;
+use selftest::selftest;
+use serve::serve;
+use svg::render_svg;
+use unicode_width::UnicodeWidthStr;
+use velocity::{print_velocity, velocities, ContributorMeta, Velocity};
+use watch::{is_repeat_run, is_watch_run, watch};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::IsTerminal;
+use std::path::Path;
 use std::process::Command;
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(name = "git-stats")]
 #[command(about = "Generate git commit statistics per contributor (commits & lines changed)")]
 struct Args {
-    #[arg(short, long, help = "Branch to analyze")]
-    branch: Option<String>,
+    #[arg(short = 'C', long, global = true, value_name = "PATH", action = clap::ArgAction::Append, help = "Analyze the repository at PATH instead of the current directory; repeat to aggregate several repositories")]
+    repo: Vec<String>,
 
-    #[arg(short, long, help = "Start date (e.g., 2025-01-01)")]
+    #[arg(long, value_name = "PATH", help = "Aggregate the repositories listed in this file (one path per line)")]
+    repos_file: Option<String>,
+
+    #[arg(long, help = "With several repositories, add a per-repository breakdown of contributors")]
+    per_repo: bool,
+
+    #[arg(short, long, global = true, action = clap::ArgAction::Append, help = "Branch to analyze; repeat with --html for one report with a tab per branch")]
+    branch: Vec<String>,
+
+    #[arg(value_name = "REVSPEC", help = "Revision or range to analyze (e.g. v1.0..v2.0) instead of a branch")]
+    revspec: Option<String>,
+
+    #[arg(long, value_name = "RANGE", action = clap::ArgAction::Append, help = "Revision range to analyze, e.g. v1.0..v2.0 (same as the positional REVSPEC); repeat with --html for tabs")]
+    range: Vec<String>,
+
+    #[arg(short, long, global = true, help = "Start date (e.g., 2025-01-01)")]
+    since: Option<String>,
+
+    #[arg(short, long, global = true, help = "End date (e.g., 2025-12-31)")]
+    until: Option<String>,
+
+    #[arg(long, global = true, value_parser = PERIODS, conflicts_with_all = ["since", "until"], help = "Analyze a calendar period up to today (this-week, this-month, this-quarter, this-year) or the last complete one (last-week, ...)")]
+    period: Option<String>,
+
+    #[arg(long, requires = "period", help = "Compare with the period before --period (the same number of days for one in progress) in every output")]
+    with_previous: bool,
+
+    #[arg(long, global = true, value_name = "DATE", help = "Compute everything from history as it stood at DATE (the branch's tip then, blame included)")]
+    as_of: Option<String>,
+
+    #[arg(short, long, global = true, action = clap::ArgAction::Append, help = "Merge authors (format: Name,Alias1,Alias2 into first name, or Alias=Name; exact names)")]
+    merge: Vec<String>,
+
+    #[arg(long, global = true, help = "Merge author names that differ only in case, accents or spacing under their most used spelling")]
+    merge_fold_case: bool,
+
+    #[arg(long, global = true, value_parser = ["author", "committer", "both"], help = "Whom commits are credited to: author (default), committer (who applied them), or both (authors, plus an Applied column of others' commits each person committed)")]
+    credit: Option<String>,
+
+    #[arg(short, long, help = "Show visual graph of contributions after the table")]
+    graph: bool,
+
+    #[arg(long, help = "Show commits by weekday and hour (author local time) for the team and each contributor, in the terminal or HTML report")]
+    punchcard: bool,
+
+    #[arg(long, help = "Show in the HTML report how much contributors' active hours (UTC) overlap, pair by pair")]
+    hours_overlap: bool,
+
+    #[arg(long, value_parser = ["table-first", "graph-first"], help = "Order of table and graphs with --graph (default: table-first)")]
+    layout: Option<String>,
+
+    #[arg(long, help = "Generate HTML report with commits/lines toggle, period selector, and charts")]
+    html: Option<Option<String>>,
+
+    #[arg(long, value_name = "N", help = "Only show the N biggest contributors (by --sort) and fold the rest into an \"Others\" row and chart series")]
+    top: Option<usize>,
+
+    #[arg(long, value_name = "PERCENT", help = "In the HTML stacked chart, fold contributors below this share into an \"Others\" band")]
+    others_below: Option<f64>,
+
+    #[arg(long, value_name = "DIR", help = "Write the HTML report as index.html + data.json + app.js + style.css in DIR")]
+    html_split: Option<String>,
+
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, help = "Shrink the --html page when it would exceed SIZE (e.g. 5M), with the --size-strategy steps")]
+    max_report_size: Option<u64>,
+
+    #[arg(long, value_delimiter = ',', value_parser = SIZE_STRATEGIES, requires = "max_report_size", help = "Steps --max-report-size may take, in order: daily (coarser buckets, no calendar), fold (fewer contributors, the rest as Others), split (data in scripts beside the page); default all")]
+    size_strategy: Vec<String>,
+
+    #[arg(long, value_name = "DIR", help = "Write a report per repository and an index.html linking them in DIR")]
+    html_index: Option<String>,
+
+    #[arg(long, value_name = "DIR", help = "Write the HTML report as index.html in DIR plus a page per contributor with their commits, most-touched files, daily activity and aliases")]
+    html_dir: Option<String>,
+
+    #[arg(long, value_name = "PATH", help = "Write the report as an embeddable fragment (no <html>/<head>, scoped CSS) to PATH, or '-' for stdout")]
+    html_fragment: Option<String>,
+
+    #[arg(long, value_name = "PATH", help = "Write the activity-over-time and per-contributor charts as a static SVG image to PATH")]
+    svg: Option<String>,
+
+    #[arg(long, value_parser = ["ics"], help = "Export daily activity as an iCalendar file (ics) instead of printing the table")]
+    export: Option<String>,
+
+    #[arg(long, value_name = "PATH", default_value = "contrib-activity.ics", requires = "export", help = "File written by --export")]
+    export_path: String,
+
+    #[arg(long, value_parser = ["team", "contributor"], requires = "export", help = "One --export event per day for the team (default), or per contributor and day")]
+    export_by: Option<String>,
+
+    #[arg(long, value_name = "KIND=URL", action = clap::ArgAction::Append, help = "Link commits (commit=URL) or contributors (author=URL) in the HTML report; placeholders {web}, {hash}, {name}, {email}, {login}")]
+    forge_url_template: Vec<String>,
+
+    #[arg(long, value_name = "[REPO=]URL", action = clap::ArgAction::Append, help = "Web or clone URL of the forge to link to instead of the origin remote; prefix with REPO= to set it for one of several repositories")]
+    remote_url: Vec<String>,
+
+    #[arg(long, help = "Show contributor pictures in the HTML report: GitHub avatars for GitHub private commit emails, Gravatar otherwise (loaded by the browser; initials stay when unavailable)")]
+    avatars: bool,
+
+    #[arg(long, value_name = "ORG", requires = "avatars", help = "With --avatars, show the GitHub avatar of members of ORG, asking the GitHub API who authored each contributor's latest commit (set GITHUB_TOKEN to include private members)")]
+    github_org: Option<String>,
+
+    #[arg(long, value_name = "FILE", help = "Page template for --html, tabs and --html-split, with {{title}}, {{theme}}, {{{head}}}, {{{report}}} or one placeholder per section, and {{{scripts}}}")]
+    template: Option<String>,
+
+    #[arg(long, value_parser = ["dark", "light", "auto"], help = "Color theme of HTML output: dark (default), light, or auto to follow the viewer's system setting; reports also get a toggle")]
+    theme: Option<String>,
+
+    #[arg(long, value_name = "DIR", help = "Embed Chart.js from local files in DIR instead of loading it from a CDN")]
+    inline_assets: Option<String>,
+
+    #[arg(short, long, help = "Open HTML report after generation (optionally specify app, e.g. 'Safari', 'Firefox')")]
+    open: Option<Option<String>>,
+
+    #[arg(long, help = "Keep running and regenerate the table or report whenever the analyzed branch gets new commits")]
+    watch: bool,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 5,
+        value_parser = clap::value_parser!(u64).range(1..),
+        requires = "watch",
+        help = "How often --watch checks for new commits"
+    )]
+    watch_interval: u64,
+
+    #[arg(long, help = "Also copy the output to the system clipboard: the table as Markdown, csv as TSV, other formats as printed")]
+    copy: bool,
+
+    #[arg(long, help = "End the table with a Markdown comment recording how it was made (version, revisions and options), for archived tables")]
+    metadata_comment: bool,
+
+    #[arg(long, global = true, value_parser = ["commits", "lines", "net", "files", "score"], help = "Sort by: commits (default), lines (per --lines-metric), net (added - deleted), files changed or recency score (see --half-life)")]
+    sort: Option<String>,
+
+    #[arg(long, global = true, value_parser = ["added", "deleted", "sum", "net"], help = "What counts as lines for sorting, percentages, charts and exports: sum (added + deleted, default), added, deleted or net")]
+    lines_metric: Option<String>,
+
+    #[arg(long, global = true, help = "Leave merge commits out of all statistics")]
+    no_merges: bool,
+
+    #[arg(long, global = true, help = "Follow only the first parent of merges (mainline history); merges carry their branch's changes")]
+    first_parent: bool,
+
+    #[arg(long, global = true, help = "Ignore .git-contrib-stats.toml and ~/.config/git-contrib-stats/config.toml")]
+    no_config: bool,
+
+    #[arg(long, global = true, value_name = "NAME", conflicts_with = "no_config", help = "Apply the settings of [profile.NAME] from the config files")]
+    profile: Option<String>,
+
+    #[arg(long, help = "Don't resolve identities through .mailmap or shared email addresses")]
+    no_mailmap: bool,
+
+    #[arg(long, help = "Add columns with each contributor's commits in the last 30 and 90 days")]
+    recent: bool,
+
+    #[arg(long, help = "Print repository-level aggregates for the period before the table")]
+    summary: bool,
+
+    #[arg(long, value_name = "DAYS", value_parser = parse_half_life, help = "Add a Score column counting each commit by recency: 1 at the end of the period, halving every DAYS days before (--sort score uses 90 by default)")]
+    half_life: Option<f64>,
+
+    #[arg(long, help = "Add a Functions column counting the functions and sections each commit changes, from the diff's hunk headers (diffs every commit again)")]
+    functions: bool,
+
+    #[arg(long, help = "Show where each contributor falls in the team distribution (quartiles)")]
+    benchmarks: bool,
+
+    #[arg(long, help = "Show commits and lines per available working day, from contributor availability in the config file")]
+    velocity: bool,
+
+    /// Availability per contributor, from the config file's `[contributors."Name"]` tables
+    #[arg(skip)]
+    contributors: HashMap<String, ContributorMeta>,
+
+    /// Days nobody is expected to work, from the config file
+    #[arg(skip)]
+    holidays: Vec<String>,
+
+    /// The one repository, by its name in reports, that a `serve` request asks for
+    #[arg(skip)]
+    only_repo: Option<String>,
+
+    #[arg(long, help = "Print the bus factor overall and per top-level directory after the table")]
+    bus_factor: bool,
+
+    #[arg(long, value_parser = ["commits", "lines"], help = "Bus factor basis: commits (default) or surviving lines from blame")]
+    bus_factor_by: Option<String>,
+
+    #[arg(long, value_name = "PERCENT", default_value_t = 50.0, help = "Share of work the bus factor's contributors must exceed")]
+    bus_factor_threshold: f64,
+
+    #[arg(long, value_name = "PERCENT", help = "List contributors whose commits dropped by at least PERCENT across recent periods")]
+    fading: Option<f64>,
+
+    #[arg(long, default_value_t = 30, help = "Length in days of each period compared by --fading")]
+    fading_window: u32,
+
+    #[arg(long, default_value_t = 3, help = "Number of consecutive periods compared by --fading")]
+    fading_periods: u32,
+
+    #[arg(long, value_parser = ["table", "json", "csv", "digest"], help = "Output format: table (default), json, csv or digest (a short text summary of the period, this week by default)")]
+    format: Option<String>,
+
+    #[arg(long, help = "Include per-date series in machine-readable output")]
+    series: bool,
+
+    #[arg(long, help = "Exclude the repository's initial (root) commit from line statistics")]
+    exclude_initial: bool,
+
+    #[arg(long, help = "Leave out bursts of commits that look like a repository import or migration")]
+    exclude_imports: bool,
+
+    #[arg(long, help = "Leave out the lines of commits that mostly convert line endings (CRLF/LF)")]
+    exclude_line_endings: bool,
+
+    #[arg(long, help = "Point out commits that mostly convert line endings (CRLF/LF) on stderr, without leaving anything out")]
+    flag_line_endings: bool,
+
+    #[arg(long, global = true, help = "Ignore whitespace when counting changed lines (like git diff -w), so reindented lines don't count")]
+    ignore_whitespace: bool,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "PERCENT",
+        default_value_t = DEFAULT_RENAME_THRESHOLD,
+        value_parser = clap::value_parser!(u16).range(1..=100),
+        help = "Similarity from which a deleted and an added file count as a rename, whose unchanged lines aren't counted (like git diff -M)"
+    )]
+    find_renames: u16,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "PERCENT",
+        num_args = 0..=1,
+        default_missing_value = "50",
+        value_parser = clap::value_parser!(u16).range(1..=100),
+        help = "Also detect files copied from one the commit modifies, counting only their changed lines (like git diff -C; default 50%)"
+    )]
+    find_copies: Option<u16>,
+
+    #[arg(long, help = "Leave out the lines of commits that mostly reformat code (whitespace, wrapping, quotes, trailing commas)")]
+    detect_format_only: bool,
+
+    #[arg(long, value_name = "FILES", default_value_t = DEFAULT_IMPORT_FILES, help = "Files changed within a minute of commit time above which commits are treated as an import")]
+    import_files: u64,
+
+    #[arg(long, global = true, action = clap::ArgAction::Append, help = "Only count changes to files matching this pathspec (repeatable)")]
+    path: Vec<String>,
+
+    #[arg(long, global = true, action = clap::ArgAction::Append, help = "Ignore changes to files matching this pathspec (repeatable)")]
+    exclude_path: Vec<String>,
+
+    #[arg(long, global = true, value_delimiter = ',', value_name = "EXTS", help = "Only count changes to files with these extensions (e.g. 'rs,ts'); repeatable")]
+    ext: Vec<String>,
+
+    #[arg(long, global = true, conflicts_with = "offline", help = "Only count changes to files that still exist at the analyzed tip")]
+    existing_files_only: bool,
+
+    #[arg(long, global = true, help = "Count files marked linguist-generated/linguist-vendored in .gitattributes or listed in .statsignore")]
+    include_generated: bool,
+
+    #[arg(long, help = "Add a column with each contributor's top languages by lines changed")]
+    languages: bool,
+
+    #[arg(long, help = "Add an Activity column with a sparkline of each contributor's commits over the period")]
+    activity: bool,
+
+    #[arg(long, value_parser = ["json"], help = "Emit progress events on stderr (json: one JSON object per line)")]
+    progress: Option<String>,
+
+    #[arg(short, long, global = true, help = "Don't draw progress bars on stderr")]
+    quiet: bool,
+
+    #[arg(long, global = true, value_name = "N", help = "Diff commits on at most N threads per repository (default: one per CPU)")]
+    max_parallel: Option<usize>,
+
+    #[arg(long, global = true, value_name = "SIZE", value_parser = parse_size, help = "Rough memory budget (e.g. 512M, 2G) for the git object cache and pending commit stats; stats are written to the cache file as the budget fills")]
+    max_memory: Option<u64>,
+
+    #[arg(long, help = "Don't color the terminal table (also NO_COLOR; color is only used on a terminal)")]
+    no_color: bool,
+
+    #[arg(long, action = clap::ArgAction::Append, help = "Attribute commits by trailer value instead of author (e.g. 'On-behalf-of'); repeatable, first match wins")]
+    trailer: Vec<String>,
+
+    #[arg(long, help = "Leave out bot accounts (names or emails like '*[bot]*', renovate, github-actions)")]
+    exclude_bots: bool,
+
+    #[arg(long, value_name = "PATTERN", action = clap::ArgAction::Append, help = "Leave out authors whose name or email matches this pattern ('*' wildcards, case-insensitive); repeatable")]
+    exclude_author: Vec<String>,
+
+    #[arg(long, value_name = "PATH", help = "Only include the identities listed in this file (one name, email or 'Name <email>' per line)")]
+    authors_file: Option<String>,
+
+    #[arg(long, help = "With --authors-file, fold everyone not listed into \"External\" instead of dropping them")]
+    fold_external: bool,
+
+    #[arg(long, value_parser = ["monday", "sunday"], help = "First day of the week for weekly buckets (default: monday)")]
+    week_start: Option<String>,
+
+    #[arg(long, value_parser = LANGUAGES, help = "Language of the terminal table headers: en (default) or fr")]
+    lang: Option<String>,
+
+    #[arg(long, value_parser = GRANULARITIES, help = "Time bucket for graphs and series: day (default), week, month, quarter or year")]
+    by: Option<String>,
+
+    #[arg(long, help = "Report emails used under several names (and names with several emails) and suggest .mailmap entries")]
+    check_identities: bool,
+
+    #[arg(long, value_name = "FILE", help = "Issue labels as JSON (gh issue list --json number,labels), for --label and --label-weight")]
+    issue_labels: Option<String>,
+
+    #[arg(long, requires = "issue_labels", help = "Only count commits referencing an issue with this label (repeatable)")]
+    label: Vec<String>,
+
+    #[arg(long, value_name = "LABEL=WEIGHT", requires = "issue_labels", help = "Count commits referencing an issue with LABEL WEIGHT times in a Weighted column (repeatable)")]
+    label_weight: Vec<String>,
+
+    #[arg(long, value_name = "FILE", help = "Check commits against a JSON policy (allowed_domains, require_signoff, max_lines, max_files) instead of printing stats")]
+    policy: Option<String>,
+
+    #[arg(long, requires = "policy", help = "Exit with status 1 when any commit violates the --policy")]
+    fail_on_violation: bool,
+
+    #[arg(long, value_name = "AUTHOR", help = "Explain one contributor's totals: every counted commit with its lines, and every commit of theirs left out with the rule responsible")]
+    explain: Option<String>,
+
+    #[arg(long, value_name = "AUTHOR", conflicts_with = "explain", help = "Show one contributor's commits on the analyzed branch and those on other branches not merged into it yet")]
+    author_branches: Option<String>,
+
+    #[arg(long, help = "Report surviving lines per contributor and per top-level directory, from blame on the analyzed tree")]
+    ownership: bool,
+
+    #[arg(long, global = true, conflicts_with = "refresh", help = "Use only the stats cache: history is not walked, commits not yet cached are skipped")]
+    offline: bool,
+
+    #[arg(long, global = true, help = "Recompute every commit's stats and overwrite the stats cache")]
+    refresh: bool,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum Commands {
+    /// List the files with the most commits and line churn, with their top contributors
+    Hotspots {
+        #[arg(long, default_value_t = 20, help = "Number of files to list")]
+        limit: usize,
+    },
+    /// Classify commits as feature-, fix- or refactor-like from their message and diff, and
+    /// show each contributor's mix
+    Impact,
+    /// Serve the HTML report and JSON stats over HTTP for the analyzed repositories (GET /,
+    /// /api/stats.json, /api/repositories)
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:8080", help = "Address to listen on")]
+        listen: String,
+        #[arg(long, value_name = "N", help = "Port to listen on, replacing the one in --listen")]
+        port: Option<u16>,
+        #[arg(long, value_name = "PATH", help = "File of accepted bearer tokens, one per line (also GIT_CONTRIB_STATS_TOKEN)")]
+        token_file: Option<String>,
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            value_parser = clap::value_parser!(u64).range(1..),
+            help = "Have the served page check for new commits every SECONDS and reload when there are"
+        )]
+        reload_every: Option<u64>,
+    },
+    /// Build a small synthetic repository and check every report on it, to confirm this
+    /// build behaves correctly on this platform
+    Selftest,
+    /// Inspect or clear the per-repository stats cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum CacheAction {
+    /// Show the cache file, its size and what it holds
+    Info,
+    /// Delete the cache file
+    Clear,
+}
+
+#[derive(Clone, Copy, Default, Serialize)]
+struct ContributorStats {
+    commits: u64,
+    /// Commits touching no files (empty commits, merges without a first-parent diff)
+    empty_commits: u64,
+    lines_added: u64,
+    lines_deleted: u64,
+    /// Files touched, summed over commits (a file changed twice counts twice)
+    files_changed: u64,
+    /// Lines added - deleted
+    net_lines: i64,
+    /// Other authors' commits this contributor committed, with `--credit both`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    applied_commits: Option<u64>,
+    /// Commits counted with their `--label-weight`, when label weighting is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    weighted_commits: Option<u64>,
+    /// Commits weighted by recency, with `--half-life` or `--sort score`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<f64>,
+    /// Functions and sections changed, summed over commits, with `--functions`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    functions_touched: Option<u64>,
+}
+
+impl ContributorStats {
+    fn add(&mut self, commit: &CommitRecord) {
+        self.commits += 1;
+        if let Some(weight) = commit.weight {
+            *self.weighted_commits.get_or_insert(0) += weight;
+        }
+        if let Some(recency) = commit.recency {
+            *self.score.get_or_insert(0.0) += recency;
+        }
+        if let Some(functions) = commit.functions {
+            *self.functions_touched.get_or_insert(0) += functions;
+        }
+        if commit.files_changed == 0 {
+            self.empty_commits += 1;
+        }
+        self.lines_added += commit.lines_added;
+        self.lines_deleted += commit.lines_deleted;
+        self.files_changed += commit.files_changed;
+        self.net_lines += commit.lines_added as i64 - commit.lines_deleted as i64;
+    }
+}
+
+/// What "lines" means wherever contributors are compared by lines (`--lines-metric`).
+#[derive(Clone, Copy, PartialEq)]
+enum LinesMetric {
+    Added,
+    Deleted,
+    Sum,
+    Net,
+}
+
+impl LinesMetric {
+    fn from_args(args: &Args) -> LinesMetric {
+        match args.lines_metric.as_deref() {
+            Some("added") => LinesMetric::Added,
+            Some("deleted") => LinesMetric::Deleted,
+            Some("net") => LinesMetric::Net,
+            _ => LinesMetric::Sum,
+        }
+    }
+
+    fn value(self, added: u64, deleted: u64) -> i64 {
+        match self {
+            LinesMetric::Added => added as i64,
+            LinesMetric::Deleted => deleted as i64,
+            LinesMetric::Sum => (added + deleted) as i64,
+            LinesMetric::Net => added as i64 - deleted as i64,
+        }
+    }
+
+    fn of(self, stats: &ContributorStats) -> i64 {
+        self.value(stats.lines_added, stats.lines_deleted)
+    }
+
+    /// The `--lines-metric` value.
+    fn name(self) -> &'static str {
+        match self {
+            LinesMetric::Added => "added",
+            LinesMetric::Deleted => "deleted",
+            LinesMetric::Sum => "sum",
+            LinesMetric::Net => "net",
+        }
+    }
+
+    /// How reports name the metric, e.g. "lines changed".
+    fn label(self) -> &'static str {
+        match self {
+            LinesMetric::Added => "lines added",
+            LinesMetric::Deleted => "lines deleted",
+            LinesMetric::Sum => "lines changed",
+            LinesMetric::Net => "net lines",
+        }
+    }
+}
+
+/// The metric percentages are shares of: `Some` lines metric with `--sort lines`,
+/// `None` for commits.
+fn share_metric(args: &Args) -> Option<LinesMetric> {
+    (args.sort.as_deref() == Some("lines")).then(|| LinesMetric::from_args(args))
+}
+
+/// `value` as a percentage of `total`, the sum of the positive values. Contributors
+/// with negative net lines get no share rather than a negative one.
+fn lines_percent(value: i64, total: i64) -> f64 {
+    if total > 0 {
+        value.max(0) as f64 / total as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Orders contributors by `--sort`: commits (default), lines per `--lines-metric`, net
+/// lines, files changed or recency score.
+fn sort_contributors<N>(rows: &mut [(N, &ContributorStats)], args: &Args) {
+    let metric = LinesMetric::from_args(args);
+    match args.sort.as_deref() {
+        Some("lines") => rows.sort_by_key(|(_, s)| std::cmp::Reverse(metric.of(s))),
+        Some("net") => rows.sort_by_key(|(_, s)| std::cmp::Reverse(s.net_lines)),
+        Some("files") => rows.sort_by_key(|(_, s)| std::cmp::Reverse(s.files_changed)),
+        Some("score") => rows.sort_by(|a, b| b.1.score.unwrap_or(0.0).total_cmp(&a.1.score.unwrap_or(0.0))),
+        _ => rows.sort_by_key(|(_, s)| std::cmp::Reverse(s.commits)),
+    }
+}
+
+/// Contributors in report order (`--sort`), with the `--top` "Others" row last.
+fn ranked_contributors<'a>(
+    stats: &'a HashMap<String, ContributorStats>,
+    args: &Args,
+) -> Vec<(&'a String, &'a ContributorStats)> {
+    let mut rows: Vec<(&String, &ContributorStats)> = stats.iter().collect();
+    sort_contributors(&mut rows, args);
+    rows.sort_by_key(|(name, _)| is_others(args, name));
+    rows
+}
+
+/// With `--top N`, maps the authors of everyone outside the N biggest contributors to a
+/// single "Others (K)" identity, so every report shows them as one row or series.
+fn fold_others(commits: &[CommitRecord], author_mapping: &HashMap<String, String>, args: &Args) -> HashMap<String, String> {
+    let mut mapping = author_mapping.clone();
+    let Some(top) = args.top else { return mapping };
+    let stats = contributor_stats(commits, author_mapping);
+    let ranked = ranked_contributors(&stats, args);
+    if ranked.len() <= top {
+        return mapping;
+    }
+    let folded: HashSet<&String> = ranked[top..].iter().map(|(name, _)| *name).collect();
+    // Folding again (as --max-report-size does) counts those already in "Others (K)"
+    let count: usize = folded.iter().map(|name| folded_count(args, name)).sum();
+    let label = format!("Others ({})", count);
+    for commit in commits {
+        if folded.contains(canonical_name(&commit.author, author_mapping)) {
+            mapping.insert(commit.author.clone(), label.clone());
+        }
+    }
+    mapping
+}
+
+/// Whether `name` is the row `--top` folds the remaining contributors into.
+fn is_others(args: &Args, name: &str) -> bool {
+    args.top.is_some() && name.starts_with("Others (") && name.ends_with(')')
+}
+
+/// How many contributors the row `name` stands for: K for "Others (K)", else 1.
+fn folded_count(args: &Args, name: &str) -> usize {
+    if is_others(args, name) {
+        name[8..name.len() - 1].parse().unwrap_or(1)
+    } else {
+        1
+    }
+}
+
+/// Each contributor's most used email, or `name:<lowercased name>` when their commits have none.
+fn primary_identities(commits: &[CommitRecord], author_mapping: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut keys: HashMap<&String, HashMap<String, u64>> = HashMap::new();
+    for commit in commits {
+        let key = match commit.email.trim() {
+            "" => format!("name:{}", commit.author.to_lowercase()),
+            email => email.to_lowercase(),
+        };
+        *keys
+            .entry(canonical_name(&commit.author, author_mapping))
+            .or_default()
+            .entry(key)
+            .or_insert(0) += 1;
+    }
+    keys.into_iter()
+        .map(|(name, keys)| {
+            // Most used email wins; ties go to the alphabetically first
+            let key = keys
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+                .map(|(key, _)| key)
+                .unwrap_or_default();
+            (name.clone(), key)
+        })
+        .collect()
+}
+
+/// Stable id of each contributor, for joining machine-readable exports across runs: a
+/// hash of the alphabetically first email (after `.mailmap`) of their reported commits,
+/// or of their name when none has one. Only their own commits count, so it survives
+/// display-name changes and doesn't move with other authors' history. The `--top` row is
+/// `others`.
+fn contributor_ids(commits: &[CommitRecord], author_mapping: &HashMap<String, String>, args: &Args) -> HashMap<String, String> {
+    // Emails, then `name:<lowercased name>` keys for commits without one
+    let mut keys: HashMap<&String, (BTreeSet<String>, BTreeSet<String>)> = HashMap::new();
+    for commit in commits {
+        let (emails, names) = keys.entry(canonical_name(&commit.author, author_mapping)).or_default();
+        match commit.email.trim() {
+            "" => names.insert(format!("name:{}", commit.author.to_lowercase())),
+            email => emails.insert(email.to_lowercase()),
+        };
+    }
+    keys.into_iter()
+        .map(|(name, (emails, names))| {
+            let id = match emails.first().or(names.first()) {
+                _ if is_others(args, name) => "others".to_string(),
+                key => format!("{:016x}", stable_hash(key.map_or("", String::as_str))),
+            };
+            (name.clone(), id)
+        })
+        .collect()
+}
+
+/// One repository's contributor totals, for `--per-repo`.
+#[derive(Serialize)]
+struct RepoBreakdown {
+    name: String,
+    contributors: Vec<RepoContributor>,
+}
+
+#[derive(Serialize)]
+struct RepoContributor {
+    name: String,
+    id: String,
+    #[serde(flatten)]
+    stats: ContributorStats,
+}
+
+fn repo_breakdowns(commits: &[CommitRecord], author_mapping: &HashMap<String, String>, args: &Args) -> Vec<RepoBreakdown> {
+    let ids = contributor_ids(commits, author_mapping, args);
+    let mut by_repo: BTreeMap<&str, HashMap<&String, ContributorStats>> = BTreeMap::new();
+    for commit in commits {
+        by_repo
+            .entry(&commit.repo)
+            .or_default()
+            .entry(canonical_name(&commit.author, author_mapping))
+            .or_default()
+            .add(commit);
+    }
+    by_repo
+        .into_iter()
+        .map(|(name, stats)| {
+            let mut rows: Vec<(&String, &ContributorStats)> = stats.iter().map(|(n, s)| (*n, s)).collect();
+            sort_contributors(&mut rows, args);
+            RepoBreakdown {
+                name: name.to_string(),
+                contributors: rows
+                    .into_iter()
+                    .map(|(name, stats)| RepoContributor {
+                        name: name.clone(),
+                        id: ids[name].clone(),
+                        stats: *stats,
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+fn print_per_repo(breakdowns: &[RepoBreakdown], share: Option<LinesMetric>, color: bool, labels: &Labels) {
+    for repo in breakdowns {
+        println!();
+        println!("{}", repo.name);
+        let rows: Vec<(&String, &ContributorStats)> = repo.contributors.iter().map(|c| (&c.name, &c.stats)).collect();
+        print_table(&rows, share, None, None, None, color, labels);
+    }
+}
+
+/// A repository to analyze and the revision selected in it.
+struct Source {
+    repo: Repo,
+    rev: String,
+    /// How `rev` is shown in reports (the ref name, even when `--as-of` pinned it to a commit)
+    label: String,
+}
+
+/// Repository paths from `-C` and `--repos-file`, or the current directory.
+fn repo_paths(args: &Args) -> Result<Vec<String>, Error> {
+    let mut paths = args.repo.clone();
+    if let Some(file) = &args.repos_file {
+        let content = std::fs::read_to_string(file).map_err(|source| Error::Read { path: file.clone(), source })?;
+        paths.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                .map(String::from),
+        );
+    }
+    if paths.is_empty() {
+        paths.push(".".to_string());
+    }
+    Ok(paths)
+}
+
+/// Branches and ranges given with `--range`, `REVSPEC` or `--branch`, in that order.
+fn requested_revisions(args: &Args) -> Vec<String> {
+    args.range
+        .iter()
+        .chain(&args.revspec)
+        .chain(&args.branch)
+        .cloned()
+        .collect()
+}
+
+/// The revisions analyzed in `repo`: `revs`, or its current branch when empty.
+fn analyzed_revisions(repo: &Repo, revs: &[String]) -> Vec<String> {
+    match revs {
+        [] => repo
+            .current_branch()
+            .or_else(|| repo.fallback_branch().map(|(name, _)| name))
+            .into_iter()
+            .collect(),
+        revs => revs.to_vec(),
+    }
+}
+
+/// Ids of the commits at the tips of `revs` (or of the current branch when empty) in
+/// every repository: a report stays current until this changes.
+fn revision_tips(args: &Args, revs: &[String]) -> Result<String, Error> {
+    let mut tips = Vec::new();
+    for (_, repo) in open_repos(args, false)? {
+        tips.extend(analyzed_revisions(&repo, revs).iter().map(|rev| repo.tip_id(rev).unwrap_or_default()));
+    }
+    Ok(tips.join(","))
+}
+
+/// Opens the repository at each of `repo_paths`, named so that none share a name, keeping
+/// only the one named `only_repo` when set.
+fn open_repos(args: &Args, use_mailmap: bool) -> Result<Vec<(String, Repo)>, Error> {
+    let paths = repo_paths(args)?;
+    let mut repos = paths.iter().map(|path| Repo::open(Path::new(path), use_mailmap)).collect::<Result<Vec<_>, _>>()?;
+    disambiguate_names(&mut repos);
+    let mut repos: Vec<(String, Repo)> = paths.into_iter().zip(repos).collect();
+    if let Some(name) = &args.only_repo {
+        repos.retain(|(_, repo)| repo.name() == name);
+    }
+    Ok(repos)
+}
+
+/// Opens every repository at `rev`, or at its current branch when `None`.
+fn open_sources(args: &Args, rev: Option<&str>) -> Result<Vec<Source>, Error> {
+    let mut sources = Vec::new();
+    for (path, mut repo) in open_repos(args, !args.no_mailmap)? {
+        let rev = match rev.map(String::from).or_else(|| repo.current_branch()) {
+            Some(rev) => rev,
+            None => {
+                let Some((branch, source)) = repo.fallback_branch() else {
+                    return Err(Error::Usage(format!(
+                        "no branch is checked out in {} and none of origin/HEAD, main or master exist; pass --branch",
+                        path
+                    )));
+                };
+                eprintln!("Note: HEAD is not on a branch in {}; analyzing {} (from {})", path, branch, source);
+                branch
+            }
+        };
+        let (rev, label) = match &args.as_of {
+            Some(as_of) => (snapshot_revision(&repo, &rev, as_of)?, format!("{} as of {}", rev, as_of)),
+            None => (rev.clone(), rev),
+        };
+        if args.existing_files_only {
+            repo.restrict_to_tree(&rev)?;
+        }
+        if args.ignore_whitespace {
+            repo.ignore_whitespace();
+        }
+        if args.exclude_line_endings || args.flag_line_endings {
+            repo.measure_line_endings();
+        }
+        if args.detect_format_only {
+            repo.measure_format_only();
+        }
+        repo.detect_similar(args.find_renames, args.find_copies);
+        sources.push(Source { repo, rev, label });
+    }
+    Ok(sources)
+}
+
+/// Pins the tip of `rev` (the right side of a range) to the commit it pointed to at `as_of`.
+fn snapshot_revision(repo: &Repo, rev: &str, as_of: &str) -> Result<String, Error> {
+    let time = parse_date(as_of, true).ok_or_else(|| Error::InvalidDate {
+        option: "--as-of",
+        value: as_of.to_string(),
+    })?;
+    let (from, separator, tip) = match rev.split_once("...") {
+        Some((from, tip)) => (from, "...", tip),
+        None => match rev.split_once("..") {
+            Some((from, tip)) => (from, "..", tip),
+            None => ("", "", rev),
+        },
+    };
+    let tip = if tip.is_empty() { "HEAD" } else { tip };
+    match repo.snapshot(tip, time) {
+        Some(oid) => Ok(format!("{}{}{}", from, separator, oid)),
+        None => Err(Error::Usage(format!("{} has no commits as of {}", tip, as_of))),
+    }
+}
+
+/// The analyzed revision for a single repository, or `name (rev)` for each of several.
+fn revision_label(sources: &[Source]) -> String {
+    match sources {
+        [single] => single.label.clone(),
+        _ => sources
+            .iter()
+            .map(|s| format!("{} ({})", s.repo.name(), s.label))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+/// Reports progress on stderr: newline-delimited JSON events for embedding tools, or a
+/// bar per phase on a terminal (hidden when stderr isn't one).
+struct Progress {
+    json: bool,
+    bar: bool,
+    /// Phase and bar being drawn
+    current: RefCell<Option<(String, ProgressBar)>>,
+}
+
+#[derive(Serialize)]
+struct ProgressEvent<'a> {
+    phase: &'a str,
+    percent: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<&'a str>,
+}
+
+impl Progress {
+    fn new(args: &Args) -> Progress {
+        let json = args.progress.as_deref() == Some("json");
+        Progress {
+            json,
+            bar: !json && !args.quiet,
+            current: RefCell::new(None),
+        }
+    }
+
+    fn emit(&self, phase: &str, done: u64, total: u64, author: Option<&str>) {
+        if !self.json {
+            self.draw(phase, done, total, author);
+            return;
+        }
+        let percent = if total > 0 { (done as f64 / total as f64 * 100.0).min(100.0) } else { 100.0 };
+        let event = ProgressEvent { phase, percent: (percent * 10.0).round() / 10.0, author };
+        if let Ok(line) = serde_json::to_string(&event) {
+            eprintln!("{}", line);
+        }
+    }
+
+    /// Moves the phase's bar to `done` of `total`, starting a new bar when the phase
+    /// changes and clearing it once the phase is complete.
+    fn draw(&self, phase: &str, done: u64, total: u64, author: Option<&str>) {
+        if !self.bar {
+            return;
+        }
+        let mut current = self.current.borrow_mut();
+        if current.as_ref().is_none_or(|(name, _)| name != phase) {
+            if let Some((_, bar)) = current.take() {
+                bar.finish_and_clear();
+            }
+            let bar = ProgressBar::with_draw_target(Some(total), ProgressDrawTarget::stderr());
+            bar.set_style(
+                ProgressStyle::with_template("{prefix:>10} [{bar:30}] {pos}/{len} {wide_msg}")
+                    .unwrap_or_else(|_| ProgressStyle::default_bar())
+                    .progress_chars("=> "),
+            );
+            bar.set_prefix(phase.to_string());
+            *current = Some((phase.to_string(), bar));
+        }
+        let Some((_, bar)) = current.as_ref() else { return };
+        bar.set_length(total);
+        bar.set_position(done);
+        bar.set_message(author.unwrap_or("").to_string());
+        if done >= total {
+            bar.finish_and_clear();
+            *current = None;
+        }
+    }
+}
+
+/// Walks the history once, producing one record per commit.
+fn get_commits(
+    repo: &Repo,
+    branch: &str,
+    args: &Args,
     since: Option<String>,
+    until: Option<String>,
+    progress: &Progress,
+) -> Result<Vec<CommitRecord>, Error> {
+    let paths = PathFilter::new(&args.path, &args.exclude_path, &args.ext, !args.include_generated)?;
+    let options = WalkOptions {
+        since,
+        until,
+        no_merges: args.no_merges,
+        first_parent: args.first_parent,
+    };
+    let mut key = format!(
+        "path={:?};exclude-path={:?};first-parent={}",
+        args.path, args.exclude_path, args.first_parent
+    );
+    if !args.ext.is_empty() {
+        key.push_str(&format!(";ext={:?}", args.ext));
+    }
+    if args.ignore_whitespace {
+        key.push_str(";ignore-whitespace");
+    }
+    if args.exclude_line_endings || args.flag_line_endings {
+        key.push_str(";line-endings");
+    }
+    if args.detect_format_only {
+        key.push_str(";format-only");
+    }
+    if args.find_renames != DEFAULT_RENAME_THRESHOLD {
+        key.push_str(&format!(";find-renames={}", args.find_renames));
+    }
+    if let Some(threshold) = args.find_copies {
+        key.push_str(&format!(";find-copies={}", threshold));
+    }
+    if args.include_generated {
+        key.push_str(";include-generated");
+    } else {
+        key.push_str(&format!(";ignore-rules={:x}", repo.ignore_rules_digest()));
+    }
+    let mut cache = StatsCache::open(repo.git_dir(), key, args.max_memory.is_some());
+    let walk_key = format!("{}|first-parent={}", branch, args.first_parent);
+    let filter = options.filter();
+
+    let ids = if args.offline {
+        let Some(ids) = cache.walk(&walk_key) else {
+            return Err(Error::Usage(format!(
+                "no cached history for '{}' in {}; run once without --offline",
+                branch,
+                repo.name()
+            )));
+        };
+        ids
+    } else {
+        let ids = repo.walk(branch, &options)?;
+        cache.record_walk(&walk_key, &ids);
+        repo.filter_ids(ids, &filter)
+    };
+    let total = ids.len() as u64;
+    let mut last_percent = 0;
+    let mut missing = 0;
+    progress.emit("scan", 0, total, None);
+
+    // Diff what the cache can't answer first, possibly on several threads
+    let todo: Vec<Oid> = if args.offline {
+        Vec::new()
+    } else if args.refresh || args.existing_files_only {
+        ids.clone()
+    } else {
+        ids.iter().copied().filter(|oid| cache.get(*oid).is_none()).collect()
+    };
+    let mut done = total - todo.len() as u64;
+    // Recorded as they arrive, so only the cache (or its journal) keeps the raw stats
+    let mut fresh: HashMap<Oid, Option<CommitRecord>> = HashMap::new();
+    diff_commits(repo, &todo, args, |oid, raw| {
+        fresh.insert(oid, repo.record(oid, &raw, &paths));
+        // With --existing-files-only they depend on the current tip, so aren't cached
+        if !args.existing_files_only {
+            cache.insert(oid, raw);
+        }
+        done += 1;
+        if let Some(percent) = (done * 100).checked_div(total) {
+            if percent > last_percent {
+                last_percent = percent;
+                progress.emit("scan", done, total, None);
+            }
+        }
+    })?;
+
+    let mut commits = Vec::with_capacity(ids.len());
+    for oid in ids {
+        let raw = if args.offline {
+            match cache.take(oid) {
+                Some(raw) if filter.accepts(raw.committer_time, raw.is_merge) => raw,
+                Some(_) => continue,
+                None => {
+                    missing += 1;
+                    continue;
+                }
+            }
+        } else if let Some(record) = fresh.remove(&oid) {
+            commits.extend(record);
+            continue;
+        } else {
+            match cache.take(oid) {
+                Some(raw) => raw,
+                None => {
+                    let raw = repo.raw_commit(oid, &paths, args.first_parent)?;
+                    cache.insert(oid, raw.clone());
+                    raw
+                }
+            }
+        };
+        commits.extend(repo.record(oid, &raw, &paths));
+    }
+    if missing > 0 {
+        eprintln!(
+            "Warning: {} commits in {} have no cached stats for these --path/--first-parent settings and were skipped",
+            missing,
+            repo.name()
+        );
+    }
+    progress.emit("scan", total, total, None);
+    cache.save();
+    Ok(commits)
+}
+
+/// Scans every source. A commit shared by several repositories (forks, mirrors) is
+/// counted once, for the first repository it appears in.
+fn scan_sources(
+    sources: &[Source],
+    args: &Args,
+    since: Option<String>,
+    until: Option<String>,
+    progress: &Progress,
+) -> Result<Vec<CommitRecord>, Error> {
+    let mut commits = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    for source in sources {
+        for commit in get_commits(&source.repo, &source.rev, args, since.clone(), until.clone(), progress)? {
+            if sources.len() == 1 || seen.insert(commit.hash.clone()) {
+                commits.push(commit);
+            }
+        }
+    }
+    Ok(commits)
+}
+
+/// Scans history and applies identity resolution and trailer attribution.
+fn load_commits(
+    sources: &[Source],
+    args: &Args,
+    since: Option<String>,
+    until: Option<String>,
+    progress: &Progress,
+) -> Result<Vec<CommitRecord>, Error> {
+    let mut commits = scan_sources(sources, args, since, until, progress)?;
+
+    if args.credit.as_deref() == Some("committer") {
+        credit_committers(&mut commits);
+    }
+    if !args.no_mailmap {
+        unify_identities(&mut commits);
+    }
+    if args.merge_fold_case {
+        unify_name_spellings(&mut commits);
+    }
+    if args.credit.as_deref() == Some("both") {
+        name_committers(&mut commits);
+    }
+
+    if args.exclude_bots || !args.exclude_author.is_empty() {
+        let bots: &[&str] = if args.exclude_bots { BOT_PATTERNS } else { &[] };
+        commits.retain(|commit| {
+            !bots
+                .iter()
+                .copied()
+                .chain(args.exclude_author.iter().map(String::as_str))
+                .any(|pattern| author_matches(commit, pattern))
+        });
+    }
+
+    if let Some(path) = &args.authors_file {
+        let roster = Roster::load(path)?;
+        if args.fold_external {
+            for commit in commits.iter_mut().filter(|c| !roster.contains(c)) {
+                commit.author = "External".to_string();
+            }
+        } else {
+            commits.retain(|commit| roster.contains(commit));
+        }
+    }
+
+    if let Some(path) = &args.issue_labels {
+        let issue_labels = IssueLabels::load(path)?;
+        if !args.label.is_empty() {
+            let wanted: HashSet<String> = args.label.iter().map(|l| l.to_lowercase()).collect();
+            commits.retain(|commit| issue_labels.matches(commit, &wanted));
+        }
+        if !args.label_weight.is_empty() {
+            let weights = parse_label_weights(&args.label_weight)?;
+            for commit in &mut commits {
+                commit.weight = Some(issue_labels.weight(commit, &weights));
+            }
+        }
+    }
+
+    if let Some(half_life) = half_life(args) {
+        let end = fading_end_date(&args.until);
+        for commit in &mut commits {
+            commit.recency = Some(recency(commit, end, half_life));
+        }
+    }
+
+    if args.functions {
+        let paths = PathFilter::new(&args.path, &args.exclude_path, &args.ext, !args.include_generated)?;
+        let total = commits.len() as u64;
+        for (done, commit) in commits.iter_mut().enumerate() {
+            progress.emit("functions", done as u64, total, None);
+            let source = sources.iter().find(|s| s.repo.name() == commit.repo);
+            if let (Some(source), Ok(oid)) = (source, commit.hash.parse()) {
+                commit.functions = Some(source.repo.functions_touched(oid, &paths, args.first_parent)?);
+            }
+        }
+        progress.emit("functions", total, total, None);
+    }
+
+    if !args.trailer.is_empty() {
+        // Re-key every commit so --merge, sorting and all outputs work on trailer values
+        for commit in &mut commits {
+            commit.author = trailer_value(commit, &args.trailer)
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| format!("(no {})", args.trailer[0]));
+        }
+    }
+
+    Ok(commits)
+}
+
+/// Half-life of `--sort score` without `--half-life`, in days.
+const DEFAULT_HALF_LIFE: f64 = 90.0;
+
+fn parse_half_life(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(days) if days > 0.0 && days.is_finite() => Ok(days),
+        _ => Err(format!("'{}' is not a positive number of days", value)),
+    }
+}
+
+/// Half-life of the recency score, when one is shown or sorted on.
+fn half_life(args: &Args) -> Option<f64> {
+    args.half_life.or((args.sort.as_deref() == Some("score")).then_some(DEFAULT_HALF_LIFE))
+}
+
+/// A commit's weight in the recency score: 1 on `end` (or later), 0.5 one half-life
+/// before, 0.25 two half-lives before, by author date.
+fn recency(commit: &CommitRecord, end: NaiveDate, half_life: f64) -> f64 {
+    let Ok(date) = NaiveDate::parse_from_str(&commit.date, "%Y-%m-%d") else { return 0.0 };
+    let age = (end - date).num_days().max(0) as f64;
+    0.5_f64.powf(age / half_life)
+}
+
+/// Identities allowed by `--authors-file`, matched exactly by name or (case-insensitively) email.
+struct Roster {
+    names: HashSet<String>,
+    emails: HashSet<String>,
+}
+
+impl Roster {
+    /// Reads one identity per line: `Name`, `email@host` or `Name <email@host>`.
+    /// Blank lines and `#` comments are ignored.
+    fn load(path: &str) -> Result<Roster, Error> {
+        let content = std::fs::read_to_string(path).map_err(|source| Error::Read { path: path.to_string(), source })?;
+        let mut roster = Roster {
+            names: HashSet::new(),
+            emails: HashSet::new(),
+        };
+        for line in content.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            match line.split_once('<') {
+                Some((name, email)) => {
+                    if !name.trim().is_empty() {
+                        roster.names.insert(name.trim().to_string());
+                    }
+                    roster.emails.insert(email.trim_end_matches('>').trim().to_lowercase());
+                }
+                None if line.contains('@') => {
+                    roster.emails.insert(line.to_lowercase());
+                }
+                None => {
+                    roster.names.insert(line.to_string());
+                }
+            }
+        }
+        Ok(roster)
+    }
+
+    fn contains(&self, commit: &CommitRecord) -> bool {
+        self.names.contains(&commit.author) || self.emails.contains(&commit.email)
+    }
+}
+
+/// Identities treated as automation by `--exclude-bots`.
+const BOT_PATTERNS: &[&str] = &[
+    "*[bot]*",
+    "*(bot)",
+    "*-bot",
+    "renovate*",
+    "dependabot*",
+    "github-actions*",
+];
+
+/// Case-insensitive glob match of `pattern` against the commit's author name or email.
+fn author_matches(commit: &CommitRecord, pattern: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    glob_match(&pattern, &commit.author.to_lowercase()) || glob_match(&pattern, &commit.email)
+}
+
+/// Matches `text` against `pattern`, where `*` stands for any run of characters.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = text.strip_prefix(first) else { return false };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else { return rest.is_empty() };
+    for part in parts {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Commits per contributor in the last 30 and 90 days (before `--as-of`), regardless of --since/--until.
+#[derive(Clone, Copy, Default, Serialize)]
+struct RecentActivity {
+    commits_30d: u64,
+    commits_90d: u64,
+}
+
+fn get_recent_activity(
+    sources: &[Source],
+    args: &Args,
+    author_mapping: &HashMap<String, String>,
+    progress: &Progress,
+) -> HashMap<String, RecentActivity> {
+    let today = fading_end_date(&args.as_of);
+    let cutoff_30 = (today - chrono::Duration::days(30)).format("%Y-%m-%d").to_string();
+    let cutoff_90 = (today - chrono::Duration::days(90)).format("%Y-%m-%d").to_string();
+
+    let commits = load_commits(sources, args, Some(cutoff_90.clone()), args.as_of.clone(), progress).or_exit();
+    let mut recent: HashMap<String, RecentActivity> = HashMap::new();
+    for commit in &commits {
+        let entry = recent
+            .entry(canonical_name(&commit.author, author_mapping).clone())
+            .or_default();
+        if commit.date >= cutoff_90 {
+            entry.commits_90d += 1;
+        }
+        if commit.date >= cutoff_30 {
+            entry.commits_30d += 1;
+        }
+    }
+    recent
+}
+
+/// Display name of the language usually written in files with extension `ext`.
+fn language_name(ext: &str) -> &str {
+    match ext {
+        "" => "(none)",
+        "rs" => "Rust",
+        "ts" | "tsx" | "mts" | "cts" => "TypeScript",
+        "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+        "py" => "Python",
+        "go" => "Go",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "swift" => "Swift",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => "C++",
+        "cs" => "C#",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "elm" => "Elm",
+        "hs" => "Haskell",
+        "ex" | "exs" => "Elixir",
+        "scala" => "Scala",
+        "sh" | "bash" | "zsh" => "Shell",
+        "html" | "htm" => "HTML",
+        "css" | "scss" | "sass" | "less" => "CSS",
+        "sql" => "SQL",
+        "md" | "markdown" => "Markdown",
+        "json" => "JSON",
+        "yml" | "yaml" => "YAML",
+        "toml" => "TOML",
+        "xml" => "XML",
+        "lock" => "Lockfile",
+        other => other,
+    }
+}
+
+/// Lines added + deleted per contributor and language, for `--languages`.
+fn language_breakdown(
+    commits: &[CommitRecord],
+    author_mapping: &HashMap<String, String>,
+) -> HashMap<String, BTreeMap<String, u64>> {
+    let mut languages: HashMap<String, BTreeMap<String, u64>> = HashMap::new();
+    for commit in commits {
+        let entry = languages
+            .entry(canonical_name(&commit.author, author_mapping).clone())
+            .or_default();
+        for (ext, lines) in &commit.lines_by_ext {
+            *entry.entry(language_name(ext).to_string()).or_default() += lines;
+        }
+    }
+    languages
+}
+
+/// Top three languages as "Rust 80%, TOML 15%, Markdown 5%".
+fn describe_languages(languages: Option<&BTreeMap<String, u64>>) -> String {
+    let Some(languages) = languages else { return String::new() };
+    let total: u64 = languages.values().sum();
+    // Languages that would round to 0% only add noise
+    let mut ranked: Vec<(&String, &u64)> = languages.iter().filter(|(_, lines)| **lines * 200 >= total).collect();
+    ranked.sort_by_key(|(_, lines)| std::cmp::Reverse(**lines));
+    ranked
+        .iter()
+        .take(3)
+        .map(|(name, lines)| format!("{} {:.0}%", name, **lines as f64 / total as f64 * 100.0))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Share of all added lines above which a root commit is treated as a bulk import.
+const INITIAL_IMPORT_SHARE: f64 = 0.5;
+
+/// Returns the root commits that dominate line statistics, i.e. look like an
+/// initial `git add .` of an existing codebase rather than authored work.
+fn detect_initial_imports(commits: &[CommitRecord]) -> Vec<&CommitRecord> {
+    let total_added: u64 = commits.iter().map(|c| c.lines_added).sum();
+    if total_added == 0 {
+        return Vec::new();
+    }
+    commits
+        .iter()
+        .filter(|c| c.is_root && c.lines_added as f64 / total_added as f64 > INITIAL_IMPORT_SHARE)
+        .collect()
+}
+
+/// Longest gap between committer timestamps of commits in the same import burst.
+const IMPORT_WINDOW_SECS: i64 = 60;
+
+/// Commits made by an import (e.g. `git svn`, `git p4` or a migration script): a burst
+/// committed within seconds of each other that together touch at least `min_files` files.
+struct ImportBurst {
+    /// Indices into the commits, oldest first
+    commits: Vec<usize>,
+    files: u64,
+    start: i64,
+    end: i64,
+}
+
+/// `--import-files` unless given.
+const DEFAULT_IMPORT_FILES: u64 = 1000;
+
+/// Finds import bursts by committer time, which import tools set to the moment of the
+/// import while keeping the original author dates. Root commits are left to `--exclude-initial`.
+fn detect_import_bursts(commits: &[CommitRecord], min_files: u64) -> Vec<ImportBurst> {
+    let mut order: Vec<usize> = (0..commits.len()).filter(|&i| !commits[i].is_root).collect();
+    order.sort_by_key(|&i| commits[i].committer_time);
+
+    let mut bursts = Vec::new();
+    let mut current: Vec<usize> = Vec::new();
+    let flush = |current: &mut Vec<usize>, bursts: &mut Vec<ImportBurst>| {
+        let files: u64 = current.iter().map(|&i| commits[i].files_changed).sum();
+        if files >= min_files {
+            bursts.push(ImportBurst {
+                start: commits[current[0]].committer_time,
+                end: commits[current[current.len() - 1]].committer_time,
+                commits: std::mem::take(current),
+                files,
+            });
+        }
+        current.clear();
+    };
+    for i in order {
+        if let Some(&last) = current.last() {
+            if commits[i].committer_time - commits[last].committer_time > IMPORT_WINDOW_SECS {
+                flush(&mut current, &mut bursts);
+            }
+        }
+        current.push(i);
+    }
+    if !current.is_empty() {
+        flush(&mut current, &mut bursts);
+    }
+    bursts
+}
+
+/// Drops the commits of import bursts with `--exclude-imports`, or points them out.
+fn handle_imports(commits: &mut Vec<CommitRecord>, args: &Args) {
+    let bursts = detect_import_bursts(commits, args.import_files);
+    if bursts.is_empty() {
+        return;
+    }
+    if args.exclude_imports {
+        let excluded: HashSet<String> = bursts
+            .iter()
+            .flat_map(|b| b.commits.iter().map(|&i| commits[i].hash.clone()))
+            .collect();
+        commits.retain(|c| !excluded.contains(&c.hash));
+        return;
+    }
+    for burst in &bursts {
+        let mut authors: Vec<&str> = burst.commits.iter().map(|&i| commits[i].author.as_str()).collect();
+        authors.sort_unstable();
+        authors.dedup();
+        let when = |time: i64| {
+            Local
+                .timestamp_opt(time, 0)
+                .single()
+                .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default()
+        };
+        let (start, end) = (when(burst.start), when(burst.end));
+        let span = if start == end { start } else { format!("{} to {}", start, end) };
+        eprintln!(
+            "Note: {} commits touching {} files were committed at {} (by {}), which looks like an import; use --exclude-imports to leave them out",
+            burst.commits.len(),
+            burst.files,
+            span,
+            if authors.len() > 3 { format!("{} and {} more", authors[..3].join(", "), authors.len() - 3) } else { authors.join(", ") }
+        );
+    }
+}
+
+/// Gives every commit sharing an email address the name most often used with it,
+/// so "J. Doe" and "John Doe" committing as jd@example.com become one contributor.
+fn unify_identities(commits: &mut [CommitRecord]) {
+    let mut names_by_email: HashMap<&str, HashMap<&str, u64>> = HashMap::new();
+    for commit in commits.iter() {
+        if !commit.email.is_empty() {
+            *names_by_email
+                .entry(&commit.email)
+                .or_default()
+                .entry(&commit.author)
+                .or_insert(0) += 1;
+        }
+    }
+
+    let preferred: HashMap<String, String> = names_by_email
+        .into_iter()
+        .filter(|(_, names)| names.len() > 1)
+        .filter_map(|(email, names)| {
+            // Most frequent spelling wins; ties go to the alphabetically first name
+            let (name, _) = names
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))?;
+            Some((email.to_string(), name.to_string()))
+        })
+        .collect();
+
+    for commit in commits.iter_mut() {
+        if let Some(name) = preferred.get(&commit.email) {
+            commit.author = name.clone();
+        }
+    }
+}
+
+/// Email GitHub commits with when changes are merged or edited in its web interface; it
+/// stands for whoever clicked, not for someone who applied the commit.
+const WEB_FLOW_EMAIL: &str = "noreply@github.com";
+
+/// `--credit committer`: credits each commit to its committer instead of its author,
+/// except commits made through GitHub's web interface, which keep their author.
+fn credit_committers(commits: &mut [CommitRecord]) {
+    for commit in commits.iter_mut().filter(|c| c.committer_email != WEB_FLOW_EMAIL) {
+        std::mem::swap(&mut commit.author, &mut commit.committer);
+        std::mem::swap(&mut commit.email, &mut commit.committer_email);
+    }
+}
+
+/// `--credit both`: records who committed each commit when it isn't the author, named as
+/// in their own commits when they author any under the same email, so a maintainer who
+/// applies patches keeps one row.
+fn name_committers(commits: &mut [CommitRecord]) {
+    let names: HashMap<String, String> = commits
+        .iter()
+        .filter(|c| !c.email.is_empty())
+        .map(|c| (c.email.clone(), c.author.clone()))
+        .collect();
+    for commit in commits.iter_mut() {
+        if commit.committer_email == commit.email || commit.committer_email == WEB_FLOW_EMAIL {
+            continue;
+        }
+        let name = names.get(&commit.committer_email).unwrap_or(&commit.committer);
+        if *name != commit.author {
+            commit.applied_by = Some(name.clone());
+        }
+    }
+}
+
+/// `name` lowercased, with accents dropped and spaces collapsed, so "José  García" and
+/// "JOSE GARCIA" compare equal.
+fn fold_name(name: &str) -> String {
+    let mut folded = String::with_capacity(name.len());
+    for c in name.split_whitespace().collect::<Vec<_>>().join(" ").chars().flat_map(char::to_lowercase) {
+        let base = match c {
+            // Combining marks, as left by decomposed (NFD) input
+            '\u{300}'..='\u{36f}' => "",
+            'à'..='å' | 'ā' | 'ă' | 'ą' => "a",
+            'æ' => "ae",
+            'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+            'ð' | 'ď' | 'đ' => "d",
+            'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+            'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+            'ĥ' | 'ħ' => "h",
+            'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+            'ĵ' => "j",
+            'ķ' => "k",
+            'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+            'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+            'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+            'œ' => "oe",
+            'ŕ' | 'ŗ' | 'ř' => "r",
+            'ś' | 'ŝ' | 'ş' | 'š' | 'ș' => "s",
+            'ß' => "ss",
+            'ţ' | 'ť' | 'ŧ' | 'ț' => "t",
+            'þ' => "th",
+            'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+            'ŵ' => "w",
+            'ý' | 'ÿ' | 'ŷ' => "y",
+            'ź' | 'ż' | 'ž' => "z",
+            _ => {
+                folded.push(c);
+                continue;
+            }
+        };
+        folded.push_str(base);
+    }
+    folded
+}
+
+/// With `--merge-fold-case`, gives every commit the most used of the author names that
+/// fold to the same text, so "jose garcia", "José García" and "JOSE GARCIA" become one
+/// contributor.
+fn unify_name_spellings(commits: &mut [CommitRecord]) {
+    let mut spellings: HashMap<String, HashMap<&str, u64>> = HashMap::new();
+    for commit in commits.iter() {
+        *spellings.entry(fold_name(&commit.author)).or_default().entry(&commit.author).or_insert(0) += 1;
+    }
+
+    let preferred: HashMap<String, String> = spellings
+        .into_values()
+        .filter(|names| names.len() > 1)
+        .flat_map(|names| {
+            // Most frequent spelling wins; ties go to the alphabetically first name
+            let (name, _) = names
+                .iter()
+                .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(name, count)| (name.to_string(), *count))
+                .unwrap_or_default();
+            names.into_keys().map(move |alias| (alias.to_string(), name.clone()))
+        })
+        .collect();
+
+    for commit in commits.iter_mut() {
+        if let Some(name) = preferred.get(&commit.author) {
+            commit.author = name.clone();
+        }
+    }
+}
+
+/// Value of the first trailer matching one of `keys` (case-insensitive), in key order.
+fn trailer_value<'a>(commit: &'a CommitRecord, keys: &[String]) -> Option<&'a str> {
+    keys.iter().find_map(|key| {
+        commit
+            .trailers
+            .iter()
+            .find(|(k, v)| k.trim().eq_ignore_ascii_case(key) && !v.trim().is_empty())
+            .map(|(_, v)| v.trim())
+    })
+}
+
+/// Maps each alias to its canonical name. Accepts `Name,Alias1,Alias2` (the first name
+/// wins, as documented) as well as `Alias=Name`.
+fn build_author_mapping(merge_args: &[String]) -> HashMap<String, String> {
+    let mut mapping = HashMap::new();
+    for m in merge_args {
+        if let Some((alias, canonical)) = m.split_once('=') {
+            mapping.insert(alias.trim().to_string(), canonical.trim().to_string());
+        } else {
+            let mut names = m.split(',').map(str::trim).filter(|n| !n.is_empty());
+            if let Some(canonical) = names.next() {
+                for alias in names {
+                    mapping.insert(alias.to_string(), canonical.to_string());
+                }
+            }
+        }
+    }
+    mapping
+}
+
+/// Warns on stderr about `--merge` aliases that match no author in the scanned range,
+/// suggesting the closest existing names, since a typo otherwise leaves duplicates unmerged.
+fn warn_unmatched_aliases<'a>(
+    commits: impl IntoIterator<Item = &'a CommitRecord>,
+    author_mapping: &HashMap<String, String>,
+) {
+    let authors: BTreeSet<&str> = commits.into_iter().map(|c| c.author.as_str()).collect();
+    let mut unmatched: Vec<&String> = author_mapping
+        .keys()
+        .filter(|alias| !authors.contains(alias.as_str()))
+        .collect();
+    unmatched.sort();
+
+    for alias in unmatched {
+        let wanted = alias.to_lowercase();
+        let mut suggestions: Vec<(usize, &str)> = authors
+            .iter()
+            .map(|name| (edit_distance(&wanted, &name.to_lowercase()), *name))
+            .filter(|(distance, _)| *distance <= (wanted.chars().count() / 3).max(2))
+            .collect();
+        suggestions.sort();
+        let hint = match suggestions.as_slice() {
+            [] => String::new(),
+            close => format!(
+                " (did you mean {}?)",
+                close.iter().take(3).map(|(_, n)| format!("\"{}\"", n)).collect::<Vec<_>>().join(", ")
+            ),
+        };
+        eprintln!("Warning: --merge alias \"{}\" matches no author in range{}", alias, hint);
+    }
+}
+
+/// Levenshtein distance over characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (diagonal + usize::from(ca != *cb)).min(row[j] + 1).min(above + 1);
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Resolves an author by exact name equality. Unlike `git log --author`, which takes a
+/// regex, "Ann" never matches "Annette" and names like "Joe (bot)" need no escaping.
+fn canonical_name<'a>(author: &'a String, author_mapping: &'a HashMap<String, String>) -> &'a String {
+    author_mapping.get(author).unwrap_or(author)
+}
+
+/// Each contributor's commits (after `--merge`), grouped in a single pass over the history
+/// so per-contributor series don't rescan every commit.
+fn commits_by_contributor<'a>(
+    commits: &'a [CommitRecord],
+    author_mapping: &'a HashMap<String, String>,
+) -> HashMap<&'a String, Vec<&'a CommitRecord>> {
+    let mut grouped: HashMap<&String, Vec<&CommitRecord>> = HashMap::new();
+    for commit in commits {
+        grouped.entry(canonical_name(&commit.author, author_mapping)).or_default().push(commit);
+    }
+    grouped
+}
+
+fn get_commits_by_date<'a>(commits: impl IntoIterator<Item = &'a CommitRecord>) -> BTreeMap<String, u64> {
+    let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+    for commit in commits {
+        *counts.entry(commit.date.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn get_lines_by_date<'a>(commits: impl IntoIterator<Item = &'a CommitRecord>, metric: LinesMetric) -> BTreeMap<String, i64> {
+    let mut counts: BTreeMap<String, i64> = BTreeMap::new();
+    for commit in commits {
+        *counts.entry(commit.date.clone()).or_insert(0) += metric.value(commit.lines_added, commit.lines_deleted);
+    }
+    counts
+}
+
+fn get_net_lines_by_date(commits: &[CommitRecord]) -> BTreeMap<String, i64> {
+    let mut net: BTreeMap<String, i64> = BTreeMap::new();
+    for commit in commits {
+        *net.entry(commit.date.clone()).or_insert(0) += commit.lines_added as i64 - commit.lines_deleted as i64;
+    }
+    net
+}
+
+/// Number of time slices in the table's activity sparklines.
+const SPARKLINE_WIDTH: usize = 12;
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Commits per equal slice of the analyzed period as "▁▂▃▅▇", scaled to the busiest slice
+/// of `daily` itself so quiet contributors still show their trend. Idle slices are blank.
+fn sparkline(daily: &BTreeMap<String, u64>, first: NaiveDate, last: NaiveDate) -> String {
+    let days = (last - first).num_days() + 1;
+    let width = (days as usize).min(SPARKLINE_WIDTH);
+    let mut slices = vec![0u64; width];
+    for (date, count) in daily {
+        if let Ok(d) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            let offset = (d - first).num_days().clamp(0, days - 1);
+            slices[(offset * width as i64 / days) as usize] += count;
+        }
+    }
+    let max = slices.iter().copied().max().unwrap_or(0).max(1);
+    slices
+        .iter()
+        .map(|&n| match n {
+            0 => ' ',
+            n => SPARKLINE_LEVELS[((n * 8).div_ceil(max) as usize).clamp(1, 8) - 1],
+        })
+        .collect()
+}
+
+/// Each contributor's sparkline over the period spanned by `commits`, and the team's.
+fn activity_sparklines(
+    commits: &[CommitRecord],
+    author_mapping: &HashMap<String, String>,
+) -> (String, HashMap<String, String>) {
+    let team = get_commits_by_date(commits);
+    let parse = |d: Option<&String>| d.and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+    let (Some(first), Some(last)) = (parse(team.keys().next()), parse(team.keys().last())) else {
+        return (String::new(), HashMap::new());
+    };
+    let contributors = commits_by_contributor(commits, author_mapping)
+        .into_iter()
+        .map(|(name, own)| (name.clone(), sparkline(&get_commits_by_date(own), first, last)))
+        .collect();
+    (sparkline(&team, first, last), contributors)
+}
+
+/// Prints a bar graph of `data`; with `by`, keys are bucket start dates labeled per `--by`.
+fn print_time_graph(title: &str, data: &BTreeMap<String, u64>, by: Option<&str>) {
+    if data.is_empty() {
+        println!("{}: No data", title);
+        println!();
+        return;
+    }
+
+    let height = 8;
+    let width = data.len().min(60);
+
+    println!("{}", title);
+    println!("{}", "─".repeat(title.len()));
+
+    let values: Vec<u64> = if data.len() > width {
+        let chunk_size = data.len().div_ceil(width);
+        data.values()
+            .collect::<Vec<_>>()
+            .chunks(chunk_size)
+            .map(|chunk| chunk.iter().copied().sum())
+            .collect()
+    } else {
+        data.values().copied().collect()
+    };
+
+    let max_val = *values.iter().max().unwrap_or(&1);
+
+    for row in (0..height).rev() {
+        let threshold = (row as f64 / height as f64) * max_val as f64;
+        for &val in &values {
+            if val as f64 > threshold {
+                print!("█");
+            } else {
+                print!(" ");
+            }
+        }
+        if row == height - 1 {
+            println!(" {}", max_val);
+        } else if row == 0 {
+            println!(" 0");
+        } else {
+            println!();
+        }
+    }
+
+    let label = |key: &String| match by {
+        Some(by) => NaiveDate::parse_from_str(key, "%Y-%m-%d")
+            .map(|d| bucket_label(d, by))
+            .unwrap_or_else(|_| key.clone()),
+        None => key.clone(),
+    };
+    let dates: Vec<String> = data.keys().map(label).collect();
+    if let (Some(first), Some(last)) = (dates.first(), dates.last()) {
+        println!("{:<width$}{}", first, last, width = values.len().saturating_sub(last.len()));
+    }
+    println!();
+}
+
+/// Q1, median and Q3 using linear interpolation between closest ranks.
+fn quartiles(values: &[u64]) -> (f64, f64, f64) {
+    let mut sorted: Vec<u64> = values.to_vec();
+    sorted.sort_unstable();
+    let at = |p: f64| -> f64 {
+        if sorted.is_empty() {
+            return 0.0;
+        }
+        let pos = p * (sorted.len() - 1) as f64;
+        let lo = pos.floor() as usize;
+        let hi = pos.ceil() as usize;
+        sorted[lo] as f64 + (sorted[hi] as f64 - sorted[lo] as f64) * (pos - lo as f64)
+    };
+    (at(0.25), at(0.5), at(0.75))
+}
+
+fn quartile_band(value: u64, q: (f64, f64, f64)) -> &'static str {
+    let v = value as f64;
+    if v < q.0 {
+        "lower quarter"
+    } else if v < q.1 {
+        "lower-middle"
+    } else if v <= q.2 {
+        "upper-middle"
+    } else {
+        "upper quarter"
+    }
+}
+
+fn print_benchmarks(
+    sorted_stats: &[(&String, &ContributorStats)],
+    active_days: &HashMap<String, u64>,
+    metric: LinesMetric,
+) {
+    let commits: Vec<u64> = sorted_stats.iter().map(|(_, s)| s.commits).collect();
+    let lines: Vec<u64> = sorted_stats
+        .iter()
+        .map(|(_, s)| metric.of(s).max(0) as u64)
+        .collect();
+    let days: Vec<u64> = sorted_stats
+        .iter()
+        .map(|(name, _)| active_days.get(*name).copied().unwrap_or(0))
+        .collect();
+
+    let q_commits = quartiles(&commits);
+    let q_lines = quartiles(&lines);
+    let q_days = quartiles(&days);
+
+    println!();
+    println!("Team distribution (context for calibration, not a ranking)");
+    println!();
+    println!("| {:<12} | {:>10} | {:>10} | {:>10} |", "Metric", "Q1", "Median", "Q3");
+    println!("|{:-<14}|{:-<12}|{:-<12}|{:-<12}|", "", "", "", "");
+    for (label, q) in [("Commits", q_commits), ("Lines", q_lines), ("Active days", q_days)] {
+        println!("| {:<12} | {:>10.1} | {:>10.1} | {:>10.1} |", label, q.0, q.1, q.2);
+    }
+    println!();
+
+    // Alphabetical so the listing doesn't read as a leaderboard
+    let mut by_name: Vec<(&String, &ContributorStats)> = sorted_stats.to_vec();
+    by_name.sort_by_key(|(name, _)| name.to_lowercase());
+
+    let name_width = by_name.iter().map(|(n, _)| display_width(n)).max().unwrap_or(12).max(12);
+    println!(
+        "| {:<name_width$} | {:<13} | {:<13} | {:<13} |",
+        "Contributor", "Commits", "Lines", "Active days",
+        name_width = name_width
+    );
+    println!("|{:-<width$}|{:-<15}|{:-<15}|{:-<15}|", "", "", "", "", width = name_width + 2);
+    for (name, stat) in by_name {
+        let days = active_days.get(name).copied().unwrap_or(0);
+        println!(
+            "| {} | {:<13} | {:<13} | {:<13} |",
+            pad_display(name, name_width),
+            quartile_band(stat.commits, q_commits),
+            quartile_band(metric.of(stat).max(0) as u64, q_lines),
+            quartile_band(days, q_days),
+        );
+    }
+}
+
+#[derive(Serialize)]
+struct DatePoint<T> {
+    date: String,
+    count: T,
+}
+
+#[derive(Serialize)]
+struct ContributorSeries {
+    commits: Vec<DatePoint<u64>>,
+    lines: Vec<DatePoint<i64>>,
+}
+
+#[derive(Serialize)]
+struct JsonContributor<'a> {
+    name: &'a str,
+    id: String,
+    #[serde(flatten)]
+    stats: &'a ContributorStats,
+    percent: f64,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    recent: Option<RecentActivity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    languages: Option<&'a BTreeMap<String, u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    series: Option<ContributorSeries>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    velocity: Option<Velocity>,
+}
+
+#[derive(Serialize)]
+struct JsonTotals {
+    commits: u64,
+    empty_commits: u64,
+    lines_added: u64,
+    lines_deleted: u64,
+    files_changed: u64,
+    net_lines: i64,
+}
+
+impl JsonTotals {
+    fn of<'a>(stats: impl Iterator<Item = &'a ContributorStats>) -> Self {
+        stats.fold(
+            JsonTotals { commits: 0, empty_commits: 0, lines_added: 0, lines_deleted: 0, files_changed: 0, net_lines: 0 },
+            |mut totals, s| {
+                totals.commits += s.commits;
+                totals.empty_commits += s.empty_commits;
+                totals.lines_added += s.lines_added;
+                totals.lines_deleted += s.lines_deleted;
+                totals.files_changed += s.files_changed;
+                totals.net_lines += s.lines_added as i64 - s.lines_deleted as i64;
+                totals
+            },
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct JsonPreviousContributor<'a> {
+    name: &'a str,
+    #[serde(flatten)]
+    stats: &'a ContributorStats,
+}
+
+/// `--with-previous`: the same figures for the period before `--period`
+#[derive(Serialize)]
+struct JsonPrevious<'a> {
+    since: &'a str,
+    until: &'a str,
+    contributors: Vec<JsonPreviousContributor<'a>>,
+    totals: JsonTotals,
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    metadata: ReportMetadata,
+    branch: &'a str,
+    since: Option<&'a str>,
+    until: Option<&'a str>,
+    merges_included: bool,
+    first_parent: bool,
+    /// `--lines-metric`: what `percent` (with `--sort lines`) and line series count
+    lines_metric: &'static str,
+    contributors: Vec<JsonContributor<'a>>,
+    totals: JsonTotals,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    growth: Option<Vec<DatePoint<i64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fading: Option<Vec<FadingContributor>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repositories: Option<Vec<RepoBreakdown>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous: Option<JsonPrevious<'a>>,
+}
+
+fn to_points<T: Copy>(data: &BTreeMap<String, T>) -> Vec<DatePoint<T>> {
+    data.iter()
+        .map(|(date, count)| DatePoint { date: date.clone(), count: *count })
+        .collect()
+}
+
+fn json_report(
+    args: &Args,
+    branch: &str,
+    commits: &[CommitRecord],
+    sorted_stats: &[(&String, &ContributorStats)],
+    author_mapping: &HashMap<String, String>,
+    recent: Option<&HashMap<String, RecentActivity>>,
+    previous: Option<&PreviousPeriod>,
+) -> Result<String, Error> {
+    let share = share_metric(args);
+    let metric = LinesMetric::from_args(args);
+    let with_series = args.series;
+    let (by, week_start) = (granularity(args), week_start_day(args));
+    let total_commits: u64 = sorted_stats.iter().map(|(_, s)| s.commits).sum();
+    let total_lines: i64 = sorted_stats.iter().map(|(_, s)| metric.of(s).max(0)).sum();
+
+    let by_contributor = with_series.then(|| commits_by_contributor(commits, author_mapping));
+    let languages = args.languages.then(|| language_breakdown(commits, author_mapping));
+    let velocity = args.velocity.then(|| velocities(args, commits, sorted_stats));
+    let mut ids = contributor_ids(commits, author_mapping, args);
+    let contributors = sorted_stats
+        .iter()
+        .map(|(name, stat)| {
+            let percent = match share {
+                Some(metric) => lines_percent(metric.of(stat), total_lines),
+                None if total_commits > 0 => stat.commits as f64 / total_commits as f64 * 100.0,
+                None => 0.0,
+            };
+            let series = by_contributor.as_ref().map(|by_contributor| {
+                let own = by_contributor.get(*name).map_or(&[][..], Vec::as_slice);
+                ContributorSeries {
+                    commits: to_points(&bucket_series(&get_commits_by_date(own.iter().copied()), by, week_start, false)),
+                    lines: to_points(&bucket_series(&get_lines_by_date(own.iter().copied(), metric), by, week_start, false)),
+                }
+            });
+            JsonContributor {
+                name,
+                id: ids.remove(*name).unwrap_or_default(),
+                stats: stat,
+                percent,
+                recent: recent.map(|r| r.get(*name).copied().unwrap_or_default()),
+                languages: languages.as_ref().and_then(|l| l.get(*name)),
+                series,
+                velocity: velocity.as_ref().and_then(|v| v.get(*name).copied()),
+            }
+        })
+        .collect();
+
+    let growth = with_series.then(|| {
+        let mut running: i64 = 0;
+        get_net_lines_by_date(commits)
+            .into_iter()
+            .map(|(date, net)| {
+                running += net;
+                DatePoint { date, count: running }
+            })
+            .collect()
+    });
+
+    let report = JsonReport {
+        metadata: ReportMetadata::new(args)?,
+        branch,
+        since: args.since.as_deref(),
+        until: args.until.as_deref(),
+        merges_included: !args.no_merges,
+        first_parent: args.first_parent,
+        lines_metric: metric.name(),
+        contributors,
+        totals: JsonTotals::of(sorted_stats.iter().map(|(_, s)| *s)),
+        growth,
+        fading: args.fading.map(|threshold| {
+            find_fading(
+                commits,
+                author_mapping,
+                fading_end_date(&args.until),
+                args.fading_window,
+                args.fading_periods,
+                threshold,
+            )
+        }),
+        repositories: args
+            .per_repo
+            .then(|| repo_breakdowns(commits, author_mapping, args)),
+        previous: previous.map(|previous| {
+            let mut contributors: Vec<JsonPreviousContributor> = previous
+                .stats
+                .iter()
+                .map(|(name, stats)| JsonPreviousContributor { name, stats })
+                .collect();
+            contributors.sort_by(|a, b| b.stats.commits.cmp(&a.stats.commits).then_with(|| a.name.cmp(b.name)));
+            JsonPrevious {
+                since: &previous.since,
+                until: &previous.until,
+                contributors,
+                totals: JsonTotals::of(previous.stats.values()),
+            }
+        }),
+    };
+
+    Ok(serde_json::to_string_pretty(&report).expect("Failed to serialize report"))
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn print_csv_report(
+    sorted_stats: &[(&String, &ContributorStats)],
+    ids: &HashMap<String, String>,
+    share: Option<LinesMetric>,
+    recent: Option<&HashMap<String, RecentActivity>>,
+    previous: Option<&PreviousPeriod>,
+) {
+    let total_commits: u64 = sorted_stats.iter().map(|(_, s)| s.commits).sum();
+    let total_lines: i64 = share.map_or(0, |metric| sorted_stats.iter().map(|(_, s)| metric.of(s).max(0)).sum());
+
+    let show_applied = sorted_stats.iter().any(|(_, s)| s.applied_commits.is_some());
+    let applied_header = if show_applied { ",applied_commits" } else { "" };
+    let show_score = sorted_stats.iter().any(|(_, s)| s.score.is_some());
+    let score_header = if show_score { ",score" } else { "" };
+    let show_functions = sorted_stats.iter().any(|(_, s)| s.functions_touched.is_some());
+    let functions_header = if show_functions { ",functions_touched" } else { "" };
+    let recent_header = if recent.is_some() { ",commits_30d,commits_90d" } else { "" };
+    let previous_header = if previous.is_some() { ",previous_commits,previous_lines_added,previous_lines_deleted" } else { "" };
+    println!(
+        "name,id,commits,lines_added,lines_deleted,percent,empty_commits,files_changed,net_lines{}{}{}{}{}",
+        applied_header, score_header, functions_header, recent_header, previous_header
+    );
+    for (name, stat) in sorted_stats {
+        let pct = match share {
+            Some(metric) => lines_percent(metric.of(stat), total_lines),
+            None if total_commits > 0 => stat.commits as f64 / total_commits as f64 * 100.0,
+            None => 0.0,
+        };
+        let recent_cells = match recent {
+            Some(recent) => {
+                let activity = recent.get(*name).copied().unwrap_or_default();
+                format!(",{},{}", activity.commits_30d, activity.commits_90d)
+            }
+            None => String::new(),
+        };
+        let previous_cells = match previous {
+            Some(previous) => {
+                let before = previous.stats.get(*name).copied().unwrap_or_default();
+                format!(",{},{},{}", before.commits, before.lines_added, before.lines_deleted)
+            }
+            None => String::new(),
+        };
+        let applied_cell = if show_applied { format!(",{}", stat.applied_commits.unwrap_or(0)) } else { String::new() };
+        let score_cell = if show_score { format!(",{:.2}", stat.score.unwrap_or(0.0)) } else { String::new() };
+        let functions_cell = if show_functions { format!(",{}", stat.functions_touched.unwrap_or(0)) } else { String::new() };
+        println!(
+            "{},{},{},{},{},{:.2},{},{},{}{}{}{}{}{}",
+            csv_field(name),
+            ids.get(*name).map_or("", String::as_str),
+            stat.commits,
+            stat.lines_added,
+            stat.lines_deleted,
+            pct,
+            stat.empty_commits,
+            stat.files_changed,
+            stat.net_lines,
+            applied_cell,
+            score_cell,
+            functions_cell,
+            recent_cells,
+            previous_cells
+        );
+    }
+}
+
+#[derive(Serialize)]
+struct FadingContributor {
+    name: String,
+    /// Commits per period, oldest first
+    commits: Vec<u64>,
+    drop_percent: f64,
+}
+
+/// Contributors whose commits decline in every one of `periods` consecutive windows of
+/// `window_days` ending at `end`, with an overall drop of at least `threshold` percent.
+fn find_fading(
+    commits: &[CommitRecord],
+    author_mapping: &HashMap<String, String>,
+    end: NaiveDate,
+    window_days: u32,
+    periods: u32,
+    threshold: f64,
+) -> Vec<FadingContributor> {
+    let periods = periods.max(2) as i64;
+    let window = window_days.max(1) as i64;
+    let mut buckets: BTreeMap<&String, Vec<u64>> = BTreeMap::new();
+
+    for commit in commits {
+        let Ok(date) = NaiveDate::parse_from_str(&commit.date, "%Y-%m-%d") else { continue };
+        let age = (end - date).num_days();
+        if age < 0 || age >= window * periods {
+            continue;
+        }
+        // Index 0 is the oldest period
+        let idx = (periods - 1 - age / window) as usize;
+        buckets
+            .entry(canonical_name(&commit.author, author_mapping))
+            .or_insert_with(|| vec![0; periods as usize])[idx] += 1;
+    }
+
+    let mut fading: Vec<FadingContributor> = buckets
+        .into_iter()
+        .filter_map(|(name, counts)| {
+            let first = *counts.first()?;
+            let last = *counts.last()?;
+            let declining = counts.windows(2).all(|w| w[1] <= w[0]);
+            let drop_percent = if first > 0 { (first - last) as f64 / first as f64 * 100.0 } else { 0.0 };
+            (declining && first > 0 && drop_percent >= threshold).then(|| FadingContributor {
+                name: name.clone(),
+                commits: counts,
+                drop_percent,
+            })
+        })
+        .collect();
+    fading.sort_by(|a, b| b.drop_percent.total_cmp(&a.drop_percent));
+    fading
+}
+
+fn fading_end_date(until: &Option<String>) -> NaiveDate {
+    until
+        .as_deref()
+        .and_then(|u| NaiveDate::parse_from_str(u, "%Y-%m-%d").ok())
+        .unwrap_or_else(|| Local::now().date_naive())
+}
+
+fn print_fading(fading: &[FadingContributor], window_days: u32) {
+    println!();
+    println!("Fading contributors (commits per {}-day period, oldest first)", window_days);
+    println!();
+    if fading.is_empty() {
+        println!("None");
+        return;
+    }
+    for f in fading {
+        let counts: Vec<String> = f.commits.iter().map(|c| c.to_string()).collect();
+        println!("  {:<24} {}  (-{:.0}%)", f.name, counts.join(" → "), f.drop_percent);
+    }
+}
+
+/// Day weekly buckets start on, from `--week-start`.
+fn week_start_day(args: &Args) -> Weekday {
+    match args.week_start.as_deref() {
+        Some("sunday") => Weekday::Sun,
+        _ => Weekday::Mon,
+    }
+}
+
+/// First day of the week containing `date`.
+fn week_start(date: NaiveDate, start: Weekday) -> NaiveDate {
+    let offset = (date.weekday().num_days_from_monday() + 7 - start.num_days_from_monday()) % 7;
+    date - chrono::Duration::days(offset as i64)
+}
+
+/// ISO week label (`2025-W14`) of the week beginning on `start`. Sunday-start weeks
+/// take the number of the Monday that follows, as they share six of its days.
+fn week_label(start: NaiveDate) -> String {
+    let to_monday = (7 - start.weekday().num_days_from_monday()) % 7;
+    let week = (start + chrono::Duration::days(to_monday as i64)).iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+/// Time bucket sizes for `--by`, finest first.
+const GRANULARITIES: [&str; 5] = ["day", "week", "month", "quarter", "year"];
+
+/// `--by`, defaulting to days.
+fn granularity(args: &Args) -> &str {
+    args.by.as_deref().unwrap_or("day")
+}
+
+/// First day of the `by` bucket containing `date`: calendar weeks (see `--week-start`),
+/// months, quarters and years rather than fixed-length spans.
+fn bucket_start(date: NaiveDate, by: &str, week_start_day: Weekday) -> NaiveDate {
+    let first_of_month = |month0: u32| NaiveDate::from_ymd_opt(date.year(), month0 + 1, 1).unwrap_or(date);
+    match by {
+        "week" => week_start(date, week_start_day),
+        "month" => first_of_month(date.month0()),
+        "quarter" => first_of_month(date.month0() / 3 * 3),
+        "year" => first_of_month(0),
+        _ => date,
+    }
+}
+
+fn next_bucket(start: NaiveDate, by: &str) -> NaiveDate {
+    let months = |n| start.checked_add_months(Months::new(n)).unwrap_or(NaiveDate::MAX);
+    match by {
+        "week" => start + chrono::Duration::days(7),
+        "month" => months(1),
+        "quarter" => months(3),
+        "year" => months(12),
+        _ => start + chrono::Duration::days(1),
+    }
+}
+
+/// Display label of a bucket: the date, `2025-W14`, `2025-03`, `2025-Q1` or `2025`.
+fn bucket_label(start: NaiveDate, by: &str) -> String {
+    match by {
+        "week" => week_label(start),
+        "month" => start.format("%Y-%m").to_string(),
+        "quarter" => format!("{}-Q{}", start.year(), start.month0() / 3 + 1),
+        "year" => start.year().to_string(),
+        _ => start.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// Sums a per-day series into `by` buckets keyed by their start date. With `fill`, empty
+/// buckets between the first and last one are included as zero so the axis is linear.
+fn bucket_series<T: Copy + Default + std::ops::AddAssign>(
+    daily: &BTreeMap<String, T>,
+    by: &str,
+    week_start_day: Weekday,
+    fill: bool,
+) -> BTreeMap<String, T> {
+    let mut buckets: BTreeMap<NaiveDate, T> = BTreeMap::new();
+    for (date, value) in daily {
+        if let Ok(d) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            *buckets.entry(bucket_start(d, by, week_start_day)).or_default() += *value;
+        }
+    }
+    if fill {
+        if let (Some(&first), Some(&last)) = (buckets.keys().next(), buckets.keys().last()) {
+            let mut bucket = first;
+            while bucket < last {
+                buckets.entry(bucket).or_default();
+                bucket = next_bucket(bucket, by);
+            }
+        }
+    }
+    buckets
+        .into_iter()
+        .map(|(start, value)| (start.format("%Y-%m-%d").to_string(), value))
+        .collect()
+}
+
+fn print_summary(commits: &[CommitRecord], contributor_count: usize, week_start_day: Weekday, bus_factor: Option<&BusFactor>) {
+    println!("Summary");
+    println!("───────");
+    if commits.is_empty() {
+        println!("No commits in range");
+        println!();
+        return;
+    }
+
+    let by_day = get_commits_by_date(commits);
+    let mut by_week: BTreeMap<String, u64> = BTreeMap::new();
+    for (date, count) in &by_day {
+        if let Ok(d) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            *by_week.entry(week_label(week_start(d, week_start_day))).or_insert(0) += count;
+        }
+    }
+    // max_by_key keeps the last maximum; iterate in reverse so ties resolve to the earliest date
+    let busiest_day = by_day.iter().rev().max_by_key(|(_, c)| **c);
+    let busiest_week = by_week.iter().rev().max_by_key(|(_, c)| **c);
+    let largest = commits
+        .iter()
+        .max_by_key(|c| c.lines_added + c.lines_deleted)
+        .filter(|c| c.lines_added + c.lines_deleted > 0);
+
+    let first = by_day.keys().next().and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+    let last = by_day.keys().last().and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+    let span_days = match (first, last) {
+        (Some(f), Some(l)) => (l - f).num_days() + 1,
+        _ => 1,
+    };
+
+    println!("Commits:          {}", commits.len());
+    let empty = commits.iter().filter(|c| c.files_changed == 0).count();
+    if empty > 0 {
+        println!("Empty commits:    {} (no file changes)", empty);
+    }
+    println!("Contributors:     {}", contributor_count);
+    if let (Some(f), Some(l)) = (first, last) {
+        println!("Period:           {} to {} ({} days)", f, l, span_days);
+    }
+    if let Some((date, count)) = busiest_day {
+        println!("Busiest day:      {} ({} commits)", date, count);
+    }
+    if let Some((week, count)) = busiest_week {
+        println!("Busiest week:     {} ({} commits)", week, count);
+    }
+    if let Some(c) = largest {
+        println!(
+            "Largest commit:   {} by {} on {} (+{} -{})",
+            &c.hash[..c.hash.len().min(10)],
+            c.author,
+            c.date,
+            c.lines_added,
+            c.lines_deleted
+        );
+    }
+    println!("Avg commits/day:  {:.2}", commits.len() as f64 / span_days as f64);
+    if let Some(bus) = bus_factor {
+        println!("Bus factor:       {}", bus.describe("commits"));
+    }
+    println!();
+}
+
+const GREEN: &str = "32";
+const RED: &str = "31";
+const BOLD: &str = "1";
+
+/// Whether to color the terminal table: stdout is a terminal, and neither `--no-color`
+/// nor a non-empty `NO_COLOR` asks otherwise.
+fn use_color(args: &Args) -> bool {
+    !args.no_color && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && std::io::stdout().is_terminal()
+}
+
+/// Wraps already padded `text` in an ANSI style, so escape codes don't count toward widths.
+fn paint(text: String, style: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[{}m{}\x1b[0m", style, text)
+    } else {
+        text
+    }
+}
+
+/// Columns `text` takes in a terminal: CJK characters and most emoji are two wide,
+/// combining marks zero.
+fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// `text` with its first letter in upper case, for labels starting a heading.
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars.next().map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+}
+
+/// Left-aligns `text` in `width` terminal columns.
+fn pad_display(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(display_width(text))))
+}
+
+fn print_table(
+    sorted_stats: &[(&String, &ContributorStats)],
+    share: Option<LinesMetric>,
+    recent: Option<&HashMap<String, RecentActivity>>,
+    languages: Option<&HashMap<String, BTreeMap<String, u64>>>,
+    sparklines: Option<&(String, HashMap<String, String>)>,
+    color: bool,
+    labels: &Labels,
+) {
+    let name_width = sorted_stats
+        .iter()
+        .map(|(name, _)| display_width(name))
+        .max()
+        .unwrap_or(12)
+        .max(12);
+    // Sized to the headers, which depend on --lang
+    let added_width = display_width(labels.added).max(11);
+    let deleted_width = display_width(labels.deleted).max(11);
+
+    let total_commits: u64 = sorted_stats.iter().map(|(_, s)| s.commits).sum();
+    let total_added: u64 = sorted_stats.iter().map(|(_, s)| s.lines_added).sum();
+    let total_deleted: u64 = sorted_stats.iter().map(|(_, s)| s.lines_deleted).sum();
+    let total_lines: i64 = share.map_or(0, |metric| sorted_stats.iter().map(|(_, s)| metric.of(s).max(0)).sum());
+    let total_empty: u64 = sorted_stats.iter().map(|(_, s)| s.empty_commits).sum();
+    let total_files: u64 = sorted_stats.iter().map(|(_, s)| s.files_changed).sum();
+
+    // Only shown when there is something to report, keeping the usual table unchanged
+    let show_empty = total_empty > 0;
+    let empty_cell = |n: &dyn std::fmt::Display| if show_empty { format!(" {:>6} |", n) } else { String::new() };
+    let empty_rule = if show_empty { format!("{:-<8}|", "") } else { String::new() };
+    let show_applied = sorted_stats.iter().any(|(_, s)| s.applied_commits.is_some());
+    let applied_cell = |n: &dyn std::fmt::Display| if show_applied { format!(" {:>9} |", n) } else { String::new() };
+    let applied_rule = if show_applied { format!("{:-<11}|", "") } else { String::new() };
+    let total_applied: u64 = sorted_stats.iter().filter_map(|(_, s)| s.applied_commits).sum();
+    let show_weighted = sorted_stats.iter().any(|(_, s)| s.weighted_commits.is_some());
+    let weighted_cell = |n: &dyn std::fmt::Display| if show_weighted { format!(" {:>8} |", n) } else { String::new() };
+    let weighted_rule = if show_weighted { format!("{:-<10}|", "") } else { String::new() };
+    let total_weighted: u64 = sorted_stats.iter().filter_map(|(_, s)| s.weighted_commits).sum();
+    let show_score = sorted_stats.iter().any(|(_, s)| s.score.is_some());
+    let score_cell = |n: &dyn std::fmt::Display| if show_score { format!(" {:>8} |", n) } else { String::new() };
+    let score_rule = if show_score { format!("{:-<10}|", "") } else { String::new() };
+    let total_score: f64 = sorted_stats.iter().filter_map(|(_, s)| s.score).sum();
+    let show_functions = sorted_stats.iter().any(|(_, s)| s.functions_touched.is_some());
+    let functions_cell = |n: &dyn std::fmt::Display| if show_functions { format!(" {:>9} |", n) } else { String::new() };
+    let functions_rule = if show_functions { format!("{:-<11}|", "") } else { String::new() };
+    let total_functions: u64 = sorted_stats.iter().filter_map(|(_, s)| s.functions_touched).sum();
+
+    let activity_width = SPARKLINE_WIDTH.max(display_width(labels.activity));
+    let activity_cell = |line: &str| if sparklines.is_some() { format!(" {:<activity_width$} |", line) } else { String::new() };
+    let activity_rule = if sparklines.is_some() { format!("{:-<width$}|", "", width = activity_width + 2) } else { String::new() };
+
+    let recent_header = if recent.is_some() { format!(" {:>8} | {:>8} |", labels.last_30d, labels.last_90d) } else { String::new() };
+    let recent_rule = if recent.is_some() { format!("{:-<10}|{:-<10}|", "", "") } else { String::new() };
+
+    let team_languages = languages.map(|l| {
+        let mut team: BTreeMap<String, u64> = BTreeMap::new();
+        for (name, _) in sorted_stats {
+            for (language, lines) in l.get(*name).into_iter().flatten() {
+                *team.entry(language.clone()).or_default() += lines;
+            }
+        }
+        team
+    });
+    let team_cell = describe_languages(team_languages.as_ref());
+    let language_cells: Vec<String> = sorted_stats
+        .iter()
+        .map(|(name, _)| describe_languages(languages.and_then(|l| l.get(*name))))
+        .collect();
+    let language_width = language_cells.iter().chain([&team_cell]).map(|c| c.chars().count()).max().unwrap_or(0).max(display_width(labels.languages));
+    let language_cell = |text: &str| if languages.is_some() { format!(" {:<language_width$} |", text) } else { String::new() };
+    let language_rule = if languages.is_some() { format!("{:-<width$}|", "", width = language_width + 2) } else { String::new() };
+
+    let rule = format!(
+        "|{:-<width$}|{:-<10}|{}{}{}{}{}{:-<added$}|{:-<deleted$}|{:-<10}|{:-<12}|{:-<8}|{}{}{}",
+        "", "", empty_rule, applied_rule, weighted_rule, score_rule, functions_rule, "", "", "", "", "", activity_rule, recent_rule, language_rule,
+        width = name_width + 2,
+        added = added_width + 2,
+        deleted = deleted_width + 2
+    );
+    println!(
+        "| {} | {:>8} |{}{}{}{}{} {:>added_width$} | {:>deleted_width$} | {:>8} | {:>10} | {:>6} |{}{}{}",
+        pad_display(labels.contributor, name_width), labels.commits, empty_cell(&labels.empty), applied_cell(&labels.applied), weighted_cell(&labels.weighted), score_cell(&labels.score), functions_cell(&labels.functions), labels.added, labels.deleted, labels.files, labels.net, "%", activity_cell(labels.activity), recent_header,
+        language_cell(labels.languages),
+    );
+    println!("{}", rule);
+
+    for (i, ((name, stat), languages)) in sorted_stats.iter().zip(&language_cells).enumerate() {
+        let pct = match share {
+            Some(metric) => lines_percent(metric.of(stat), total_lines),
+            None if total_commits > 0 => (stat.commits as f64 / total_commits as f64) * 100.0,
+            None => 0.0,
+        };
+        let recent_cells = match recent {
+            Some(recent) => {
+                let activity = recent.get(*name).copied().unwrap_or_default();
+                format!(" {:>8} | {:>8} |", activity.commits_30d, activity.commits_90d)
+            }
+            None => String::new(),
+        };
+        println!(
+            "| {} | {:>8} |{}{}{}{}{} {} | {} | {:>8} | {:>10} | {:>5.1}% |{}{}{}",
+            paint(pad_display(name, name_width), BOLD, color && i == 0),
+            stat.commits,
+            empty_cell(&stat.empty_commits),
+            applied_cell(&stat.applied_commits.unwrap_or(0)),
+            weighted_cell(&stat.weighted_commits.unwrap_or(0)),
+            score_cell(&format!("{:.1}", stat.score.unwrap_or(0.0))),
+            functions_cell(&stat.functions_touched.unwrap_or(0)),
+            paint(format!("{:>added_width$}", stat.lines_added), GREEN, color),
+            paint(format!("{:>deleted_width$}", stat.lines_deleted), RED, color),
+            stat.files_changed,
+            format!("{:+}", stat.net_lines),
+            pct,
+            activity_cell(sparklines.and_then(|(_, s)| s.get(*name)).map_or("", String::as_str)),
+            recent_cells,
+            language_cell(languages),
+        );
+    }
 
-    #[arg(short, long, help = "End date (e.g., 2025-12-31)")]
-    until: Option<String>,
+    let recent_totals = match recent {
+        Some(recent) => {
+            // Only contributors in the table, so the columns add up
+            let (r30, r90) = sorted_stats
+                .iter()
+                .filter_map(|(name, _)| recent.get(*name))
+                .fold((0, 0), |(a, b), r| (a + r.commits_30d, b + r.commits_90d));
+            format!(" {:>8} | {:>8} |", r30, r90)
+        }
+        None => String::new(),
+    };
+    println!("{}", rule);
+    println!(
+        "| {} | {:>8} |{}{}{}{}{} {} | {} | {:>8} | {:>10} | {:>6} |{}{}{}",
+        pad_display(labels.total, name_width),
+        total_commits,
+        empty_cell(&total_empty),
+        applied_cell(&total_applied),
+        weighted_cell(&total_weighted),
+        score_cell(&format!("{:.1}", total_score)),
+        functions_cell(&total_functions),
+        paint(format!("{:>added_width$}", total_added), GREEN, color),
+        paint(format!("{:>deleted_width$}", total_deleted), RED, color),
+        total_files,
+        format!("{:+}", total_added as i64 - total_deleted as i64),
+        "100%",
+        activity_cell(sparklines.map_or("", |(team, _)| team.as_str())),
+        recent_totals,
+        language_cell(&team_cell),
+    );
+}
 
-    #[arg(short, long, action = clap::ArgAction::Append, help = "Merge authors (format: Alias1,Alias2 into first name)")]
-    merge: Vec<String>,
+fn print_graphs(
+    args: &Args,
+    commits: &[CommitRecord],
+    sorted_stats: &[(&String, &ContributorStats)],
+    author_mapping: &HashMap<String, String>,
+) {
+    let by = args.by.as_deref();
+    let bucketed = |daily: BTreeMap<String, u64>| match by {
+        Some(by) => bucket_series(&daily, by, week_start_day(args), true),
+        None => daily,
+    };
 
-    #[arg(short, long, help = "Show visual graph of contributions")]
-    graph: bool,
+    let team_data = bucketed(get_commits_by_date(commits));
+    print_time_graph("Team (all contributors)", &team_data, by);
 
-    #[arg(long, help = "Generate HTML report with commits/lines toggle, period selector, and charts")]
-    html: Option<Option<String>>,
+    let by_contributor = commits_by_contributor(commits, author_mapping);
+    for (name, _) in sorted_stats {
+        let own = by_contributor.get(*name).map_or(&[][..], Vec::as_slice);
+        print_time_graph(name, &bucketed(get_commits_by_date(own.iter().copied())), by);
+    }
+}
 
-    #[arg(short, long, help = "Open HTML report after generation (optionally specify app, e.g. 'Safari', 'Firefox')")]
-    open: Option<Option<String>>,
+/// Commits per weekday (rows, starting at `start`) and hour of day (columns), in each
+/// author's local time.
+type Punchcard = [[u64; 24]; 7];
 
-    #[arg(long, help = "Sort by: commits (default) or lines")]
-    sort: Option<String>,
+fn punchcard<'a>(commits: impl IntoIterator<Item = &'a CommitRecord>, start: Weekday) -> Punchcard {
+    let mut grid = [[0; 24]; 7];
+    for commit in commits {
+        if let Ok(date) = NaiveDate::parse_from_str(&commit.date, "%Y-%m-%d") {
+            let row = (date.weekday().num_days_from_monday() + 7 - start.num_days_from_monday()) % 7;
+            grid[row as usize][commit.hour as usize % 24] += 1;
+        }
+    }
+    grid
 }
 
-struct ContributorStats {
-    commits: u64,
-    lines_added: u64,
-    lines_deleted: u64,
+/// Short weekday names ("Mon") in punch card row order.
+fn weekday_names(start: Weekday) -> Vec<String> {
+    std::iter::successors(Some(start), |day| Some(day.succ()))
+        .take(7)
+        .map(|day| day.to_string())
+        .collect()
 }
 
-fn get_current_branch() -> Option<String> {
-    let output = Command::new("git")
-        .args(["rev-parse", "--abbrev-ref", "HEAD"])
-        .output()
-        .ok()?;
+fn print_punchcard(title: &str, grid: &Punchcard, start: Weekday) {
+    println!("{}", title);
+    println!("{}", "─".repeat(title.chars().count()));
+    let max = grid.iter().flatten().copied().max().unwrap_or(0);
+    if max == 0 {
+        println!("No data");
+        println!();
+        return;
+    }
 
-    if output.status.success() {
-        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        None
+    let hours: String = (0..24).step_by(3).map(|h| format!("{:<6}", h)).collect();
+    println!("     {}", hours.trim_end());
+    let levels = ['░', '▒', '▓', '█'];
+    for (name, row) in weekday_names(start).iter().zip(grid) {
+        let cells: String = row
+            .iter()
+            .map(|&count| match count {
+                0 => "· ".to_string(),
+                _ => format!("{} ", levels[((count * 4).div_ceil(max) as usize).clamp(1, 4) - 1]),
+            })
+            .collect();
+        println!("{:<4} {}", name, cells.trim_end());
     }
+    println!();
 }
 
-fn get_authors(branch: &str, since: &Option<String>, until: &Option<String>) -> Vec<String> {
-    let mut args = vec!["log".to_string(), branch.to_string(), "--format=%aN".to_string()];
-
-    if let Some(s) = since {
-        args.push(format!("--since={}", s));
-    }
-    if let Some(u) = until {
-        args.push(format!("--until={}", u));
+fn print_punchcards(
+    args: &Args,
+    commits: &[CommitRecord],
+    sorted_stats: &[(&String, &ContributorStats)],
+    author_mapping: &HashMap<String, String>,
+) {
+    let start = week_start_day(args);
+    print_punchcard("Punch card: team (all contributors)", &punchcard(commits, start), start);
+    let by_contributor = commits_by_contributor(commits, author_mapping);
+    for (name, _) in sorted_stats {
+        let own = by_contributor.get(*name).map_or(&[][..], Vec::as_slice);
+        print_punchcard(&format!("Punch card: {}", name), &punchcard(own.iter().copied(), start), start);
     }
+}
 
-    let output = Command::new("git")
-        .args(&args)
-        .output()
-        .expect("Failed to execute git log");
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut authors: Vec<String> = stdout
-        .lines()
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_string())
-        .collect();
-
-    authors.sort();
-    authors.dedup();
-    authors
+/// Drops the lines of root commits with `--exclude-initial`, or points out root commits
+/// that look like bulk imports.
+fn handle_initial_commits(commits: &mut [CommitRecord], args: &Args) {
+    if args.exclude_initial {
+        // The commit itself still counts, only its lines are dropped
+        for commit in commits.iter_mut().filter(|c| c.is_root) {
+            commit.lines_added = 0;
+            commit.lines_deleted = 0;
+            commit.lines_by_ext.clear();
+        }
+    } else {
+        for commit in detect_initial_imports(commits) {
+            eprintln!(
+                "Note: initial commit {} by {} accounts for most added lines; use --exclude-initial to leave it out",
+                &commit.hash[..commit.hash.len().min(10)],
+                commit.author
+            );
+        }
+    }
 }
 
-fn get_commit_count(
-    branch: &str,
-    author: &str,
-    since: &Option<String>,
-    until: &Option<String>,
-) -> u64 {
-    let mut args = vec![
-        "log".to_string(),
-        branch.to_string(),
-        format!("--author={}", author),
-        "--oneline".to_string(),
-    ];
+/// Share of a commit's changed lines above which it is treated as a line-ending normalization.
+const LINE_ENDING_SHARE: f64 = 0.5;
 
-    if let Some(s) = since {
-        args.push(format!("--since={}", s));
+/// Drops the lines of commits that mostly convert line endings with `--exclude-line-endings`,
+/// or points them out with `--flag-line-endings`, since such a commit otherwise credits its
+/// author with every line.
+fn handle_line_endings(commits: &mut [CommitRecord], args: &Args) {
+    if !args.exclude_line_endings && !args.flag_line_endings {
+        return;
     }
-    if let Some(u) = until {
-        args.push(format!("--until={}", u));
+    let is_normalization = |c: &CommitRecord| {
+        let lines = c.lines_added + c.lines_deleted;
+        lines > 0 && c.eol_lines as f64 / lines as f64 > LINE_ENDING_SHARE
+    };
+    for commit in commits.iter_mut().filter(|c| is_normalization(c)) {
+        if args.exclude_line_endings {
+            // The commit itself still counts, only its lines are dropped
+            commit.lines_added = 0;
+            commit.lines_deleted = 0;
+            commit.lines_by_ext.clear();
+        } else {
+            eprintln!(
+                "Note: commit {} by {} mostly changes line endings ({} of {} lines); use --exclude-line-endings to leave its lines out",
+                &commit.hash[..commit.hash.len().min(10)],
+                commit.author,
+                commit.eol_lines,
+                commit.lines_added + commit.lines_deleted
+            );
+        }
     }
-
-    let output = Command::new("git")
-        .args(&args)
-        .output()
-        .expect("Failed to execute git log");
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    stdout.lines().filter(|s| !s.is_empty()).count() as u64
 }
 
-fn get_line_stats(
-    branch: &str,
-    author: &str,
-    since: &Option<String>,
-    until: &Option<String>,
-) -> (u64, u64) {
-    let mut args = vec![
-        "log".to_string(),
-        branch.to_string(),
-        format!("--author={}", author),
-        "--pretty=tformat:".to_string(),
-        "--numstat".to_string(),
-    ];
+/// Share of a commit's changed lines above which `--detect-format-only` treats it as a
+/// reformatting.
+const FORMAT_SHARE: f64 = 0.8;
 
-    if let Some(s) = since {
-        args.push(format!("--since={}", s));
+/// Drops the lines of commits that mostly reformat code with `--detect-format-only`, so a
+/// mass prettier or rustfmt run doesn't credit its author with every line it touched.
+fn handle_formatting(commits: &mut [CommitRecord], args: &Args) {
+    if !args.detect_format_only {
+        return;
     }
-    if let Some(u) = until {
-        args.push(format!("--until={}", u));
+    let mut reformats = 0;
+    for commit in commits.iter_mut() {
+        let lines = commit.lines_added + commit.lines_deleted;
+        if lines > 0 && commit.format_lines as f64 / lines as f64 > FORMAT_SHARE {
+            // The commit itself still counts, only its lines are dropped
+            commit.lines_added = 0;
+            commit.lines_deleted = 0;
+            commit.lines_by_ext.clear();
+            reformats += 1;
+        }
     }
+    if reformats > 0 {
+        eprintln!(
+            "Note: left out the lines of {} {} that mostly reformat code (--detect-format-only)",
+            reformats,
+            if reformats == 1 { "commit" } else { "commits" }
+        );
+    }
+}
 
-    let output = Command::new("git")
-        .args(&args)
-        .output()
-        .expect("Failed to execute git log");
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut added: u64 = 0;
-    let mut deleted: u64 = 0;
+/// Totals per contributor, after `--merge`.
+fn contributor_stats(commits: &[CommitRecord], author_mapping: &HashMap<String, String>) -> HashMap<String, ContributorStats> {
+    let mut stats_by_canonical: HashMap<String, ContributorStats> = HashMap::new();
+    for commit in commits {
+        let canonical = canonical_name(&commit.author, author_mapping);
+        stats_by_canonical.entry(canonical.clone()).or_default().add(commit);
+        if let Some(committer) = &commit.applied_by {
+            let canonical = canonical_name(committer, author_mapping);
+            *stats_by_canonical.entry(canonical.clone()).or_default().applied_commits.get_or_insert(0) += 1;
+        }
+    }
+    stats_by_canonical
+}
 
-    for line in stdout.lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 2 {
-            if let Ok(a) = parts[0].parse::<u64>() {
-                added += a;
+/// Opens the generated report with `--open`, in the given app or the default browser.
+fn open_report(args: &Args, output_path: &str) {
+    if args.open.is_some() && !is_repeat_run() {
+        let app = args.open.as_ref().and_then(|o| o.clone());
+        #[cfg(target_os = "macos")]
+        {
+            if let Some(app_name) = app {
+                let _ = Command::new("open")
+                    .args(["-a", &app_name, output_path])
+                    .spawn();
+            } else {
+                let _ = Command::new("open")
+                    .args(["-a", "Safari", output_path])
+                    .spawn();
+            }
+        }
+        #[cfg(target_os = "linux")]
+        {
+            if let Some(app_name) = app {
+                let _ = Command::new(&app_name).arg(output_path).spawn();
+            } else {
+                let _ = Command::new("xdg-open").arg(output_path).spawn();
             }
-            if let Ok(d) = parts[1].parse::<u64>() {
-                deleted += d;
+        }
+        #[cfg(target_os = "windows")]
+        {
+            if let Some(app_name) = app {
+                let _ = Command::new("cmd")
+                    .args(["/C", "start", &app_name, output_path])
+                    .spawn();
+            } else {
+                let _ = Command::new("cmd")
+                    .args(["/C", "start", output_path])
+                    .spawn();
             }
         }
     }
-
-    (added, deleted)
 }
 
-fn build_author_mapping(merge_args: &[String]) -> HashMap<String, String> {
-    let mut mapping = HashMap::new();
-    for m in merge_args {
-        if let Some((alias, canonical)) = m.split_once('=') {
-            mapping.insert(alias.to_string(), canonical.to_string());
+/// With several branches or ranges, writes one HTML report with a tab per revision.
+fn generate_revision_tabs(args: &Args, revisions: &[String], author_mapping: &HashMap<String, String>, progress: &Progress) {
+    if args.html.is_none() || args.html_split.is_some() || args.html_index.is_some() || args.html_dir.is_some() || args.html_fragment.is_some() || args.command.is_some() {
+        Error::Usage("several branches or ranges can only be compared in a single --html report".to_string()).exit();
+    }
+    let tabs: Vec<(String, Vec<CommitRecord>, HashMap<String, String>)> = revisions
+        .iter()
+        .map(|rev| {
+            let sources = open_sources(args, Some(rev)).or_exit();
+            let mut commits = load_commits(&sources, args, args.since.clone(), args.until.clone(), progress).or_exit();
+            handle_initial_commits(&mut commits, args);
+            handle_line_endings(&mut commits, args);
+            handle_formatting(&mut commits, args);
+            handle_imports(&mut commits, args);
+            let mapping = fold_others(&commits, author_mapping, args);
+            (revision_label(&sources), commits, mapping)
+        })
+        .collect();
+    warn_unmatched_aliases(tabs.iter().flat_map(|(_, commits, _)| commits), author_mapping);
+
+    let output_path = args
+        .html
+        .as_ref()
+        .and_then(|o| o.clone())
+        .unwrap_or_else(|| "contrib-report.html".to_string());
+    match generate_html_tabs(args, &tabs, &output_path, progress) {
+        Ok(_) => {
+            println!("HTML report generated: {} ({} tabs)", output_path, tabs.len());
+            open_report(args, &output_path);
         }
+        Err(source) => Error::Write { path: output_path.clone(), source }.exit(),
     }
-    mapping
 }
 
-fn get_commits_by_date(
-    branch: &str,
-    author: Option<&str>,
-    since: &Option<String>,
-    until: &Option<String>,
-) -> BTreeMap<String, u64> {
-    let mut args = vec![
-        "log".to_string(),
-        branch.to_string(),
-        "--format=%ad".to_string(),
-        "--date=short".to_string(),
-    ];
-
-    if let Some(a) = author {
-        args.push(format!("--author={}", a));
-    }
-    if let Some(s) = since {
-        args.push(format!("--since={}", s));
+fn main() {
+    let mut args = Args::parse();
+    if let Some(Commands::Selftest) = &args.command {
+        // Runs its own invocations against the fixture; nothing here applies
+        std::process::exit(if selftest() { 0 } else { 1 });
     }
-    if let Some(u) = until {
-        args.push(format!("--until={}", u));
+    apply_memory_limit(&args);
+    if !args.no_config {
+        let first_repo = repo_paths(&args).or_exit().swap_remove(0);
+        apply_config(&mut args, Path::new(&first_repo));
     }
-
-    let output = Command::new("git")
-        .args(&args)
-        .output()
-        .expect("Failed to execute git log");
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut counts: BTreeMap<String, u64> = BTreeMap::new();
-
-    for line in stdout.lines() {
-        if !line.is_empty() {
-            *counts.entry(line.to_string()).or_insert(0) += 1;
+    apply_period(&mut args);
+    for (option, value, end_of_day) in [("--since", &args.since, false), ("--until", &args.until, true), ("--as-of", &args.as_of, true)] {
+        if let Some(value) = value {
+            parse_option_date(option, value, end_of_day);
         }
     }
+    if let Some(as_of) = args.as_of.clone() {
+        // Later commits are out of the snapshot whatever --until says
+        args.until = match args.until.take() {
+            Some(until) if parse_date(&until, true) < parse_date(&as_of, true) => Some(until),
+            _ => Some(as_of),
+        };
+    }
 
-    counts
-}
-
-fn get_lines_by_date(
-    branch: &str,
-    author: Option<&str>,
-    since: &Option<String>,
-    until: &Option<String>,
-) -> BTreeMap<String, u64> {
-    let mut args = vec![
-        "log".to_string(),
-        branch.to_string(),
-        "--format=%ad".to_string(),
-        "--date=short".to_string(),
-        "--numstat".to_string(),
-    ];
-
-    if let Some(a) = author {
-        args.push(format!("--author={}", a));
-    }
-    if let Some(s) = since {
-        args.push(format!("--since={}", s));
-    }
-    if let Some(u) = until {
-        args.push(format!("--until={}", u));
-    }
-
-    let output = Command::new("git")
-        .args(&args)
-        .output()
-        .expect("Failed to execute git log");
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut counts: BTreeMap<String, u64> = BTreeMap::new();
-    let mut current_date: Option<String> = None;
+    if args.watch && !is_watch_run() {
+        watch(&args);
+    }
+    if args.copy && !is_copy_run() {
+        copy_output(&args);
+    }
 
-    for line in stdout.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-        // Date lines are in format YYYY-MM-DD
-        if trimmed.len() == 10 && trimmed.chars().nth(4) == Some('-') && trimmed.chars().nth(7) == Some('-') {
-            current_date = Some(trimmed.to_string());
-        } else if let Some(ref date) = current_date {
-            // numstat lines: added<tab>deleted<tab>filename
-            let parts: Vec<&str> = trimmed.split_whitespace().collect();
-            if parts.len() >= 2 {
-                if let (Ok(added), Ok(deleted)) = (parts[0].parse::<u64>(), parts[1].parse::<u64>()) {
-                    *counts.entry(date.clone()).or_insert(0) += added + deleted;
-                }
+    if let Some(Commands::Cache { action }) = &args.command {
+        for path in repo_paths(&args).or_exit() {
+            let repo = Repo::discover(Path::new(&path), false);
+            match action {
+                CacheAction::Info => print_cache_info(repo.git_dir()),
+                CacheAction::Clear => match clear_cache(repo.git_dir()) {
+                    Ok(true) => println!("Cleared stats cache in {}", repo.git_dir().display()),
+                    Ok(false) => println!("No stats cache in {}", repo.git_dir().display()),
+                    Err(source) => Error::Write {
+                        path: repo.git_dir().display().to_string(),
+                        source,
+                    }
+                    .exit(),
+                },
             }
         }
+        return;
     }
 
-    counts
-}
+    let author_mapping = build_author_mapping(&args.merge);
 
-fn print_time_graph(title: &str, data: &BTreeMap<String, u64>) {
-    if data.is_empty() {
-        println!("{}: No data", title);
-        println!();
+    let progress = Progress::new(&args);
+
+    if let Some(Commands::Serve { listen, port, token_file, reload_every }) = &args.command {
+        serve(&args, listen, *port, token_file.as_deref(), *reload_every, &author_mapping);
         return;
     }
 
-    let height = 8;
-    let width = data.len().min(60);
-
-    println!("{}", title);
-    println!("{}", "─".repeat(title.len()));
-
-    let values: Vec<u64> = if data.len() > width {
-        let chunk_size = (data.len() + width - 1) / width;
-        data.values()
-            .collect::<Vec<_>>()
-            .chunks(chunk_size)
-            .map(|chunk| chunk.iter().copied().sum())
-            .collect()
-    } else {
-        data.values().copied().collect()
-    };
+    let revisions = requested_revisions(&args);
+    if revisions.len() > 1 {
+        generate_revision_tabs(&args, &revisions, &author_mapping, &progress);
+        return;
+    }
 
-    let max_val = *values.iter().max().unwrap_or(&1);
+    let sources = open_sources(&args, revisions.first().map(String::as_str)).or_exit();
+    let branch = revision_label(&sources);
 
-    for row in (0..height).rev() {
-        let threshold = (row as f64 / height as f64) * max_val as f64;
-        for &val in &values {
-            if val as f64 > threshold {
-                print!("█");
-            } else {
-                print!(" ");
-            }
-        }
-        if row == height - 1 {
-            println!(" {}", max_val);
-        } else if row == 0 {
-            println!(" 0");
-        } else {
-            println!();
-        }
+    if args.check_identities {
+        // Raw identities, before the automatic same-email unification hides the drift
+        let commits = scan_sources(&sources, &args, args.since.clone(), args.until.clone(), &progress).or_exit();
+        print_identity_report(&commits);
+        return;
     }
 
-    let dates: Vec<&String> = data.keys().collect();
-    if let (Some(first), Some(last)) = (dates.first(), dates.last()) {
-        println!("{:<width$}{}", first, last, width = values.len().saturating_sub(last.len()));
+    if let Some(Commands::Hotspots { limit }) = &args.command {
+        let commits = load_commits(&sources, &args, args.since.clone(), args.until.clone(), &progress).or_exit();
+        print_hotspots(&sources, &args, &commits, &author_mapping, *limit, &progress);
+        return;
     }
-    println!();
-}
-
-fn generate_html_report(
-    branch: &str,
-    sorted_stats: &[(&String, &ContributorStats)],
-    author_mapping: &HashMap<String, String>,
-    since: &Option<String>,
-    until: &Option<String>,
-    output_path: &str,
-) -> std::io::Result<()> {
-    let mut file = File::create(output_path)?;
-
-    let reverse_mapping: HashMap<&String, Vec<&String>> = {
-        let mut map: HashMap<&String, Vec<&String>> = HashMap::new();
-        for (alias, canonical) in author_mapping {
-            map.entry(canonical).or_default().push(alias);
-        }
-        map
-    };
 
-    let mut weekly_commits: BTreeMap<String, BTreeMap<String, u64>> = BTreeMap::new();
-    let mut weekly_lines: BTreeMap<String, BTreeMap<String, u64>> = BTreeMap::new();
-    let mut total_weekly_commits: BTreeMap<String, u64> = BTreeMap::new();
-    let mut total_weekly_lines: BTreeMap<String, u64> = BTreeMap::new();
+    if let Some(Commands::Impact) = &args.command {
+        let commits = load_commits(&sources, &args, args.since.clone(), args.until.clone(), &progress).or_exit();
+        print_impact(&sources, &args, &commits, &author_mapping, &progress);
+        return;
+    }
 
-    for (canonical_name, _) in sorted_stats {
-        let mut authors_to_query: Vec<&str> = vec![canonical_name.as_str()];
-        if let Some(aliases) = reverse_mapping.get(canonical_name) {
-            for alias in aliases {
-                authors_to_query.push(alias.as_str());
-            }
+    if let Some(path) = &args.policy {
+        let policy = Policy::load(path);
+        let commits = load_commits(&sources, &args, args.since.clone(), args.until.clone(), &progress).or_exit();
+        let violating = print_policy_report(&policy, &commits);
+        if violating > 0 && args.fail_on_violation {
+            std::process::exit(EXIT_VIOLATIONS);
         }
+        return;
+    }
 
-        let mut combined_commits: BTreeMap<String, u64> = BTreeMap::new();
-        let mut combined_lines: BTreeMap<String, u64> = BTreeMap::new();
-        for author in authors_to_query {
-            let commits_data = get_commits_by_date(branch, Some(author), since, until);
-            for (date, count) in commits_data {
-                *combined_commits.entry(date.clone()).or_insert(0) += count;
-                *total_weekly_commits.entry(date).or_insert(0) += count;
-            }
-            let lines_data = get_lines_by_date(branch, Some(author), since, until);
-            for (date, count) in lines_data {
-                *combined_lines.entry(date.clone()).or_insert(0) += count;
-                *total_weekly_lines.entry(date).or_insert(0) += count;
+    if let Some(dir) = &args.html_index {
+        let repos: Vec<(String, String, Vec<CommitRecord>)> = sources
+            .iter()
+            .map(|source| {
+                let mut commits = load_commits(std::slice::from_ref(source), &args, args.since.clone(), args.until.clone(), &progress).or_exit();
+                handle_initial_commits(&mut commits, &args);
+                handle_line_endings(&mut commits, &args);
+                handle_formatting(&mut commits, &args);
+                handle_imports(&mut commits, &args);
+                (source.repo.name().to_string(), source.label.clone(), commits)
+            })
+            .collect();
+        warn_unmatched_aliases(repos.iter().flat_map(|(_, _, commits)| commits), &author_mapping);
+        match generate_html_index(&args, &repos, &author_mapping, dir, &progress) {
+            Ok(_) => {
+                let index = Path::new(dir).join("index.html");
+                println!("HTML index written to {} ({} repositories)", index.display(), repos.len());
+                open_report(&args, &index.to_string_lossy());
             }
+            Err(source) => Error::Write { path: dir.clone(), source }.exit(),
         }
-        weekly_commits.insert(canonical_name.to_string(), combined_commits);
-        weekly_lines.insert(canonical_name.to_string(), combined_lines);
+        return;
     }
 
-    let colors = ["#58a6ff", "#3fb950", "#f0883e", "#a371f7", "#f85149", "#8b949e"];
-
-    let contributors_json: Vec<String> = sorted_stats
-        .iter()
-        .enumerate()
-        .map(|(i, (name, stats))| {
-            let commits = weekly_commits.get(*name).cloned().unwrap_or_default();
-            let commits_json: String = commits
-                .iter()
-                .map(|(date, count)| format!("{{\"date\":\"{}\",\"count\":{}}}", date, count))
-                .collect::<Vec<_>>()
-                .join(",");
-
-            let lines = weekly_lines.get(*name).cloned().unwrap_or_default();
-            let lines_json: String = lines
-                .iter()
-                .map(|(date, count)| format!("{{\"date\":\"{}\",\"count\":{}}}", date, count))
-                .collect::<Vec<_>>()
-                .join(",");
-
-            format!(
-                r#"{{
-                    "name": "{}",
-                    "commits": {},
-                    "added": {},
-                    "deleted": {},
-                    "color": "{}",
-                    "weeklyCommits": [{}],
-                    "weeklyLines": [{}]
-                }}"#,
-                name,
-                stats.commits,
-                stats.lines_added,
-                stats.lines_deleted,
-                colors[i % colors.len()],
-                commits_json,
-                lines_json
-            )
-        })
-        .collect();
-
-    let total_weekly_commits_json: String = total_weekly_commits
-        .iter()
-        .map(|(date, count)| format!("{{\"date\":\"{}\",\"count\":{}}}", date, count))
-        .collect::<Vec<_>>()
-        .join(",");
-
-    let total_weekly_lines_json: String = total_weekly_lines
-        .iter()
-        .map(|(date, count)| format!("{{\"date\":\"{}\",\"count\":{}}}", date, count))
-        .collect::<Vec<_>>()
-        .join(",");
-
-    let since_display = since.clone().unwrap_or_else(|| "beginning".to_string());
-    let until_display = until.clone().unwrap_or_else(|| "now".to_string());
-
-    let html = format!(
-        r#"<!DOCTYPE html>
-<html lang="en">
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>Contributors - {branch}</title>
-    <script src="https://cdn.jsdelivr.net/npm/chart.js"></script>
-    <script src="https://cdn.jsdelivr.net/npm/chartjs-adapter-date-fns"></script>
-    <style>
-        * {{ box-sizing: border-box; margin: 0; padding: 0; }}
-        body {{
-            font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif;
-            background: #0d1117;
-            color: #c9d1d9;
-            padding: 24px;
-        }}
-        .container {{ max-width: 1200px; margin: 0 auto; }}
-        h1 {{ font-size: 24px; font-weight: 600; margin-bottom: 8px; }}
-        .subtitle {{ color: #8b949e; font-size: 14px; margin-bottom: 24px; }}
-        .card {{
-            background: #161b22;
-            border: 1px solid #30363d;
-            border-radius: 6px;
-            padding: 16px;
-            margin-bottom: 16px;
-        }}
-        .card-title {{ font-size: 14px; font-weight: 600; margin-bottom: 16px; color: #c9d1d9; }}
-        .main-chart {{ height: 200px; }}
-        .contributors-grid {{ display: grid; grid-template-columns: repeat(auto-fit, minmax(350px, 1fr)); gap: 16px; }}
-        .contributor-card {{
-            background: #161b22;
-            border: 1px solid #30363d;
-            border-radius: 6px;
-            padding: 16px;
-        }}
-        .contributor-header {{ display: flex; align-items: center; gap: 12px; margin-bottom: 12px; }}
-        .avatar {{
-            width: 48px; height: 48px;
-            border-radius: 50%;
-            display: flex; align-items: center; justify-content: center;
-            font-weight: 600; font-size: 18px; color: white;
-        }}
-        .contributor-info h3 {{ font-size: 16px; font-weight: 600; }}
-        .contributor-stats {{ font-size: 12px; color: #8b949e; margin-top: 4px; }}
-        .contributor-stats .added {{ color: #3fb950; }}
-        .contributor-stats .deleted {{ color: #f85149; }}
-        .rank {{
-            margin-left: auto;
-            background: #21262d;
-            padding: 4px 8px;
-            border-radius: 20px;
-            font-size: 12px;
-            color: #8b949e;
-        }}
-        .mini-chart {{ height: 300px; margin-top: 12px; }}
-        canvas {{ width: 100% !important; }}
-        .period-toggle {{
-            display: flex;
-            gap: 0;
-            margin-bottom: 16px;
-        }}
-        .period-toggle button {{
-            background: #21262d;
-            border: 1px solid #30363d;
-            color: #c9d1d9;
-            padding: 6px 16px;
-            font-size: 12px;
-            cursor: pointer;
-            transition: all 0.2s;
-        }}
-        .period-toggle button:first-child {{
-            border-radius: 6px 0 0 6px;
-        }}
-        .period-toggle button:last-child {{
-            border-radius: 0 6px 6px 0;
-        }}
-        .period-toggle button:not(:first-child) {{
-            border-left: none;
-        }}
-        .period-toggle button:hover {{
-            background: #30363d;
-        }}
-        .period-toggle button.active {{
-            background: #58a6ff;
-            border-color: #58a6ff;
-            color: #0d1117;
-        }}
-        .card-header {{
-            display: flex;
-            justify-content: space-between;
-            align-items: center;
-            margin-bottom: 16px;
-        }}
-    </style>
-</head>
-<body>
-    <div class="container">
-        <h1>Contributors</h1>
-        <div class="subtitle">Contributions to {branch} · {since_display} to {until_display}</div>
-
-        <div class="card">
-            <div class="card-header">
-                <div class="card-title" style="margin-bottom: 0;" id="chartTitle">Commits over time</div>
-                <div style="display: flex; gap: 16px;">
-                    <div class="period-toggle metric-toggle">
-                        <button class="active" data-metric="commits">Commits</button>
-                        <button data-metric="lines">Lines</button>
-                    </div>
-                    <div class="period-toggle">
-                        <button class="active" data-period="1">1 Day</button>
-                        <button data-period="3">3 Days</button>
-                        <button data-period="7">1 Week</button>
-                        <button data-period="30">1 Month</button>
-                        <button data-period="365">1 Year</button>
-                    </div>
-                </div>
-            </div>
-            <div class="main-chart">
-                <canvas id="mainChart"></canvas>
-            </div>
-        </div>
-
-        <div class="contributors-grid" id="contributorsGrid"></div>
-    </div>
-
-    <script>
-    const totalWeeklyCommits = [{total_weekly_commits_json}];
-    const totalWeeklyLines = [{total_weekly_lines_json}];
-    const contributors = [{contributors_json}];
-
-    // Calculate global bounds from both datasets
-    const allCommitDates = totalWeeklyCommits.map(d => d.date);
-    const allLinesDates = totalWeeklyLines.map(d => d.date);
-    const allDates = [...new Set([...allCommitDates, ...allLinesDates])].sort();
-    const globalMinDate = allDates[0];
-    const globalMaxDate = allDates[allDates.length - 1];
-
-    // Current metric state
-    let currentMetric = 'commits';
-    const getTotalWeekly = () => currentMetric === 'commits' ? totalWeeklyCommits : totalWeeklyLines;
-    const getContribWeekly = (contrib) => currentMetric === 'commits' ? contrib.weeklyCommits : contrib.weeklyLines;
-
-    // Calculate totals for percentages
-    const totalCommits = contributors.reduce((sum, c) => sum + c.commits, 0);
-    const totalLines = contributors.reduce((sum, c) => sum + c.added + c.deleted, 0);
-
-    // Aggregation function
-    function aggregateByPeriod(data, days) {{
-        if (days === 1) return data.map(d => ({{ x: d.date, y: d.count }}));
-
-        const sorted = [...data].sort((a, b) => a.date.localeCompare(b.date));
-        const buckets = {{}};
-
-        sorted.forEach(d => {{
-            const date = new Date(d.date);
-            const bucketStart = new Date(date);
-            bucketStart.setDate(bucketStart.getDate() - (bucketStart.getDate() % days));
-            const key = bucketStart.toISOString().split('T')[0];
-            buckets[key] = (buckets[key] || 0) + d.count;
-        }});
-
-        return Object.entries(buckets)
-            .map(([date, count]) => ({{ x: date, y: count }}))
-            .sort((a, b) => a.x.localeCompare(b.x));
-    }}
-
-    // Pad data with boundary points
-    function padData(data, minDate, maxDate) {{
-        const result = [...data];
-        if (result.length === 0 || result[0].x > minDate) {{
-            result.unshift({{ x: minDate, y: 0 }});
-        }}
-        if (result.length === 0 || result[result.length - 1].x < maxDate) {{
-            result.push({{ x: maxDate, y: 0 }});
-        }}
-        return result;
-    }}
-
-    // Fill data to match all dates from reference (for proper stacking)
-    function fillToAllDates(data, referenceDates) {{
-        const dataMap = new Map(data.map(d => [d.x, d.y]));
-        return referenceDates.map(date => ({{
-            x: date,
-            y: dataMap.get(date) || 0
-        }}));
-    }}
-
-    // Calculate max for current period
-    function getGlobalMax(period) {{
-        const totalAgg = aggregateByPeriod(getTotalWeekly(), period);
-        return Math.max(...totalAgg.map(d => d.y), 1);
-    }}
-
-    let currentPeriod = 1;
-    let mainChart, contribCharts = [];
-
-    // Main chart - stacked area with all contributors (reversed: smallest at bottom, largest at top)
-    const mainCtx = document.getElementById('mainChart').getContext('2d');
-    const allDatesForPeriod = (period) => aggregateByPeriod(getTotalWeekly(), period).map(d => d.x);
-    const contributorsReversed = [...contributors].reverse();
-    const mainDatasets = contributorsReversed.map((contrib, i) => ({{
-        label: contrib.name,
-        data: fillToAllDates(aggregateByPeriod(getContribWeekly(contrib), 1), allDatesForPeriod(1)),
-        borderColor: contrib.color,
-        backgroundColor: contrib.color + '80',
-        fill: 'origin',
-        tension: 0.03,
-        pointRadius: 0,
-        pointHoverRadius: 4,
-        pointHoverBackgroundColor: contrib.color,
-        borderWidth: 1
-    }}));
-
-    mainChart = new Chart(mainCtx, {{
-        type: 'line',
-        data: {{ datasets: mainDatasets }},
-        options: {{
-            responsive: true,
-            maintainAspectRatio: false,
-            interaction: {{
-                intersect: false,
-                mode: 'index'
-            }},
-            plugins: {{
-                legend: {{ display: false }},
-                tooltip: {{
-                    backgroundColor: '#161b22',
-                    borderColor: '#30363d',
-                    borderWidth: 1,
-                    titleColor: '#c9d1d9',
-                    bodyColor: '#c9d1d9',
-                    padding: 12,
-                    displayColors: true,
-                    callbacks: {{
-                        title: (items) => items[0]?.label || '',
-                        label: (item) => `${{item.dataset.label}}: ${{item.parsed.y}} ${{currentMetric === 'commits' ? 'commits' : 'lines'}}`
-                    }}
-                }}
-            }},
-            scales: {{
-                x: {{
-                    type: 'time',
-                    time: {{ unit: 'month' }},
-                    min: globalMinDate,
-                    max: globalMaxDate,
-                    grid: {{ color: '#21262d' }},
-                    ticks: {{ color: '#8b949e' }}
-                }},
-                y: {{
-                    stacked: true,
-                    beginAtZero: true,
-                    grid: {{ color: '#21262d' }},
-                    ticks: {{ color: '#8b949e', precision: 0 }}
-                }}
-            }}
-        }}
-    }});
-
-    // Contributor cards
-    const grid = document.getElementById('contributorsGrid');
-
-    contributors.forEach((contrib, index) => {{
-        const initials = contrib.name.split(' ').map(n => n[0]).join('').toUpperCase();
-        const card = document.createElement('div');
-        card.className = 'contributor-card';
-        card.innerHTML = `
-            <div class="contributor-header">
-                <div class="avatar" style="background: ${{contrib.color}};">${{initials}}</div>
-                <div class="contributor-info">
-                    <h3>${{contrib.name}}</h3>
-                    <div class="contributor-stats">
-                        <span id="metric-${{index}}">${{contrib.commits.toLocaleString()}} commits</span>
-                        <span id="percent-${{index}}" style="color: #8b949e; margin-left: 8px;">${{(contrib.commits / totalCommits * 100).toFixed(1)}}%</span> &nbsp;
-                        <span class="added">${{contrib.added.toLocaleString()}} ++</span> &nbsp;
-                        <span class="deleted">${{contrib.deleted.toLocaleString()}} --</span>
-                    </div>
-                </div>
-                <span class="rank">#${{index + 1}}</span>
-            </div>
-            <div class="mini-chart">
-                <canvas id="chart-${{index}}"></canvas>
-            </div>
-        `;
-        grid.appendChild(card);
-
-        const contribData = fillToAllDates(aggregateByPeriod(getContribWeekly(contrib), 1), allDatesForPeriod(1));
-
-        // Mini chart
-        const chart = new Chart(document.getElementById(`chart-${{index}}`).getContext('2d'), {{
-            type: 'line',
-            data: {{
-                datasets: [{{
-                    data: contribData,
-                    borderColor: contrib.color,
-                    backgroundColor: contrib.color + '20',
-                    fill: true,
-                    tension: 0.03,
-                    pointRadius: 0,
-                    pointHoverRadius: 5,
-                    pointHoverBackgroundColor: contrib.color,
-                    borderWidth: 2
-                }}]
-            }},
-            options: {{
-                responsive: true,
-                maintainAspectRatio: false,
-                interaction: {{
-                    intersect: false,
-                    mode: 'index'
-                }},
-                plugins: {{
-                    legend: {{ display: false }},
-                    tooltip: {{
-                        backgroundColor: '#161b22',
-                        borderColor: '#30363d',
-                        borderWidth: 1,
-                        titleColor: '#c9d1d9',
-                        bodyColor: '#c9d1d9',
-                        padding: 10,
-                        displayColors: false,
-                        callbacks: {{
-                            title: (items) => items[0]?.label || '',
-                            label: (item) => `${{item.parsed.y}} ${{currentMetric === 'commits' ? 'commits' : 'lines'}}`
-                        }}
-                    }}
-                }},
-                scales: {{
-                    x: {{
-                        type: 'time',
-                        time: {{ unit: 'month' }},
-                        min: globalMinDate,
-                        max: globalMaxDate,
-                        grid: {{ display: false }},
-                        ticks: {{ color: '#8b949e', maxTicksLimit: 4 }}
-                    }},
-                    y: {{
-                        beginAtZero: true,
-                        min: -getGlobalMax(1) * 0.05,
-                        max: getGlobalMax(1),
-                        grid: {{ display: false }},
-                        ticks: {{ display: false }}
-                    }}
-                }}
-            }}
-        }});
-        contribCharts.push({{ chart, contrib }});
-    }});
-
-    // Period toggle handler
-    function updateCharts(period) {{
-        currentPeriod = period;
-        const globalMax = getGlobalMax(period);
-        const dates = allDatesForPeriod(period);
-
-        // Update main chart (all contributor datasets)
-        contributorsReversed.forEach((contrib, i) => {{
-            mainChart.data.datasets[i].data = fillToAllDates(aggregateByPeriod(getContribWeekly(contrib), period), dates);
-        }});
-        mainChart.update();
-
-        // Update contributor charts
-        contribCharts.forEach(({{ chart, contrib }}) => {{
-            const newData = fillToAllDates(aggregateByPeriod(getContribWeekly(contrib), period), dates);
-            chart.data.datasets[0].data = newData;
-            chart.options.scales.y.max = globalMax;
-            chart.options.scales.y.min = -globalMax * 0.05;
-            chart.update();
-        }});
-    }}
-
-    // Period toggle button click handlers
-    document.querySelectorAll('.period-toggle:not(.metric-toggle) button').forEach(btn => {{
-        btn.addEventListener('click', () => {{
-            document.querySelectorAll('.period-toggle:not(.metric-toggle) button').forEach(b => b.classList.remove('active'));
-            btn.classList.add('active');
-            updateCharts(parseInt(btn.dataset.period));
-        }});
-    }});
-
-    // Metric toggle button click handlers
-    document.querySelectorAll('.metric-toggle button').forEach(btn => {{
-        btn.addEventListener('click', () => {{
-            document.querySelectorAll('.metric-toggle button').forEach(b => b.classList.remove('active'));
-            btn.classList.add('active');
-            currentMetric = btn.dataset.metric;
-            document.getElementById('chartTitle').textContent = currentMetric === 'commits' ? 'Commits over time' : 'Lines changed over time';
-            // Update contributor card stats and percentages
-            contributors.forEach((contrib, index) => {{
-                const value = currentMetric === 'commits' ? contrib.commits : (contrib.added + contrib.deleted);
-                const total = currentMetric === 'commits' ? totalCommits : totalLines;
-                const percent = (value / total * 100).toFixed(1);
-                const label = currentMetric === 'commits' ? 'commits' : 'lines';
-                document.getElementById(`metric-${{index}}`).textContent = `${{value.toLocaleString()}} ${{label}}`;
-                document.getElementById(`percent-${{index}}`).textContent = `${{percent}}%`;
-            }});
-            updateCharts(currentPeriod);
-        }});
-    }});
-
-    // Set default: Lines metric and 1 Week period
-    document.querySelector('.metric-toggle button[data-metric="lines"]').click();
-    document.querySelector('.period-toggle:not(.metric-toggle) button[data-period="7"]').click();
-    </script>
-</body>
-</html>"#,
-        branch = branch,
-        since_display = since_display,
-        until_display = until_display,
-        total_weekly_commits_json = total_weekly_commits_json,
-        total_weekly_lines_json = total_weekly_lines_json,
-        contributors_json = contributors_json.join(","),
-    );
-
-    file.write_all(html.as_bytes())?;
-    Ok(())
-}
-
-fn main() {
-    let args = Args::parse();
-
-    let branch = args.branch.unwrap_or_else(|| {
-        get_current_branch().unwrap_or_else(|| "main".to_string())
-    });
+    if args.ownership {
+        // Blame reaches back to any commit, so resolve identities over the full history
+        let commits = load_commits(&sources, &args, None, None, &progress).or_exit();
+        print_ownership_report(&sources, &branch, &args, &commits, &author_mapping, &progress);
+        return;
+    }
 
-    let author_mapping = build_author_mapping(&args.merge);
+    if let Some(name) = &args.author_branches {
+        print_author_branches(&args, &author_mapping, name, &progress);
+        return;
+    }
 
-    let raw_authors = get_authors(&branch, &args.since, &args.until);
+    let mut commits = load_commits(&sources, &args, args.since.clone(), args.until.clone(), &progress).or_exit();
+    warn_unmatched_aliases(&commits, &author_mapping);
+    handle_initial_commits(&mut commits, &args);
+    handle_line_endings(&mut commits, &args);
+    handle_formatting(&mut commits, &args);
+    handle_imports(&mut commits, &args);
 
-    let mut stats_by_canonical: HashMap<String, ContributorStats> = HashMap::new();
+    if let Some(name) = &args.explain {
+        print_explanation(&sources, &args, &commits, &author_mapping, name, &progress);
+        return;
+    }
 
-    for author in &raw_authors {
-        let canonical = author_mapping.get(author).unwrap_or(author);
+    // Bus factor and fading look at everyone; the other reports at the --top contributors
+    let full_mapping = author_mapping;
+    let author_mapping = fold_others(&commits, &full_mapping, &args);
+    let stats_by_canonical = contributor_stats(&commits, &author_mapping);
 
-        let commits = get_commit_count(&branch, author, &args.since, &args.until);
-        let (added, deleted) = get_line_stats(&branch, author, &args.since, &args.until);
+    let share = share_metric(&args);
+    let sorted_stats = ranked_contributors(&stats_by_canonical, &args);
 
-        let entry = stats_by_canonical
-            .entry(canonical.clone())
-            .or_insert(ContributorStats {
-                commits: 0,
-                lines_added: 0,
-                lines_deleted: 0,
-            });
+    let format = args.format.as_deref().unwrap_or("table");
 
-        entry.commits += commits;
-        entry.lines_added += added;
-        entry.lines_deleted += deleted;
-    }
+    let recent = args
+        .recent
+        .then(|| get_recent_activity(&sources, &args, &author_mapping, &progress));
+    let previous = matches!(format, "table" | "json" | "csv" | "digest")
+        .then(|| previous_period(&args, &author_mapping, &progress).or_exit())
+        .flatten();
 
-    let sort_by_lines = args.sort.as_deref() == Some("lines");
-    let mut sorted_stats: Vec<(&String, &ContributorStats)> = stats_by_canonical.iter().collect();
-    if sort_by_lines {
-        sorted_stats.sort_by(|a, b| {
-            let a_lines = a.1.lines_added + a.1.lines_deleted;
-            let b_lines = b.1.lines_added + b.1.lines_deleted;
-            b_lines.cmp(&a_lines)
-        });
-    } else {
-        sorted_stats.sort_by(|a, b| b.1.commits.cmp(&a.1.commits));
+    if format == "table" && args.html_fragment.as_deref() != Some("-") {
+        if sources.len() > 1 {
+            println!("Repositories: {}", branch);
+        } else if branch.contains("..") {
+            println!("Range: {}", branch);
+        } else {
+            println!("Branch: {}", branch);
+        }
+        if args.no_merges {
+            println!("Merge commits: excluded");
+        }
+        if args.first_parent {
+            println!("History: first-parent only");
+        }
+        println!();
     }
 
-    println!("Branch: {}", branch);
-    println!();
-
-    let name_width = sorted_stats
-        .iter()
-        .map(|(name, _)| name.len())
-        .max()
-        .unwrap_or(12)
-        .max(12);
-
-    let total_commits: u64 = sorted_stats.iter().map(|(_, s)| s.commits).sum();
-    let total_added: u64 = sorted_stats.iter().map(|(_, s)| s.lines_added).sum();
-    let total_deleted: u64 = sorted_stats.iter().map(|(_, s)| s.lines_deleted).sum();
-    let total_lines: u64 = total_added + total_deleted;
-
-    if args.html.is_some() {
+    if args.export.is_some() {
+        let per_contributor = args.export_by.as_deref() == Some("contributor");
+        match std::fs::write(&args.export_path, activity_calendar(&branch, &commits, &author_mapping, per_contributor)) {
+            Ok(_) => println!("Calendar written to {}", args.export_path),
+            Err(source) => Error::Write {
+                path: args.export_path.clone(),
+                source,
+            }
+            .exit(),
+        }
+    } else if let Some(path) = &args.svg {
+        match std::fs::write(path, render_svg(&args, &branch, &commits, &sorted_stats, &author_mapping)) {
+            Ok(_) => println!("SVG chart written to {}", path),
+            Err(source) => Error::Write { path: path.clone(), source }.exit(),
+        }
+    } else if let Some(dir) = &args.html_split {
+        match generate_html_split(&args, &branch, &commits, &sorted_stats, &author_mapping, dir, &progress) {
+            Ok(_) => println!("HTML report written to {}", Path::new(dir).join("index.html").display()),
+            Err(source) => Error::Write { path: dir.clone(), source }.exit(),
+        }
+    } else if let Some(dir) = &args.html_dir {
+        match generate_html_dir(&sources, &args, &commits, &sorted_stats, &author_mapping, dir, &progress) {
+            Ok(_) => {
+                let index = Path::new(dir).join("index.html");
+                println!("HTML report written to {}", index.display());
+                open_report(&args, &index.to_string_lossy());
+            }
+            Err(source) => Error::Write { path: dir.clone(), source }.exit(),
+        }
+    } else if let Some(path) = &args.html_fragment {
+        match generate_html_fragment(&args, &branch, &commits, &sorted_stats, &author_mapping, path, &progress) {
+            Ok(_) if path == "-" => {}
+            Ok(_) => println!("HTML fragment written to {}", path),
+            Err(source) => Error::Write { path: path.clone(), source }.exit(),
+        }
+    } else if args.html.is_some() {
         let output_path = args
             .html
             .as_ref()
@@ -899,104 +3114,107 @@ fn main() {
             .unwrap_or_else(|| "contrib-report.html".to_string());
 
         match generate_html_report(
+            &args,
             &branch,
+            &commits,
             &sorted_stats,
             &author_mapping,
-            &args.since,
-            &args.until,
             &output_path,
+            &progress,
         ) {
             Ok(_) => {
                 println!("HTML report generated: {}", output_path);
-                if args.open.is_some() {
-                    let app = args.open.as_ref().and_then(|o| o.clone());
-                    #[cfg(target_os = "macos")]
-                    {
-                        if let Some(app_name) = app {
-                            let _ = Command::new("open")
-                                .args(["-a", &app_name, &output_path])
-                                .spawn();
-                        } else {
-                            let _ = Command::new("open")
-                                .args(["-a", "Safari", &output_path])
-                                .spawn();
-                        }
-                    }
-                    #[cfg(target_os = "linux")]
-                    {
-                        if let Some(app_name) = app {
-                            let _ = Command::new(&app_name).arg(&output_path).spawn();
-                        } else {
-                            let _ = Command::new("xdg-open").arg(&output_path).spawn();
-                        }
-                    }
-                    #[cfg(target_os = "windows")]
-                    {
-                        if let Some(app_name) = app {
-                            let _ = Command::new("cmd")
-                                .args(["/C", "start", &app_name, &output_path])
-                                .spawn();
-                        } else {
-                            let _ = Command::new("cmd")
-                                .args(["/C", "start", &output_path])
-                                .spawn();
-                        }
-                    }
-                }
+                open_report(&args, &output_path);
+            }
+            Err(source) => Error::Write { path: output_path, source }.exit(),
+        }
+    } else if format == "json" {
+        println!("{}", json_report(&args, &branch, &commits, &sorted_stats, &author_mapping, recent.as_ref(), previous.as_ref()).or_exit());
+    } else if format == "digest" {
+        print_digest(&sources, &args, &commits, &sorted_stats, &author_mapping, previous.as_ref(), &progress);
+    } else if format == "csv" {
+        let ids = contributor_ids(&commits, &author_mapping, &args);
+        print_csv_report(&sorted_stats, &ids, share, recent.as_ref(), previous.as_ref());
+    } else {
+        if args.summary {
+            let bus = BusFactor::of(&commit_owners(&commits, &full_mapping), args.bus_factor_threshold);
+            let contributors: HashSet<&String> = commits.iter().map(|c| canonical_name(&c.author, &full_mapping)).collect();
+            print_summary(&commits, contributors.len(), week_start_day(&args), bus.as_ref());
+        }
+
+        let graph_first = args.layout.as_deref() == Some("graph-first");
+        if args.graph && graph_first {
+            print_graphs(&args, &commits, &sorted_stats, &author_mapping);
+        }
+
+        let languages = args.languages.then(|| language_breakdown(&commits, &author_mapping));
+        let sparklines = args.activity.then(|| activity_sparklines(&commits, &author_mapping));
+        print_table(&sorted_stats, share, recent.as_ref(), languages.as_ref(), sparklines.as_ref(), use_color(&args), labels(&args));
+        if let Some(previous) = &previous {
+            print_comparison(&sorted_stats, previous, LinesMetric::from_args(&args));
+        }
+
+        if args.per_repo {
+            print_per_repo(&repo_breakdowns(&commits, &author_mapping, &args), share, use_color(&args), labels(&args));
+        }
+
+        if args.benchmarks {
+            let mut active_dates: HashMap<&String, BTreeSet<&str>> = HashMap::new();
+            for commit in &commits {
+                active_dates
+                    .entry(canonical_name(&commit.author, &author_mapping))
+                    .or_default()
+                    .insert(commit.date.as_str());
             }
-            Err(e) => eprintln!("Error generating HTML report: {}", e),
+            let active_days: HashMap<String, u64> = active_dates
+                .into_iter()
+                .map(|(name, dates)| (name.clone(), dates.len() as u64))
+                .collect();
+            let ranked: Vec<_> = sorted_stats.iter().copied().filter(|(name, _)| !is_others(&args, name)).collect();
+            print_benchmarks(&ranked, &active_days, LinesMetric::from_args(&args));
         }
-    } else if args.graph {
-        let team_data = get_commits_by_date(&branch, None, &args.since, &args.until);
-        print_time_graph("Team (all contributors)", &team_data);
 
-        for (name, _) in &sorted_stats {
-            let author_data = get_commits_by_date(&branch, Some(name), &args.since, &args.until);
-            print_time_graph(name, &author_data);
+        if args.velocity {
+            print_velocity(&sorted_stats, &velocities(&args, &commits, &sorted_stats), LinesMetric::from_args(&args));
         }
-    } else {
-        println!(
-            "| {:<name_width$} | {:>8} | {:>15} | {:>17} | {:>6} |",
-            "Contributeur", "Commits", "Lignes ajoutees", "Lignes supprimees", "%",
-            name_width = name_width
-        );
-        println!(
-            "|{:-<width$}|{:-<10}|{:-<17}|{:-<19}|{:-<8}|",
-            "", "", "", "", "",
-            width = name_width + 2
-        );
 
-        for (name, stat) in &sorted_stats {
-            let pct = if sort_by_lines {
-                let lines = stat.lines_added + stat.lines_deleted;
-                if total_lines > 0 { (lines as f64 / total_lines as f64) * 100.0 } else { 0.0 }
+        if args.bus_factor {
+            println!();
+            if args.bus_factor_by.as_deref() == Some("lines") {
+                // Blame reaches back to any commit, so resolve identities over the full history
+                let history = load_commits(&sources, &args, None, None, &progress).or_exit();
+                print_bus_factor(&sources, &args, &history, &full_mapping, &progress);
             } else {
-                if total_commits > 0 { (stat.commits as f64 / total_commits as f64) * 100.0 } else { 0.0 }
-            };
-            println!(
-                "| {:<name_width$} | {:>8} | {:>15} | {:>17} | {:>5.1}% |",
-                name,
-                stat.commits,
-                stat.lines_added,
-                stat.lines_deleted,
-                pct,
-                name_width = name_width
+                print_bus_factor(&sources, &args, &commits, &full_mapping, &progress);
+            }
+        }
+
+        if let Some(threshold) = args.fading {
+            let fading = find_fading(
+                &commits,
+                &full_mapping,
+                fading_end_date(&args.until),
+                args.fading_window,
+                args.fading_periods,
+                threshold,
             );
+            print_fading(&fading, args.fading_window);
         }
 
-        println!(
-            "|{:-<width$}|{:-<10}|{:-<17}|{:-<19}|{:-<8}|",
-            "", "", "", "", "",
-            width = name_width + 2
-        );
-        println!(
-            "| {:<name_width$} | {:>8} | {:>15} | {:>17} | {:>6} |",
-            "TOTAL",
-            total_commits,
-            total_added,
-            total_deleted,
-            "100%",
-            name_width = name_width
-        );
+        if args.graph && !graph_first {
+            println!();
+            print_graphs(&args, &commits, &sorted_stats, &author_mapping);
+        }
+
+        if args.punchcard {
+            println!();
+            print_punchcards(&args, &commits, &sorted_stats, &author_mapping);
+        }
+
+        // A Markdown comment, hidden wherever the table is rendered
+        if args.metadata_comment {
+            println!();
+            println!("[//]: # \"{}\"", ReportMetadata::new(&args).or_exit().summary().replace('"', "\\\""));
+        }
     }
 }
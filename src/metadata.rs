@@ -0,0 +1,154 @@
+use crate::error::Error;
+use crate::forge::strip_userinfo;
+use crate::repo::DEFAULT_RENAME_THRESHOLD;
+use crate::{analyzed_revisions, open_repos, requested_revisions, Args, DEFAULT_IMPORT_FILES};
+use chrono::Local;
+use serde::Serialize;
+
+/// Version of the libgit2 library this build reads repositories with.
+pub fn libgit2_version() -> String {
+    let (major, minor, patch) = git2::Version::get().libgit2_version();
+    format!("{}.{}.{}", major, minor, patch)
+}
+
+/// One analyzed revision and the commit it pointed to.
+#[derive(Serialize)]
+struct AnalyzedRevision {
+    repository: String,
+    /// URL of `origin` without credentials, when the repository has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remote: Option<String>,
+    revision: String,
+    commit: String,
+}
+
+/// How a report was made, embedded in JSON and HTML output and after an archived table so
+/// it can be checked or reproduced later: when, by which version, from which commits and
+/// with which options.
+#[derive(Serialize)]
+pub struct ReportMetadata {
+    generated_at: String,
+    tool_version: &'static str,
+    libgit2_version: String,
+    revisions: Vec<AnalyzedRevision>,
+    /// The command line as given, without credentials in URLs
+    arguments: Vec<String>,
+    /// Options that shape the numbers, once config files and profiles are applied
+    options: Vec<String>,
+}
+
+/// `value` as a single shell word, quoted only when needed.
+fn shell_word(value: &str) -> String {
+    if !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=@,+%".contains(c)) {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// The options in effect that change which commits count, who they are credited to and
+/// how, as command-line arguments. Output options are left out: they don't change the
+/// numbers. A new option that does belongs here.
+fn effective_options(args: &Args) -> Vec<String> {
+    let mut options = Vec::new();
+    let valued: [(&str, Vec<String>); 21] = [
+        // The positional REVSPEC is the same as --range
+        ("range", args.range.iter().chain(&args.revspec).cloned().collect()),
+        ("branch", args.branch.clone()),
+        ("since", args.since.iter().cloned().collect()),
+        ("until", args.until.iter().cloned().collect()),
+        ("as-of", args.as_of.iter().cloned().collect()),
+        ("path", args.path.clone()),
+        ("exclude-path", args.exclude_path.clone()),
+        ("ext", args.ext.clone()),
+        ("merge", args.merge.clone()),
+        ("credit", args.credit.iter().cloned().collect()),
+        ("trailer", args.trailer.clone()),
+        ("exclude-author", args.exclude_author.clone()),
+        ("authors-file", args.authors_file.iter().cloned().collect()),
+        ("issue-labels", args.issue_labels.iter().cloned().collect()),
+        ("label", args.label.clone()),
+        ("label-weight", args.label_weight.clone()),
+        ("find-copies", args.find_copies.iter().map(u16::to_string).collect()),
+        ("half-life", args.half_life.iter().map(f64::to_string).collect()),
+        ("top", args.top.iter().map(usize::to_string).collect()),
+        ("sort", args.sort.iter().cloned().collect()),
+        ("lines-metric", args.lines_metric.iter().cloned().collect()),
+    ];
+    for (name, values) in valued {
+        for value in values {
+            options.push(format!("--{}", name));
+            options.push(value);
+        }
+    }
+    if args.find_renames != DEFAULT_RENAME_THRESHOLD {
+        options.push("--find-renames".to_string());
+        options.push(args.find_renames.to_string());
+    }
+    if args.import_files != DEFAULT_IMPORT_FILES {
+        options.push("--import-files".to_string());
+        options.push(args.import_files.to_string());
+    }
+    let switches = [
+        ("no-merges", args.no_merges),
+        ("first-parent", args.first_parent),
+        ("no-mailmap", args.no_mailmap),
+        ("merge-fold-case", args.merge_fold_case),
+        ("exclude-bots", args.exclude_bots),
+        ("fold-external", args.fold_external),
+        ("exclude-initial", args.exclude_initial),
+        ("exclude-line-endings", args.exclude_line_endings),
+        ("flag-line-endings", args.flag_line_endings),
+        ("exclude-imports", args.exclude_imports),
+        ("ignore-whitespace", args.ignore_whitespace),
+        ("detect-format-only", args.detect_format_only),
+        ("include-generated", args.include_generated),
+        ("existing-files-only", args.existing_files_only),
+    ];
+    options.extend(switches.iter().filter(|(_, on)| *on).map(|(name, _)| format!("--{}", name)));
+    options
+}
+
+impl ReportMetadata {
+    /// Describes a report about to be generated with `args`.
+    pub fn new(args: &Args) -> Result<ReportMetadata, Error> {
+        let revs = requested_revisions(args);
+        let mut revisions = Vec::new();
+        for (_, repo) in open_repos(args, false)? {
+            revisions.extend(analyzed_revisions(&repo, &revs).into_iter().map(|revision| AnalyzedRevision {
+                repository: repo.name().to_string(),
+                remote: repo.remote_url("origin").map(|url| strip_userinfo(&url)),
+                commit: repo.tip_id(&revision).unwrap_or_default(),
+                revision,
+            }));
+        }
+        Ok(ReportMetadata {
+            generated_at: Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+            tool_version: env!("CARGO_PKG_VERSION"),
+            libgit2_version: libgit2_version(),
+            revisions,
+            arguments: std::env::args().skip(1).map(|arg| strip_userinfo(&arg)).collect(),
+            options: effective_options(args),
+        })
+    }
+
+    /// One line for people: "Generated 2025-01-31T10:00:00+01:00 by git-contrib-stats
+    /// 0.4.2 (libgit2 1.9.7) from app main@1a2b3c4d5e6f with --since 2025-01-01".
+    pub fn summary(&self) -> String {
+        let revisions: Vec<String> = self
+            .revisions
+            .iter()
+            .map(|r| format!("{} {}@{}", r.repository, r.revision, &r.commit[..r.commit.len().min(12)]))
+            .collect();
+        let options: Vec<String> = self.options.iter().map(|option| shell_word(option)).collect();
+        format!(
+            "Generated {} by git-contrib-stats {} (libgit2 {}) from {}{}{}",
+            self.generated_at,
+            self.tool_version,
+            self.libgit2_version,
+            revisions.join(", "),
+            if options.is_empty() { "" } else { " with " },
+            options.join(" ")
+        )
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{commits_by_contributor, CommitRecord};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Most contributors shown in the matrix, the biggest first; beyond that it stops fitting.
+const MAX_CONTRIBUTORS: usize = 12;
+
+/// Length in hours of the shared window suggested for each pair.
+const WINDOW_HOURS: usize = 3;
+
+/// How much each pair of contributors' active hours coincide, with `--hours-overlap`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HourOverlap {
+    contributors: Vec<String>,
+    /// Percent of active hours two contributors have in common, row by column
+    percent: Vec<Vec<u32>>,
+    /// First UTC hour of the `WINDOW_HOURS` the pair shares most, when they share any
+    window: Vec<Vec<Option<usize>>>,
+    window_hours: usize,
+}
+
+/// Share of a contributor's commits in each UTC hour. A commit counts half in its own
+/// hour and a quarter in each neighbor, since work goes on around the moment it lands.
+fn hour_profile(commits: &[&CommitRecord]) -> [f64; 24] {
+    let mut profile = [0.0; 24];
+    for commit in commits {
+        let hour = commit.utc_hour as usize % 24;
+        profile[(hour + 23) % 24] += 0.25;
+        profile[hour] += 0.5;
+        profile[(hour + 1) % 24] += 0.25;
+    }
+    let total: f64 = profile.iter().sum();
+    if total > 0.0 {
+        profile.iter_mut().for_each(|share| *share /= total);
+    }
+    profile
+}
+
+/// Compares the hour profiles of `names` (in that order, at most `MAX_CONTRIBUTORS`):
+/// two people overlap by the share of activity they have in common hour by hour, so
+/// 100% is the same working pattern and 0% never active at the same time.
+pub fn hour_overlap(commits: &[CommitRecord], author_mapping: &HashMap<String, String>, names: &[&String]) -> HourOverlap {
+    let by_contributor = commits_by_contributor(commits, author_mapping);
+    let names: Vec<&String> = names.iter().copied().take(MAX_CONTRIBUTORS).collect();
+    let profiles: Vec<[f64; 24]> = names
+        .iter()
+        .map(|name| hour_profile(by_contributor.get(name).map_or(&[][..], Vec::as_slice)))
+        .collect();
+
+    let shared = |a: &[f64; 24], b: &[f64; 24]| -> [f64; 24] { std::array::from_fn(|hour| a[hour].min(b[hour])) };
+    let mut percent = Vec::with_capacity(profiles.len());
+    let mut window = Vec::with_capacity(profiles.len());
+    for a in &profiles {
+        let (row_percent, row_window) = profiles
+            .iter()
+            .map(|b| {
+                let both = shared(a, b);
+                let best = (0..24)
+                    .map(|start| (start, (0..WINDOW_HOURS).map(|k| both[(start + k) % 24]).sum::<f64>()))
+                    .max_by(|x, y| x.1.total_cmp(&y.1).then_with(|| y.0.cmp(&x.0)))
+                    .filter(|(_, sum)| *sum > 0.0)
+                    .map(|(start, _)| start);
+                ((both.iter().sum::<f64>() * 100.0).round() as u32, best)
+            })
+            .unzip();
+        percent.push(row_percent);
+        window.push(row_window);
+    }
+    HourOverlap {
+        contributors: names.into_iter().cloned().collect(),
+        percent,
+        window,
+        window_hours: WINDOW_HOURS,
+    }
+}
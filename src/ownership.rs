@@ -0,0 +1,128 @@
+use crate::error::OrExit;
+use crate::repo::PathFilter;
+use crate::{canonical_name, Args, CommitRecord, Progress, Source};
+use std::collections::HashMap;
+
+/// An amount (surviving lines, commits) per contributor within one directory or the
+/// whole tree.
+#[derive(Default)]
+pub struct Owners {
+    lines: HashMap<String, u64>,
+    pub total: u64,
+}
+
+impl Owners {
+    pub fn add(&mut self, name: &str, lines: u64) {
+        *self.lines.entry(name.to_string()).or_insert(0) += lines;
+        self.total += lines;
+    }
+
+    /// Owners by descending amount, ties alphabetical.
+    pub fn ranked(&self) -> Vec<(&str, u64)> {
+        let mut ranked: Vec<(&str, u64)> = self.lines.iter().map(|(n, l)| (n.as_str(), *l)).collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        ranked
+    }
+
+    /// Leaves `name` out of the ranking but not the total, for the `--top` "Others" row,
+    /// which stands for many people.
+    pub fn hide(&mut self, name: &str) {
+        self.lines.remove(name);
+    }
+
+    pub fn percent(&self, lines: u64) -> f64 {
+        if self.total > 0 { lines as f64 / self.total as f64 * 100.0 } else { 0.0 }
+    }
+}
+
+/// Top-level directory of `path` ("." for files at the root), prefixed with the
+/// repository name when several repositories are analyzed.
+pub fn directory_of(path: &str, repo: &str, multi_repo: bool) -> String {
+    let dir = match path.split_once('/') {
+        Some((dir, _)) => format!("{}/", dir),
+        None => ".".to_string(),
+    };
+    if multi_repo { format!("{}:{}", repo, dir) } else { dir }
+}
+
+/// Blames the tree of every source, returning surviving lines per contributor overall
+/// and per top-level directory. Each line goes to the author of the commit that last
+/// changed it, resolved like the commit stats (`commits` must cover the full history):
+/// lines from commits left out by `--exclude-author`, `--authors-file` or `--no-merges`
+/// are not counted.
+pub fn surviving_lines(
+    sources: &[Source],
+    args: &Args,
+    commits: &[CommitRecord],
+    author_mapping: &HashMap<String, String>,
+    progress: &Progress,
+) -> (Owners, HashMap<String, Owners>) {
+    let authors: HashMap<&str, &String> = commits.iter().map(|c| (c.hash.as_str(), &c.author)).collect();
+    let paths = PathFilter::new(&args.path, &args.exclude_path, &args.ext, !args.include_generated).or_exit();
+    let multi_repo = sources.len() > 1;
+
+    let mut overall = Owners::default();
+    let mut directories: HashMap<String, Owners> = HashMap::new();
+    for source in sources {
+        let hunks = source
+            .repo
+            .blame_tree(&source.rev, &paths, args.first_parent, |done, total| progress.emit("blame", done, total, None))
+            .or_exit();
+        for (path, oid, lines) in hunks {
+            let Some(author) = authors.get(oid.to_string().as_str()) else {
+                continue;
+            };
+            let name = canonical_name(author, author_mapping);
+            overall.add(name, lines);
+            directories
+                .entry(directory_of(&path, source.repo.name(), multi_repo))
+                .or_default()
+                .add(name, lines);
+        }
+    }
+    (overall, directories)
+}
+
+/// Prints surviving lines per contributor, then per top-level directory.
+pub fn print_ownership_report(
+    sources: &[Source],
+    branch: &str,
+    args: &Args,
+    commits: &[CommitRecord],
+    author_mapping: &HashMap<String, String>,
+    progress: &Progress,
+) {
+    let (overall, directories) = surviving_lines(sources, args, commits, author_mapping, progress);
+    let ranked = overall.ranked();
+    let name_width = ranked.iter().map(|(n, _)| n.len()).max().unwrap_or(12).max(12);
+    let title = format!("Code ownership of {} (surviving lines)", branch);
+    println!("{}", title);
+    println!("{}", "─".repeat(title.chars().count()));
+    println!("| {:<name_width$} | {:>10} | {:>6} |", "Contributor", "Lines", "%", name_width = name_width);
+    println!("|{:-<width$}|{:-<12}|{:-<8}|", "", "", "", width = name_width + 2);
+    for (name, lines) in &ranked {
+        println!("| {:<name_width$} | {:>10} | {:>5.1}% |", name, lines, overall.percent(*lines), name_width = name_width);
+    }
+    println!("|{:-<width$}|{:-<12}|{:-<8}|", "", "", "", width = name_width + 2);
+    println!("| {:<name_width$} | {:>10} | {:>6} |", "TOTAL", overall.total, "100%", name_width = name_width);
+
+    let mut directories: Vec<(String, Owners)> = directories.into_iter().collect();
+    directories.sort_by(|a, b| b.1.total.cmp(&a.1.total).then_with(|| a.0.cmp(&b.0)));
+    let dir_width = directories.iter().map(|(d, _)| d.len()).max().unwrap_or(9).max(9);
+
+    println!();
+    println!("| {:<dir_width$} | {:>10} | Top owners", "Directory", "Lines", dir_width = dir_width);
+    println!("|{:-<width$}|{:-<12}|{:-<12}", "", "", "", width = dir_width + 2);
+    for (dir, owners) in &directories {
+        let ranked = owners.ranked();
+        let mut top: Vec<String> = ranked
+            .iter()
+            .take(3)
+            .map(|(name, lines)| format!("{} {:.0}%", name, owners.percent(*lines)))
+            .collect();
+        if ranked.len() > 3 {
+            top.push(format!("+{} more", ranked.len() - 3));
+        }
+        println!("| {:<dir_width$} | {:>10} | {}", dir, owners.total, top.join(", "), dir_width = dir_width);
+    }
+}
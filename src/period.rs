@@ -0,0 +1,170 @@
+use crate::error::Error;
+use crate::repo::parse_date;
+use crate::{
+    bucket_start, contributor_stats, display_width, fading_end_date, handle_formatting, handle_imports, handle_initial_commits,
+    handle_line_endings, load_commits, open_sources, pad_display, requested_revisions, week_start_day, Args, ContributorStats,
+    LinesMetric, Progress,
+};
+use chrono::{Duration, Local, NaiveDate, TimeZone};
+use std::collections::HashMap;
+
+/// `--period` presets.
+pub const PERIODS: [&str; 8] = [
+    "this-week",
+    "last-week",
+    "this-month",
+    "last-month",
+    "this-quarter",
+    "last-quarter",
+    "this-year",
+    "last-year",
+];
+
+/// First and last day of the `--period` preset, then of the period before it. A period
+/// still in progress is compared with the same number of days at the start of the
+/// previous one.
+fn period_range(args: &Args) -> Option<((NaiveDate, NaiveDate), (NaiveDate, NaiveDate))> {
+    let (which, unit) = args.period.as_deref()?.split_once('-')?;
+    let week_start = week_start_day(args);
+    let today = fading_end_date(&args.as_of);
+    let current = bucket_start(today, unit, week_start);
+    let (start, end) = match which {
+        "last" => (bucket_start(current - Duration::days(1), unit, week_start), current - Duration::days(1)),
+        _ => (current, today),
+    };
+    let previous_start = bucket_start(start - Duration::days(1), unit, week_start);
+    let previous_end = match which {
+        "last" => start - Duration::days(1),
+        _ => (previous_start + (end - start)).min(start - Duration::days(1)),
+    };
+    Some(((start, end), (previous_start, previous_end)))
+}
+
+/// First and last day of the period before the analyzed one: the one before `--period`,
+/// or as many days just before `--since` (up to `--until` or today).
+fn previous_range(args: &Args) -> Option<(NaiveDate, NaiveDate)> {
+    if let Some((_, previous)) = period_range(args) {
+        return Some(previous);
+    }
+    let since = parse_date(args.since.as_deref()?, false)?;
+    let start = Local.timestamp_opt(since, 0).single()?.date_naive();
+    let end = fading_end_date(&args.until);
+    let days = (end - start).num_days().max(0);
+    Some((start - Duration::days(days + 1), start - Duration::days(1)))
+}
+
+/// Replaces `--since`/`--until` with the `--period` preset's dates. A digest covers this
+/// week unless told otherwise.
+pub fn apply_period(args: &mut Args) {
+    if args.format.as_deref() == Some("digest") && args.period.is_none() && args.since.is_none() && args.until.is_none() {
+        args.period = Some("this-week".to_string());
+    }
+    if let Some(((start, end), _)) = period_range(args) {
+        args.since = Some(start.to_string());
+        args.until = Some(end.to_string());
+    }
+}
+
+/// Totals of the period before `--period`, for `--with-previous`.
+pub struct PreviousPeriod {
+    pub since: String,
+    pub until: String,
+    pub stats: HashMap<String, ContributorStats>,
+}
+
+/// Analyzes the period before the current one the same way, when `--with-previous` or
+/// `--format digest` asks for it.
+pub fn previous_period(
+    args: &Args,
+    author_mapping: &HashMap<String, String>,
+    progress: &Progress,
+) -> Result<Option<PreviousPeriod>, Error> {
+    if !args.with_previous && args.format.as_deref() != Some("digest") {
+        return Ok(None);
+    }
+    let Some((start, end)) = previous_range(args) else {
+        return Ok(None);
+    };
+    let (since, until) = (start.to_string(), end.to_string());
+    let sources = open_sources(args, requested_revisions(args).first().map(String::as_str))?;
+    let mut commits = load_commits(&sources, args, Some(since.clone()), Some(until.clone()), progress)?;
+    handle_initial_commits(&mut commits, args);
+    handle_line_endings(&mut commits, args);
+    handle_formatting(&mut commits, args);
+    handle_imports(&mut commits, args);
+    let stats = contributor_stats(&commits, author_mapping);
+    Ok(Some(PreviousPeriod { since, until, stats }))
+}
+
+/// "+25%", "-10%", "new" or "=".
+pub fn change(now: i64, before: i64) -> String {
+    match (now, before) {
+        (n, b) if n == b => "=".to_string(),
+        (_, 0) => "new".to_string(),
+        (n, b) => format!("{:+.0}%", (n - b) as f64 / b.abs() as f64 * 100.0),
+    }
+}
+
+/// Commits and lines per contributor next to the previous period's, with the change.
+pub fn print_comparison(sorted_stats: &[(&String, &ContributorStats)], previous: &PreviousPeriod, metric: LinesMetric) {
+    println!();
+    println!("Compared with {} to {}", previous.since, previous.until);
+    println!();
+    let name_width = sorted_stats
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(previous.stats.keys())
+        .map(|name| display_width(name))
+        .max()
+        .unwrap_or(0)
+        .max(12);
+    println!(
+        "| {} | {:>8} | {:>8} | {:>7} | {:>10} | {:>10} | {:>7} |",
+        pad_display("Contributor", name_width),
+        "Commits",
+        "Previous",
+        "Change",
+        "Lines",
+        "Previous",
+        "Change"
+    );
+    println!("|{:-<width$}|{:-<10}|{:-<10}|{:-<9}|{:-<12}|{:-<12}|{:-<9}|", "", "", "", "", "", "", "", width = name_width + 2);
+    let empty = ContributorStats::default();
+    let row = |name: &str, now: &ContributorStats, before: &ContributorStats| {
+        let (commits, lines) = (now.commits as i64, metric.of(now));
+        let (previous_commits, previous_lines) = (before.commits as i64, metric.of(before));
+        println!(
+            "| {} | {:>8} | {:>8} | {:>7} | {:>10} | {:>10} | {:>7} |",
+            pad_display(name, name_width),
+            commits,
+            previous_commits,
+            change(commits, previous_commits),
+            lines,
+            previous_lines,
+            change(lines, previous_lines)
+        );
+    };
+    for (name, stats) in sorted_stats {
+        row(name, stats, previous.stats.get(*name).unwrap_or(&empty));
+    }
+    // Contributors who were active before but not in this period
+    let mut gone: Vec<(&String, &ContributorStats)> = previous
+        .stats
+        .iter()
+        .filter(|(name, _)| !sorted_stats.iter().any(|(current, _)| current == name))
+        .collect();
+    gone.sort_by(|a, b| b.1.commits.cmp(&a.1.commits).then_with(|| a.0.cmp(b.0)));
+    for (name, stats) in gone {
+        row(name, &empty, stats);
+    }
+    let sum = |stats: &mut dyn Iterator<Item = &ContributorStats>| {
+        stats.fold(ContributorStats::default(), |mut total, s| {
+            total.commits += s.commits;
+            total.lines_added += s.lines_added;
+            total.lines_deleted += s.lines_deleted;
+            total
+        })
+    };
+    println!("|{:-<width$}|{:-<10}|{:-<10}|{:-<9}|{:-<12}|{:-<12}|{:-<9}|", "", "", "", "", "", "", "", width = name_width + 2);
+    row("TOTAL", &sum(&mut sorted_stats.iter().map(|(_, s)| *s)), &sum(&mut previous.stats.values()));
+}
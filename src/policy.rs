@@ -0,0 +1,98 @@
+use crate::error::{Error, OrExit};
+use crate::repo::CommitRecord;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Rules checked by `--policy`, read from a JSON file. Every rule is optional.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    /// Author email domains allowed; subdomains are accepted too
+    #[serde(default)]
+    allowed_domains: Vec<String>,
+    /// Require a `Signed-off-by:` trailer on every non-merge commit
+    #[serde(default)]
+    require_signoff: bool,
+    /// Largest allowed commit, in lines added + deleted
+    max_lines: Option<u64>,
+    /// Largest allowed commit, in files changed
+    max_files: Option<u64>,
+}
+
+impl Policy {
+    pub fn load(path: &str) -> Policy {
+        let content = std::fs::read_to_string(path)
+            .map_err(|source| Error::Read { path: path.to_string(), source })
+            .or_exit();
+        serde_json::from_str(&content)
+            .map_err(|e| Error::InvalidFile {
+                kind: "policy file",
+                path: path.to_string(),
+                message: e.to_string(),
+            })
+            .or_exit()
+    }
+
+    /// Human-readable descriptions of every rule `commit` breaks.
+    fn violations(&self, commit: &CommitRecord) -> Vec<String> {
+        let mut found = Vec::new();
+        if !self.allowed_domains.is_empty() {
+            let domain = commit.email.rsplit_once('@').map(|(_, d)| d).unwrap_or("");
+            let allowed = self.allowed_domains.iter().any(|allowed| {
+                let allowed = allowed.trim_start_matches('@').to_lowercase();
+                domain == allowed || domain.ends_with(&format!(".{}", allowed))
+            });
+            if !allowed {
+                found.push(format!("email domain not allowed ({})", commit.email));
+            }
+        }
+        if self.require_signoff
+            && !commit.is_merge
+            && !commit.trailers.iter().any(|(key, _)| key.eq_ignore_ascii_case("Signed-off-by"))
+        {
+            found.push("missing Signed-off-by".to_string());
+        }
+        let lines = commit.lines_added + commit.lines_deleted;
+        if let Some(max) = self.max_lines.filter(|max| lines > *max) {
+            found.push(format!("{} lines changed (max {})", lines, max));
+        }
+        if let Some(max) = self.max_files.filter(|max| commit.files_changed > *max) {
+            found.push(format!("{} files changed (max {})", commit.files_changed, max));
+        }
+        found
+    }
+}
+
+/// Prints the commits breaking `policy`, grouped by contributor, and returns how many
+/// commits were in violation.
+pub fn print_policy_report(policy: &Policy, commits: &[CommitRecord]) -> usize {
+    let mut by_author: BTreeMap<&str, Vec<(&CommitRecord, Vec<String>)>> = BTreeMap::new();
+    for commit in commits {
+        let violations = policy.violations(commit);
+        if !violations.is_empty() {
+            by_author.entry(&commit.author).or_default().push((commit, violations));
+        }
+    }
+
+    println!("Policy check");
+    println!("────────────");
+    let violating: usize = by_author.values().map(Vec::len).sum();
+    if violating == 0 {
+        println!("All {} commits comply", commits.len());
+        return 0;
+    }
+    for (author, found) in &by_author {
+        println!("{} ({} commits)", author, found.len());
+        for (commit, violations) in found {
+            println!(
+                "  {} {}  {}",
+                &commit.hash[..commit.hash.len().min(10)],
+                commit.date,
+                violations.join("; ")
+            );
+        }
+    }
+    println!();
+    println!("{} of {} commits violate the policy", violating, commits.len());
+    violating
+}
@@ -0,0 +1,1098 @@
+use crate::error::{Error, OrExit};
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use git2::{
+    AttrCheckFlags, AttrValue, BlameOptions, BranchType, Commit, Diff, DiffFindOptions, DiffOptions, Mailmap, ObjectType, Oid, Pathspec, PathspecFlags, Repository, RevparseMode,
+    Revspec, Signature, Sort, Time, Tree, TreeWalkMode, TreeWalkResult,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+pub struct CommitRecord {
+    pub hash: String,
+    /// Name of the repository the commit was read from
+    pub repo: String,
+    pub is_root: bool,
+    pub is_merge: bool,
+    /// Committer timestamp; imports rewrite it to the time of the import
+    pub committer_time: i64,
+    pub author: String,
+    pub email: String,
+    /// Committer, resolved through `.mailmap` like the author
+    pub committer: String,
+    pub committer_email: String,
+    /// Who committed the commit on its author's behalf, with `--credit both`
+    pub applied_by: Option<String>,
+    pub date: String,
+    /// Hour of the author date (0-23), in the author's timezone like `date`
+    pub hour: u32,
+    /// Hour of the author date (0-23) in UTC, for comparing people across timezones
+    pub utc_hour: u32,
+    /// `(key, value)` trailer pairs from the commit message
+    pub trailers: Vec<(String, String)>,
+    /// Issue numbers referenced as `#123` in the commit message
+    pub issues: Vec<u64>,
+    /// Weight from `--label-weight`, when label weighting is enabled
+    pub weight: Option<u64>,
+    /// Weight from `--half-life`, 1 at the end of the period and halving every half-life before
+    pub recency: Option<f64>,
+    /// Functions and sections changed, with `--functions`
+    pub functions: Option<u64>,
+    /// Files touched (after path filters); 0 for empty commits and, by default, merges
+    pub files_changed: u64,
+    pub lines_added: u64,
+    pub lines_deleted: u64,
+    /// Lines added + deleted per lowercased file extension ("" for none)
+    pub lines_by_ext: Vec<(String, u64)>,
+    /// Lines added + deleted that only change line endings (CRLF/LF), when measured (see
+    /// `Repo::measure_line_endings`)
+    pub eol_lines: u64,
+    /// Lines added + deleted in files the commit only reformats (see `Repo::format_lines`),
+    /// when measured (see `Repo::measure_format_only`)
+    pub format_lines: u64,
+}
+
+/// Per-commit facts as read from the object database, before identity resolution.
+/// This is what the stats cache stores, keyed by commit hash.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RawCommit {
+    pub is_root: bool,
+    pub is_merge: bool,
+    /// Committer timestamp, which `--since`/`--until` filter on
+    pub committer_time: i64,
+    pub author_name: String,
+    pub author_email: String,
+    pub committer_name: String,
+    pub committer_email: String,
+    pub time: i64,
+    pub offset_minutes: i32,
+    pub trailers: Vec<(String, String)>,
+    pub issues: Vec<u64>,
+    pub files_changed: u64,
+    pub lines_added: u64,
+    pub lines_deleted: u64,
+    pub lines_by_ext: Vec<(String, u64)>,
+    pub eol_lines: u64,
+    pub format_lines: u64,
+}
+
+/// Restricts line statistics and commits to files matching `--path` and not `--exclude-path`,
+/// and with one of the `--ext` extensions when given.
+pub struct PathFilter {
+    include: Option<Pathspec>,
+    exclude: Option<Pathspec>,
+    extensions: Vec<String>,
+    /// Leave out generated and vendored files (see `Repo::is_generated`)
+    skip_generated: bool,
+}
+
+impl PathFilter {
+    pub fn new(include: &[String], exclude: &[String], extensions: &[String], skip_generated: bool) -> Result<PathFilter, Error> {
+        let compile = |specs: &[String]| {
+            (!specs.is_empty())
+                .then(|| Pathspec::new(specs.iter()).map_err(|e| Error::Usage(format!("invalid path pattern: {}", e.message()))))
+                .transpose()
+        };
+        Ok(PathFilter {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+            extensions: extensions
+                .iter()
+                .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+                .collect(),
+            skip_generated,
+        })
+    }
+
+    fn is_active(&self) -> bool {
+        self.include.is_some() || self.exclude.is_some() || !self.extensions.is_empty()
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        let hit = |spec: &Pathspec| spec.matches_path(path, PathspecFlags::DEFAULT);
+        let extension_ok = self.extensions.is_empty()
+            || self.extensions.contains(&file_extension(&path.to_string_lossy()));
+        extension_ok && self.include.as_ref().is_none_or(hit) && !self.exclude.as_ref().is_some_and(hit)
+    }
+}
+
+/// Names repositories that share a directory name (`-C a/api -C b/api`) after as few of
+/// their parent directories as tell them apart from the others, e.g. `a/api` and `b/api`,
+/// so per-repository results don't merge.
+pub fn disambiguate_names(repos: &mut [Repo]) {
+    let parents: Vec<Vec<String>> = repos.iter().map(Repo::parent_dirs).collect();
+    let names: Vec<String> = repos.iter().map(|repo| repo.name.clone()).collect();
+    let suffix = |dirs: &[String], depth: usize| dirs[dirs.len().saturating_sub(depth)..].join("/");
+    for (i, repo) in repos.iter_mut().enumerate() {
+        // The same repository given twice keeps its name
+        let clashes: Vec<usize> = (0..names.len()).filter(|&j| names[j] == names[i] && parents[j] != parents[i]).collect();
+        if clashes.is_empty() {
+            continue;
+        }
+        let deepest = clashes.iter().map(|&j| parents[j].len()).chain([parents[i].len()]).max().unwrap_or(0);
+        let depth = (1..=deepest)
+            .find(|&depth| clashes.iter().all(|&j| suffix(&parents[j], depth) != suffix(&parents[i], depth)))
+            .unwrap_or(deepest);
+        // A repository right under the filesystem root has no parent to add
+        let prefix = suffix(&parents[i], depth);
+        if !prefix.is_empty() {
+            repo.name = format!("{}/{}", prefix, names[i]);
+        }
+    }
+}
+
+/// Lowercased extension of the file name in `path`, or "" when it has none.
+/// Dotfiles such as `.gitignore` have no extension.
+pub fn file_extension(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => ext.to_lowercase(),
+        _ => String::new(),
+    }
+}
+
+/// Gitignore-style patterns, as in `.statsignore`: `*`, `?` and `**` globs, `!` to negate,
+/// a leading or inner `/` anchoring to the repository root and a trailing `/` for directories.
+/// Character classes are not supported. The last matching pattern wins.
+#[derive(Clone)]
+struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Clone)]
+struct IgnoreRule {
+    pattern: String,
+    negated: bool,
+    anchored: bool,
+    dir_only: bool,
+}
+
+impl IgnoreRules {
+    fn parse(text: &str) -> IgnoreRules {
+        let rules = text
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line.strip_prefix('\\').unwrap_or(line)),
+                };
+                let dir_only = line.ends_with('/');
+                let line = line.trim_end_matches('/');
+                IgnoreRule {
+                    anchored: line.contains('/'),
+                    pattern: line.trim_start_matches('/').to_string(),
+                    negated,
+                    dir_only,
+                }
+            })
+            .collect();
+        IgnoreRules { rules }
+    }
+
+    fn is_ignored(&self, path: &str) -> bool {
+        // A pattern matching a directory ignores everything below it
+        let dirs = path.match_indices('/').map(|(i, _)| &path[..i]);
+        let mut ignored = false;
+        for rule in &self.rules {
+            let hit = |candidate: &str| {
+                let name = if rule.anchored { candidate } else { candidate.rsplit('/').next().unwrap_or(candidate) };
+                glob(rule.pattern.as_bytes(), name.as_bytes())
+            };
+            let matched = dirs.clone().any(hit) || (!rule.dir_only && hit(path));
+            if matched {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+/// Matches a path against a glob where `*` and `?` stay within one path component
+/// and `**` spans any number of them.
+fn glob(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => {
+            let after_slash = rest.strip_prefix(b"/").unwrap_or(rest);
+            (0..=text.len()).any(|i| {
+                glob(rest, &text[i..]) || ((i == 0 || text[i - 1] == b'/') && glob(after_slash, &text[i..]))
+            })
+        }
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob(rest, &text[i..])),
+        [b'?', rest @ ..] => matches!(text, [c, ..] if *c != b'/') && glob(rest, &text[1..]),
+        [c, rest @ ..] => text.first() == Some(c) && glob(rest, &text[1..]),
+    }
+}
+
+/// History traversal settings shared by all queries.
+pub struct WalkOptions {
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub no_merges: bool,
+    pub first_parent: bool,
+}
+
+impl WalkOptions {
+    /// Parses the date bounds once for filtering many commits.
+    pub fn filter(&self) -> WalkFilter {
+        WalkFilter {
+            since: self.since.as_deref().map(|s| parse_option_date("--since", s, false)),
+            until: self.until.as_deref().map(|u| parse_option_date("--until", u, true)),
+            no_merges: self.no_merges,
+        }
+    }
+}
+
+/// Date and merge filters applied to walked commits, from live objects or cached stats.
+pub struct WalkFilter {
+    since: Option<i64>,
+    until: Option<i64>,
+    no_merges: bool,
+}
+
+impl WalkFilter {
+    pub fn accepts(&self, committer_time: i64, is_merge: bool) -> bool {
+        !(self.no_merges && is_merge)
+            && self.since.is_none_or(|s| committer_time >= s)
+            && self.until.is_none_or(|u| committer_time <= u)
+    }
+}
+
+/// Similarity in percent from which git and `Repo` pair a deleted and an added file as
+/// a rename.
+pub const DEFAULT_RENAME_THRESHOLD: u16 = 50;
+
+/// A `Repo` to reopen on another thread, for `raw_commit` (no mailmap: identities are
+/// resolved by the thread that records commits).
+pub struct DiffWorker {
+    path: PathBuf,
+    name: String,
+    statsignore: IgnoreRules,
+    existing: Option<HashSet<String>>,
+    ignore_whitespace: bool,
+    line_endings: bool,
+    format_only: bool,
+    rename_threshold: u16,
+    copy_threshold: Option<u16>,
+}
+
+impl DiffWorker {
+    pub fn open(&self) -> Result<Repo, Error> {
+        Ok(Repo {
+            repo: Repository::open(&self.path)?,
+            mailmap: None,
+            name: self.name.clone(),
+            statsignore: self.statsignore.clone(),
+            existing: self.existing.clone(),
+            ignore_whitespace: self.ignore_whitespace,
+            line_endings: self.line_endings,
+            format_only: self.format_only,
+            rename_threshold: self.rename_threshold,
+            copy_threshold: self.copy_threshold,
+        })
+    }
+}
+
+/// In-process access to the repository, replacing the `git` binary.
+pub struct Repo {
+    repo: Repository,
+    mailmap: Option<Mailmap>,
+    name: String,
+    statsignore: IgnoreRules,
+    /// Paths in the analyzed tip's tree, with `--existing-files-only`
+    existing: Option<HashSet<String>>,
+    /// Diff and blame ignoring whitespace, with `--ignore-whitespace`
+    ignore_whitespace: bool,
+    /// Measure line-ending conversions, with `--exclude-line-endings` or `--flag-line-endings`
+    line_endings: bool,
+    /// Measure reformatted lines, with `--detect-format-only`
+    format_only: bool,
+    /// Similarity (percent) from which a deleted and an added file make a rename
+    rename_threshold: u16,
+    /// Similarity from which an added file is a copy of one the commit modifies, with `--find-copies`
+    copy_threshold: Option<u16>,
+}
+
+impl Repo {
+    /// Opens the repository containing `path`, exiting when there is none (see `open`).
+    pub fn discover(path: &Path, use_mailmap: bool) -> Repo {
+        Repo::open(path, use_mailmap).or_exit()
+    }
+
+    /// Opens the repository containing `path`. Author identities are resolved through
+    /// `.mailmap` (and `mailmap.file`/`mailmap.blob`) unless disabled.
+    pub fn open(path: &Path, use_mailmap: bool) -> Result<Repo, Error> {
+        let repo = Repository::discover(path).map_err(|e| match e.code() {
+            git2::ErrorCode::NotFound => Error::NotARepository { path: path.display().to_string() },
+            _ => Error::Git(e),
+        })?;
+        let mailmap = if use_mailmap { repo.mailmap().ok() } else { None };
+        let root = repo.workdir().unwrap_or_else(|| repo.path());
+        let name = root
+            .canonicalize()
+            .unwrap_or_else(|_| root.to_path_buf())
+            .file_name()
+            .map(|n| n.to_string_lossy().trim_end_matches(".git").to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        let statsignore = IgnoreRules::parse(&fs::read_to_string(root.join(".statsignore")).unwrap_or_default());
+        Ok(Repo {
+            repo,
+            mailmap,
+            name,
+            statsignore,
+            existing: None,
+            ignore_whitespace: false,
+            line_endings: false,
+            format_only: false,
+            rename_threshold: DEFAULT_RENAME_THRESHOLD,
+            copy_threshold: None,
+        })
+    }
+
+    /// What a diff worker thread needs to open its own handle on this repository, since
+    /// a `Repo` can't be shared between threads.
+    pub fn diff_worker(&self) -> DiffWorker {
+        DiffWorker {
+            path: self.repo.workdir().unwrap_or_else(|| self.repo.path()).to_path_buf(),
+            name: self.name.clone(),
+            statsignore: self.statsignore.clone(),
+            existing: self.existing.clone(),
+            ignore_whitespace: self.ignore_whitespace,
+            line_endings: self.line_endings,
+            format_only: self.format_only,
+            rename_threshold: self.rename_threshold,
+            copy_threshold: self.copy_threshold,
+        }
+    }
+
+    fn unknown_revision(&self, revspec: &str) -> Error {
+        Error::UnknownRevision {
+            rev: revspec.to_string(),
+            repo: self.name.clone(),
+        }
+    }
+
+    fn revparse(&self, revspec: &str) -> Result<Revspec<'_>, Error> {
+        self.repo.revparse(revspec).map_err(|_| self.unknown_revision(revspec))
+    }
+
+    /// The commit at the tip of `revspec` (the right side of a range).
+    fn tip(&self, revspec: &str) -> Result<Commit<'_>, Error> {
+        let spec = self.revparse(revspec)?;
+        spec.to()
+            .or(spec.from())
+            .and_then(|obj| obj.peel_to_commit().ok())
+            .ok_or_else(|| self.unknown_revision(revspec))
+    }
+
+    /// Only counts changes to files that exist in the tree at the tip of `revspec` from now on.
+    pub fn restrict_to_tree(&mut self, revspec: &str) -> Result<(), Error> {
+        let mut files = HashSet::new();
+        self.tip(revspec)?.tree()?.walk(TreeWalkMode::PreOrder, |dir, entry| {
+            if entry.kind() == Some(ObjectType::Blob) {
+                files.insert(format!("{}{}", dir, entry.name().unwrap_or("")));
+            }
+            TreeWalkResult::Ok
+        })?;
+        self.existing = Some(files);
+        Ok(())
+    }
+
+    /// Diffs and blames ignoring whitespace from now on, like `git diff -w`, so reindented
+    /// or rewrapped lines aren't counted as changed.
+    pub fn ignore_whitespace(&mut self) {
+        self.ignore_whitespace = true;
+    }
+
+    /// Measures how many changed lines of each commit only convert line endings from now
+    /// on (`RawCommit::eol_lines`), which takes a second diff of commits that both add and
+    /// delete lines.
+    pub fn measure_line_endings(&mut self) {
+        self.line_endings = true;
+    }
+
+    /// Measures how many changed lines of each commit only reformat code from now on
+    /// (`RawCommit::format_lines`), which takes a patch of commits that both add and delete
+    /// lines.
+    pub fn measure_format_only(&mut self) {
+        self.format_only = true;
+    }
+
+    /// Options for every diff: whitespace is ignored with `--ignore-whitespace`.
+    fn diff_options(&self) -> DiffOptions {
+        let mut options = DiffOptions::new();
+        options.ignore_whitespace(self.ignore_whitespace);
+        options
+    }
+
+    /// Detects renames from `rename_threshold` percent similarity from now on (50 by
+    /// default, like `git diff -M`), and with `copy_threshold` copies of files the same
+    /// commit modifies (like `-C`), so moved or duplicated files count their edits only.
+    pub fn detect_similar(&mut self, rename_threshold: u16, copy_threshold: Option<u16>) {
+        self.rename_threshold = rename_threshold;
+        self.copy_threshold = copy_threshold;
+    }
+
+    /// Rename and copy detection applied to every diff.
+    fn find_options(&self) -> DiffFindOptions {
+        let mut options = DiffFindOptions::new();
+        options.renames(true).rename_threshold(self.rename_threshold);
+        if let Some(threshold) = self.copy_threshold {
+            options.copies(true).copy_threshold(threshold);
+        }
+        options
+    }
+
+    /// Whether `path` is marked `linguist-generated` or `linguist-vendored` in `.gitattributes`,
+    /// or listed in `.statsignore`. Such files are left out of line statistics.
+    fn is_generated(&self, path: &Path) -> bool {
+        let marked = |attr: &str| {
+            let value = self.repo.get_attr(path, attr, AttrCheckFlags::FILE_THEN_INDEX).ok().flatten();
+            matches!(AttrValue::from_string(value), AttrValue::True | AttrValue::String("true"))
+        };
+        marked("linguist-generated") || marked("linguist-vendored") || self.statsignore.is_ignored(&path.to_string_lossy())
+    }
+
+    /// Whether changes to `path` count under `paths`.
+    fn counts(&self, path: &Path, paths: &PathFilter) -> bool {
+        paths.matches(path)
+            && !(paths.skip_generated && self.is_generated(path))
+            && self.existing.as_ref().is_none_or(|files| files.contains(path.to_string_lossy().as_ref()))
+    }
+
+    /// Identifies the `.gitattributes` and `.statsignore` rules in effect, so cached line
+    /// statistics are recomputed when they change.
+    pub fn ignore_rules_digest(&self) -> u64 {
+        let root = self.repo.workdir().unwrap_or_else(|| self.repo.path());
+        let mut hasher = DefaultHasher::new();
+        for file in [root.join(".gitattributes"), root.join(".statsignore"), self.repo.commondir().join("info/attributes")] {
+            fs::read_to_string(file).unwrap_or_default().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// URL of the remote `name`, if configured.
+    pub fn remote_url(&self, name: &str) -> Option<String> {
+        let remote = self.repo.find_remote(name).ok()?;
+        remote.url().ok().map(String::from)
+    }
+
+    /// Directory name of the repository, used to label it in multi-repo reports, with
+    /// parent directories when another one shares it (see `disambiguate_names`).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Directories above the repository's root, outermost first.
+    fn parent_dirs(&self) -> Vec<String> {
+        let root = self.repo.workdir().unwrap_or_else(|| self.repo.path());
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let parent = root.parent().unwrap_or(Path::new(""));
+        parent
+            .components()
+            .filter_map(|c| match c {
+                std::path::Component::Normal(dir) => Some(dir.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The checked-out branch, or `None` when HEAD is detached or unborn.
+    pub fn current_branch(&self) -> Option<String> {
+        let head = self.repo.head().ok()?;
+        if head.is_branch() {
+            head.shorthand().ok().map(|s| s.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Picks a ref to analyze when no branch is checked out (typical of CI checkouts):
+    /// the remote's default branch via `origin/HEAD`, then `main`/`master` locally or on
+    /// `origin`, then the detached HEAD itself. Returns the ref and where it came from.
+    pub fn fallback_branch(&self) -> Option<(String, &'static str)> {
+        if let Ok(reference) = self.repo.find_reference("refs/remotes/origin/HEAD") {
+            if let Some(target) = reference.symbolic_target().ok().flatten() {
+                let name = target.trim_start_matches("refs/remotes/").to_string();
+                if self.resolves(&name) {
+                    return Some((name, "origin/HEAD"));
+                }
+            }
+        }
+        for name in ["main", "master", "origin/main", "origin/master"] {
+            if self.resolves(name) {
+                return Some((name.to_string(), "default branch name"));
+            }
+        }
+        self.resolves("HEAD").then(|| ("HEAD".to_string(), "detached HEAD"))
+    }
+
+    /// Local branches, then remote-tracking branches without a local one of the same name,
+    /// each sorted by name. Remote `HEAD` aliases are skipped.
+    pub fn branches(&self) -> Vec<String> {
+        let names = |kind: BranchType| -> Vec<String> {
+            let mut names: Vec<String> = self
+                .repo
+                .branches(Some(kind))
+                .map(|branches| {
+                    branches
+                        .filter_map(|branch| branch.ok())
+                        .filter_map(|(branch, _)| branch.name().ok().flatten().map(String::from))
+                        .filter(|name| !name.ends_with("/HEAD"))
+                        .collect()
+                })
+                .unwrap_or_default();
+            names.sort();
+            names
+        };
+        let local = names(BranchType::Local);
+        let remote: Vec<String> = names(BranchType::Remote)
+            .into_iter()
+            .filter(|name| !name.split_once('/').is_some_and(|(_, short)| local.iter().any(|l| l == short)))
+            .collect();
+        local.into_iter().chain(remote).collect()
+    }
+
+    fn resolves(&self, revspec: &str) -> bool {
+        self.repo
+            .revparse_single(revspec)
+            .and_then(|obj| obj.peel_to_commit())
+            .is_ok()
+    }
+
+    /// Whether `revspec` (a ref or range) resolves to commits, so it can be analyzed
+    /// without exiting on an unknown revision.
+    pub fn has_revision(&self, revspec: &str) -> bool {
+        self.repo
+            .revparse(revspec)
+            .is_ok_and(|spec| [spec.from(), spec.to()].into_iter().flatten().all(|obj| obj.peel_to_commit().is_ok()))
+    }
+
+    /// Id of the commit at the tip of `revspec`, or `None` when it doesn't resolve.
+    pub fn tip_id(&self, revspec: &str) -> Option<String> {
+        let spec = self.repo.revparse(revspec).ok()?;
+        let tip = spec.to().or(spec.from())?.peel_to_commit().ok()?;
+        Some(tip.id().to_string())
+    }
+
+    /// The newest first-parent ancestor of `revspec` committed at or before `time`, i.e. what
+    /// the branch pointed to then. Commits added or rewritten (rebased) later are left out.
+    pub fn snapshot(&self, revspec: &str, time: i64) -> Option<Oid> {
+        let mut commit = self.repo.revparse_single(revspec).ok()?.peel_to_commit().ok()?;
+        loop {
+            let committed = commit.committer().when().seconds();
+            if committed <= time {
+                return Some(commit.id());
+            }
+            commit = commit.parent(0).ok()?;
+        }
+    }
+
+    /// Every commit selected by `revspec` (a ref, `A..B` or `A...B`), newest first,
+    /// before date and merge filters.
+    pub fn walk(&self, revspec: &str, options: &WalkOptions) -> Result<Vec<Oid>, Error> {
+        let spec = self.revparse(revspec)?;
+        let commit_of = |obj: Option<&git2::Object>| {
+            obj.and_then(|obj| obj.peel_to_commit().ok())
+                .map(|commit| commit.id())
+                .ok_or_else(|| self.unknown_revision(revspec))
+        };
+
+        let mut walk = self.repo.revwalk()?;
+        walk.set_sorting(Sort::TIME)?;
+        let mode = spec.mode();
+        if mode.contains(RevparseMode::SINGLE) {
+            walk.push(commit_of(spec.from())?)?;
+        } else {
+            let (from, to) = (commit_of(spec.from())?, commit_of(spec.to())?);
+            walk.push(to)?;
+            if mode.contains(RevparseMode::MERGE_BASE) {
+                // A...B: commits on either side, excluding their common history
+                walk.push(from)?;
+                if let Ok(base) = self.repo.merge_base(from, to) {
+                    walk.hide(base)?;
+                }
+            } else {
+                walk.hide(from)?;
+            }
+        }
+        if options.first_parent {
+            walk.simplify_first_parent()?;
+        }
+
+        Ok(walk.filter_map(|oid| oid.ok()).collect())
+    }
+
+    /// Keeps the commits accepted by `filter`, reading their committer date and parents.
+    pub fn filter_ids(&self, ids: Vec<Oid>, filter: &WalkFilter) -> Vec<Oid> {
+        ids.into_iter()
+            .filter(|oid| {
+                let Ok(commit) = self.repo.find_commit(*oid) else { return false };
+                let committer_time = commit.committer().when().seconds();
+                filter.accepts(committer_time, commit.parent_count() > 1)
+            })
+            .collect()
+    }
+
+    /// Reads author, date, trailers and numstat-style line totals for one commit.
+    /// With `first_parent`, merges are diffed against their first parent (like
+    /// `git log --first-parent`), crediting the merged branch's changes to the merge.
+    pub fn raw_commit(&self, oid: Oid, paths: &PathFilter, first_parent: bool) -> Result<RawCommit, Error> {
+        let commit = self.repo.find_commit(oid)?;
+        let author = commit.author();
+        let committer = commit.committer();
+        let committer_time = committer.when().seconds();
+
+        let trailers = git2::message_trailers_strs(commit.message().unwrap_or(""))
+            .map(|t| {
+                t.iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let issues = issue_refs(commit.message().unwrap_or(""));
+
+        let files = self.file_stats(oid, paths, first_parent)?;
+        let lines_added: u64 = files.iter().map(|f| f.1).sum();
+        let lines_deleted: u64 = files.iter().map(|f| f.2).sum();
+        // A line-ending conversion deletes and re-adds the same lines; only then is the second diff worth it
+        let eol_lines = if self.line_endings && lines_added > 0 && lines_deleted > 0 {
+            let significant: u64 = self
+                .diff_file_stats(oid, paths, first_parent, true)?
+                .iter()
+                .map(|f| f.1 + f.2)
+                .sum();
+            (lines_added + lines_deleted).saturating_sub(significant)
+        } else {
+            0
+        };
+        // Reformatting too deletes and re-adds lines
+        let format_lines = if self.format_only && lines_added > 0 && lines_deleted > 0 {
+            self.format_lines(oid, paths, first_parent)?
+        } else {
+            0
+        };
+        let mut lines_by_ext: Vec<(String, u64)> = Vec::new();
+        for (path, added, deleted) in &files {
+            let ext = file_extension(path);
+            match lines_by_ext.iter_mut().find(|(e, _)| *e == ext) {
+                Some((_, lines)) => *lines += added + deleted,
+                None => lines_by_ext.push((ext, added + deleted)),
+            }
+        }
+
+        Ok(RawCommit {
+            is_root: commit.parent_count() == 0,
+            is_merge: commit.parent_count() > 1,
+            committer_time,
+            author_name: author.name().unwrap_or("").to_string(),
+            author_email: author.email().unwrap_or("").to_string(),
+            committer_name: committer.name().unwrap_or("").to_string(),
+            committer_email: committer.email().unwrap_or("").to_string(),
+            time: author.when().seconds(),
+            offset_minutes: author.when().offset_minutes(),
+            trailers,
+            issues,
+            files_changed: files.len() as u64,
+            lines_added,
+            lines_deleted,
+            lines_by_ext,
+            eol_lines,
+            format_lines,
+        })
+    }
+
+    /// Tags pointing at commits, with the day of the release: the tagger date of annotated
+    /// tags, else the tagged commit's author date.
+    pub fn tags(&self) -> Vec<(String, String)> {
+        let Ok(references) = self.repo.references_glob("refs/tags/*") else {
+            return Vec::new();
+        };
+        references
+            .flatten()
+            .filter_map(|reference| {
+                let name = reference.shorthand().ok()?.to_string();
+                let commit = reference.peel_to_commit().ok()?;
+                let time = reference
+                    .peel_to_tag()
+                    .ok()
+                    .and_then(|tag| tag.tagger().map(|sig| sig.when()))
+                    .unwrap_or_else(|| commit.author().when());
+                Some((name, format_short_date(time)))
+            })
+            .collect()
+    }
+
+    /// First line of the commit message.
+    pub fn summary(&self, oid: Oid) -> String {
+        match self.repo.find_commit(oid) {
+            Ok(commit) => commit.message().unwrap_or("").lines().next().unwrap_or("").trim().to_string(),
+            Err(_) => String::new(),
+        }
+    }
+
+    /// Builds the record for a commit, resolving its author through the mailmap.
+    /// Returns `None` when path filters are active and the commit touches no matching file.
+    pub fn record(&self, oid: Oid, raw: &RawCommit, paths: &PathFilter) -> Option<CommitRecord> {
+        if paths.is_active() && raw.files_changed == 0 {
+            return None;
+        }
+
+        let when = Time::new(raw.time, raw.offset_minutes);
+        let resolve = |name: &str, email: &str| {
+            self.mailmap
+                .as_ref()
+                .and_then(|mailmap| {
+                    let signature = Signature::new(name, email, &when).ok()?;
+                    let resolved = mailmap.resolve_signature(&signature).ok()?;
+                    Some((resolved.name().ok()?.to_string(), resolved.email().ok()?.to_string()))
+                })
+                .unwrap_or_else(|| (name.to_string(), email.to_string()))
+        };
+        let (name, email) = resolve(&raw.author_name, &raw.author_email);
+        let (committer, committer_email) = resolve(&raw.committer_name, &raw.committer_email);
+
+        Some(CommitRecord {
+            hash: oid.to_string(),
+            repo: self.name.clone(),
+            is_root: raw.is_root,
+            is_merge: raw.is_merge,
+            committer_time: raw.committer_time,
+            author: name,
+            email: email.to_lowercase(),
+            committer,
+            committer_email: committer_email.to_lowercase(),
+            applied_by: None,
+            date: format_short_date(when),
+            hour: local_hour(when),
+            utc_hour: (raw.time.rem_euclid(86_400) / 3_600) as u32,
+            trailers: raw.trailers.clone(),
+            issues: raw.issues.clone(),
+            weight: None,
+            recency: None,
+            functions: None,
+            files_changed: raw.files_changed,
+            lines_added: raw.lines_added,
+            lines_deleted: raw.lines_deleted,
+            lines_by_ext: raw.lines_by_ext.clone(),
+            eol_lines: raw.eol_lines,
+            format_lines: raw.format_lines,
+        })
+    }
+
+    /// Blames every text file accepted by `paths` in the tree at `revspec` (the tip of a
+    /// range), returning `(path, last commit, lines)` for each hunk of surviving lines.
+    /// `on_file` is called with `(done, total)` files as blame proceeds.
+    pub fn blame_tree(
+        &self,
+        revspec: &str,
+        paths: &PathFilter,
+        first_parent: bool,
+        mut on_file: impl FnMut(u64, u64),
+    ) -> Result<Vec<(String, Oid, u64)>, Error> {
+        let tip = self.tip(revspec)?;
+        let tree = tip.tree()?;
+
+        let mut files = Vec::new();
+        tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+            if entry.kind() == Some(ObjectType::Blob) {
+                let path = format!("{}{}", dir, entry.name().unwrap_or(""));
+                if self.counts(Path::new(&path), paths) {
+                    files.push((path, entry.id()));
+                }
+            }
+            TreeWalkResult::Ok
+        })?;
+
+        let mut hunks = Vec::new();
+        let total = files.len() as u64;
+        for (done, (path, blob)) in files.into_iter().enumerate() {
+            on_file(done as u64, total);
+            let binary = self.repo.find_blob(blob).map(|b| b.is_binary()).unwrap_or(true);
+            if binary {
+                continue;
+            }
+            let mut options = BlameOptions::new();
+            options
+                .newest_commit(tip.id())
+                .first_parent(first_parent)
+                .ignore_whitespace(self.ignore_whitespace);
+            let Ok(blame) = self.repo.blame_file(Path::new(&path), Some(&mut options)) else {
+                continue;
+            };
+            for hunk in blame.iter() {
+                hunks.push((path.clone(), hunk.final_commit_id(), hunk.lines_in_hunk() as u64));
+            }
+        }
+        on_file(total, total);
+        Ok(hunks)
+    }
+
+    /// Per-file `(path, added, deleted)` for one commit, diffed like `raw_commit`
+    /// (so merges list no files unless `first_parent`).
+    pub fn file_stats(&self, oid: Oid, paths: &PathFilter, first_parent: bool) -> Result<Vec<(String, u64, u64)>, Error> {
+        self.diff_file_stats(oid, paths, first_parent, false)
+    }
+
+    /// `file_stats`, optionally ignoring whitespace at the end of lines (which covers a
+    /// CR before the LF) to tell line-ending conversions from real changes.
+    fn diff_file_stats(
+        &self,
+        oid: Oid,
+        paths: &PathFilter,
+        first_parent: bool,
+        ignore_eol: bool,
+    ) -> Result<Vec<(String, u64, u64)>, Error> {
+        let commit = self.repo.find_commit(oid)?;
+        // Like `git log --numstat`, merges show no diff (unless first-parent) and roots diff against the empty tree
+        if commit.parent_count() > 1 && !first_parent {
+            return Ok(Vec::new());
+        }
+        let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+        let tree = commit.tree()?;
+        self.file_line_stats(parent_tree.as_ref(), &tree, paths, ignore_eol)
+    }
+
+    /// Paths `oid` creates (accepted by `paths`), not counting renames. Merges create
+    /// none unless `first_parent`, as in `file_stats`.
+    pub fn new_files(&self, oid: Oid, paths: &PathFilter, first_parent: bool) -> Result<HashSet<String>, Error> {
+        let commit = self.repo.find_commit(oid)?;
+        if commit.parent_count() > 1 && !first_parent {
+            return Ok(HashSet::new());
+        }
+        let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+        let tree = commit.tree()?;
+        let mut diff = self.repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        let _ = diff.find_similar(Some(&mut self.find_options()));
+        Ok(diff
+            .deltas()
+            .filter(|delta| delta.status() == git2::Delta::Added)
+            .filter_map(|delta| delta.new_file().path().map(Path::to_path_buf))
+            .filter(|path| self.counts(path, paths))
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect())
+    }
+
+    /// Functions and sections `oid` changes, counted once per file: distinct hunk contexts
+    /// (the text after `@@ ... @@`, git's enclosing-function line), with changes above any
+    /// function counting as one section. Merges change none unless `first_parent`.
+    pub fn functions_touched(&self, oid: Oid, paths: &PathFilter, first_parent: bool) -> Result<u64, Error> {
+        let commit = self.repo.find_commit(oid)?;
+        if commit.parent_count() > 1 && !first_parent {
+            return Ok(0);
+        }
+        let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+        let tree = commit.tree()?;
+        let mut diff = self
+            .repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut self.diff_options()))?;
+        let _ = diff.find_similar(Some(&mut self.find_options()));
+
+        let mut sections = HashSet::new();
+        for (idx, delta) in diff.deltas().enumerate() {
+            let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+                continue;
+            };
+            if !self.counts(path, paths) {
+                continue;
+            }
+            let Ok(Some(patch)) = git2::Patch::from_diff(&diff, idx) else {
+                continue;
+            };
+            for hunk in (0..patch.num_hunks()).filter_map(|i| patch.hunk(i).ok()) {
+                let header = String::from_utf8_lossy(hunk.0.header()).into_owned();
+                let context = header.splitn(3, "@@").nth(2).unwrap_or("").trim().to_string();
+                sections.insert((path.to_path_buf(), context));
+            }
+        }
+        Ok(sections.len() as u64)
+    }
+
+    /// Lines added + deleted in the files `oid` only reformats: once whitespace and the
+    /// punctuation formatters add or drop (`,;'"` and parentheses) are removed, what it
+    /// deletes from the file reads exactly like what it adds. Catches reindenting, rewrapping
+    /// and quote or trailing-comma changes from tools like prettier, black or rustfmt.
+    pub fn format_lines(&self, oid: Oid, paths: &PathFilter, first_parent: bool) -> Result<u64, Error> {
+        let commit = self.repo.find_commit(oid)?;
+        if commit.parent_count() > 1 && !first_parent {
+            return Ok(0);
+        }
+        let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+        let tree = commit.tree()?;
+        let mut diff = self
+            .repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut self.diff_options()))?;
+        let _ = diff.find_similar(Some(&mut self.find_options()));
+
+        let significant = |c: &u8| !c.is_ascii_whitespace() && !b",;'\"`()".contains(c);
+        let mut total = 0;
+        for (idx, delta) in diff.deltas().enumerate() {
+            let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+                continue;
+            };
+            if !self.counts(path, paths) {
+                continue;
+            }
+            let Ok(Some(patch)) = git2::Patch::from_diff(&diff, idx) else {
+                continue;
+            };
+            let (mut deleted, mut added): (Vec<u8>, Vec<u8>) = (Vec::new(), Vec::new());
+            let (mut lines_deleted, mut lines_added) = (0u64, 0u64);
+            for hunk in 0..patch.num_hunks() {
+                for line in (0..patch.num_lines_in_hunk(hunk).unwrap_or(0)).filter_map(|i| patch.line_in_hunk(hunk, i).ok()) {
+                    let (side, count) = match line.origin() {
+                        '-' => (&mut deleted, &mut lines_deleted),
+                        '+' => (&mut added, &mut lines_added),
+                        _ => continue,
+                    };
+                    side.extend(line.content().iter().filter(|c| significant(c)));
+                    *count += 1;
+                }
+            }
+            if lines_deleted > 0 && lines_added > 0 && deleted == added {
+                total += lines_deleted + lines_added;
+            }
+        }
+        Ok(total)
+    }
+
+    /// Root of the working tree, or `None` for a bare repository.
+    pub fn workdir(&self) -> Option<&Path> {
+        self.repo.workdir()
+    }
+
+    /// The repository's shared git directory, where the stats cache lives.
+    pub fn git_dir(&self) -> &Path {
+        self.repo.commondir()
+    }
+
+    /// Returns `(path, added, deleted)` for each delta accepted by `paths`.
+    fn file_line_stats(
+        &self,
+        old: Option<&Tree>,
+        new: &Tree,
+        paths: &PathFilter,
+        ignore_eol: bool,
+    ) -> Result<Vec<(String, u64, u64)>, Error> {
+        let mut options = self.diff_options();
+        options.ignore_whitespace_eol(ignore_eol);
+        let mut diff: Diff = self.repo.diff_tree_to_tree(old, Some(new), Some(&mut options))?;
+        // Like git's `diff.renames`, so moved files aren't counted as churn
+        let _ = diff.find_similar(Some(&mut self.find_options()));
+
+        let mut files = Vec::new();
+        for (idx, delta) in diff.deltas().enumerate() {
+            let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+                continue;
+            };
+            if !self.counts(path, paths) {
+                continue;
+            }
+            let (added, deleted) = git2::Patch::from_diff(&diff, idx)
+                .ok()
+                .flatten()
+                .and_then(|patch| patch.line_stats().ok())
+                .map_or((0, 0), |(_, a, d)| (a as u64, d as u64));
+            files.push((path.to_string_lossy().into_owned(), added, deleted));
+        }
+        Ok(files)
+    }
+}
+
+/// Issue numbers referenced as `#123` in a commit message, in order of first mention.
+/// A `#` inside a word, an `owner/repo#123` cross-reference or an HTML entity is ignored.
+fn issue_refs(message: &str) -> Vec<u64> {
+    let mut issues = Vec::new();
+    let mut prev = ' ';
+    for (i, c) in message.char_indices() {
+        if c == '#' && !prev.is_alphanumeric() && prev != '/' && prev != '&' {
+            let digits: String = message[i + 1..].chars().take_while(char::is_ascii_digit).collect();
+            if let Ok(number) = digits.parse::<u64>() {
+                if !issues.contains(&number) {
+                    issues.push(number);
+                }
+            }
+        }
+        prev = c;
+    }
+    issues
+}
+
+/// Formats a signature time in its own timezone, like `--date=short`.
+fn format_short_date(time: Time) -> String {
+    FixedOffset::east_opt(time.offset_minutes() * 60)
+        .and_then(|tz| tz.timestamp_opt(time.seconds(), 0).single())
+        .map(|dt| dt.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Hour of day of a signature time in its own timezone.
+fn local_hour(time: Time) -> u32 {
+    FixedOffset::east_opt(time.offset_minutes() * 60)
+        .and_then(|tz| tz.timestamp_opt(time.seconds(), 0).single())
+        .map(|dt| dt.hour())
+        .unwrap_or(0)
+}
+
+/// Parses the value of a date option such as `--since`, exiting with a usage error when
+/// it isn't in one of the formats `parse_date` accepts.
+pub fn parse_option_date(option: &'static str, value: &str, end_of_day: bool) -> i64 {
+    parse_date(value, end_of_day).unwrap_or_else(|| {
+        Error::InvalidDate {
+            option,
+            value: value.to_string(),
+        }
+        .exit()
+    })
+}
+
+/// Parses the date formats accepted by `--since`/`--until` into a unix timestamp:
+/// `YYYY-MM-DD`, `YYYY-MM-DD HH:MM[:SS]`, RFC 3339, and `N <unit>s ago` (or `N.units.ago`).
+/// A bare date means the start of that day, or its end when `end_of_day` is set.
+pub fn parse_date(input: &str, end_of_day: bool) -> Option<i64> {
+    let input = input.trim();
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        return Some(dt.timestamp());
+    }
+    for fmt in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(input, fmt) {
+            return Local.from_local_datetime(&naive).earliest().map(|dt| dt.timestamp());
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        let naive = if end_of_day {
+            date.and_hms_opt(23, 59, 59)?
+        } else {
+            date.and_hms_opt(0, 0, 0)?
+        };
+        return Local.from_local_datetime(&naive).earliest().map(|dt| dt.timestamp());
+    }
+
+    let relative = input.replace('.', " ");
+    let words: Vec<&str> = relative.split_whitespace().collect();
+    if let [n, unit, "ago"] = words[..] {
+        let n: i64 = n.parse().ok()?;
+        let unit = unit.trim_end_matches('s');
+        let span = match unit {
+            "second" => Duration::seconds(n),
+            "minute" => Duration::minutes(n),
+            "hour" => Duration::hours(n),
+            "day" => Duration::days(n),
+            "week" => Duration::weeks(n),
+            "month" => Duration::days(n * 30),
+            "year" => Duration::days(n * 365),
+            _ => return None,
+        };
+        return Some((Local::now() - span).timestamp());
+    }
+
+    None
+}
@@ -0,0 +1,407 @@
+use crate::error::{Error, OrExit};
+use crate::forge::percent_encode;
+use crate::repo::parse_date;
+use crate::html::report_html;
+use crate::{
+    contributor_stats, fold_others, handle_formatting, handle_imports, handle_initial_commits, handle_line_endings, json_report,
+    load_commits, open_repos, open_sources, ranked_contributors, revision_label, revision_tips, Args, CommitRecord, Progress,
+};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Environment variable holding an accepted token, in addition to `--token-file`.
+const TOKEN_ENV: &str = "GIT_CONTRIB_STATS_TOKEN";
+
+/// Cookie set when a browser opens a URL with `?token=`, so the page and its polling of
+/// `/api/version` are authorized without an `Authorization` header.
+const TOKEN_COOKIE: &str = "contrib_stats_token";
+
+/// Bearer tokens accepted by the API. Without any, only loopback addresses may be served.
+fn load_tokens(token_file: Option<&str>) -> Vec<String> {
+    let mut tokens: Vec<String> = std::env::var(TOKEN_ENV).ok().into_iter().collect();
+    if let Some(path) = token_file {
+        let content = std::fs::read_to_string(path)
+            .map_err(|source| Error::Read { path: path.to_string(), source })
+            .or_exit();
+        tokens.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                .map(String::from),
+        );
+    }
+    tokens.retain(|t| !t.trim().is_empty());
+    tokens
+}
+
+/// Compares in time independent of where the strings differ, so tokens can't be guessed
+/// byte by byte from response times.
+fn same_token(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn accepted(tokens: &[String], given: &str) -> bool {
+    tokens.iter().any(|token| same_token(token, given.trim()))
+}
+
+/// Whether the request carries an accepted token, as `Authorization: Bearer` or in the
+/// cookie set by a `?token=` visit.
+fn authorized(request: &Request, tokens: &[String]) -> bool {
+    if tokens.is_empty() {
+        return true;
+    }
+    let header = |name: &'static str| request.headers().iter().find(|h| h.field.equiv(name)).map(|h| h.value.as_str());
+    let bearer = header("Authorization").and_then(|value| value.strip_prefix("Bearer "));
+    let cookie = header("Cookie").and_then(|value| {
+        value
+            .split(';')
+            .find_map(|cookie| cookie.trim().strip_prefix(TOKEN_COOKIE)?.strip_prefix('='))
+            .map(percent_decode)
+    });
+    bearer.map(String::from).into_iter().chain(cookie).any(|given| accepted(tokens, &given))
+}
+
+/// Answers a browser visit to `path` with an accepted `?token=`: sets the token cookie and
+/// redirects to the same URL without the token, so it stays out of history and logs.
+fn token_login(path: &str, query: &str, token: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+    let rest: Vec<&str> = query.split('&').filter(|pair| !pair.is_empty() && !pair.starts_with("token=")).collect();
+    let location = if rest.is_empty() { path.to_string() } else { format!("{}?{}", path, rest.join("&")) };
+    let cookie = format!("{}={}; Path=/; HttpOnly; SameSite=Strict", TOKEN_COOKIE, percent_encode(token.trim()));
+    Response::from_string("")
+        .with_status_code(303)
+        .with_header(Header::from_bytes(&b"Location"[..], location.as_bytes()).expect("URL from the request line"))
+        .with_header(Header::from_bytes(&b"Set-Cookie"[..], cookie.as_bytes()).expect("percent-encoded"))
+}
+
+/// Decodes `%XX` escapes and `+` as a space in a query string component.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => match std::str::from_utf8(&bytes[i + 1..i + 3])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    out.push(byte);
+                    i += 2;
+                }
+                None => out.push(b'%'),
+            },
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn query_params(url: &str) -> HashMap<String, String> {
+    url.split_once('?')
+        .map(|(_, query)| query)
+        .unwrap_or("")
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+#[derive(Serialize)]
+struct ApiError<'a> {
+    error: &'a str,
+}
+
+fn typed_response(status: u16, content_type: &str, body: String) -> Response<std::io::Cursor<Vec<u8>>> {
+    let content_type = Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes()).expect("static header");
+    Response::from_string(body).with_status_code(status).with_header(content_type)
+}
+
+fn json_response(status: u16, body: String) -> Response<std::io::Cursor<Vec<u8>>> {
+    typed_response(status, "application/json", body)
+}
+
+fn error_response(status: u16, message: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+    json_response(status, serde_json::to_string(&ApiError { error: message }).unwrap_or_default())
+}
+
+/// The status a failed request is answered with: 404 for a revision the repository
+/// doesn't have, 400 for other bad input, 500 for anything on the server's side.
+fn failure(e: Error) -> (u16, String) {
+    let status = match e {
+        Error::UnknownRevision { .. } => 404,
+        Error::InvalidDate { .. } | Error::Usage(_) => 400,
+        _ => 500,
+    };
+    (status, e.to_string())
+}
+
+/// The options one request asks for: `since`, `until`, `sort`, `branch` and `repo` (a
+/// repository name from `/api/repositories`) override the server's. Revisions are checked
+/// up front so a missing one gets a 404 rather than a 500.
+fn request_args(args: &Args, params: &HashMap<String, String>) -> Result<(Args, Option<String>), (u16, String)> {
+    let mut args = args.clone();
+    args.quiet = true;
+    args.progress = None;
+    for (key, end_of_day) in [("since", false), ("until", true)] {
+        if let Some(value) = params.get(key).filter(|v| !v.is_empty()) {
+            if parse_date(value, end_of_day).is_none() {
+                return Err((400, format!("invalid {} date '{}'", key, value)));
+            }
+            match key {
+                "since" => args.since = Some(value.clone()),
+                _ => args.until = Some(value.clone()),
+            }
+        }
+    }
+    if let Some(sort) = params.get("sort") {
+        if !["commits", "lines", "net", "files", "score"].contains(&sort.as_str()) {
+            return Err((400, format!("invalid sort '{}' (expected commits, lines, net, files or score)", sort)));
+        }
+        args.sort = Some(sort.clone());
+    }
+    if let Some(name) = params.get("repo") {
+        // Named among all the repositories, as `/api/repositories` lists them
+        if !open_repos(&args, false).map_err(failure)?.iter().any(|(_, repo)| repo.name() == name) {
+            return Err((404, format!("unknown repository '{}'", name)));
+        }
+        args.only_repo = Some(name.clone());
+    }
+    let branch = params.get("branch").filter(|b| !b.is_empty()).cloned();
+    for (_, repo) in open_repos(&args, false).map_err(failure)? {
+        let known = match &branch {
+            Some(branch) => repo.has_revision(branch),
+            None => repo.current_branch().is_some() || repo.fallback_branch().is_some(),
+        };
+        if !known {
+            let rev = branch.as_deref().unwrap_or("the default branch");
+            return Err((404, format!("{} not found in {}", rev, repo.name())));
+        }
+    }
+    Ok((args, branch))
+}
+
+/// What a request renders: the `--format json` report or the HTML page.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Render {
+    Json,
+    Html,
+}
+
+/// Analyzes the history for one request and renders it, the HTML page getting
+/// `extra_scripts` after its own.
+fn report(
+    args: &Args,
+    branch: Option<&str>,
+    author_mapping: &HashMap<String, String>,
+    render: Render,
+    extra_scripts: &str,
+) -> Result<String, Error> {
+    let progress = Progress::new(args);
+    let sources = open_sources(args, branch)?;
+    let mut commits: Vec<CommitRecord> = load_commits(&sources, args, args.since.clone(), args.until.clone(), &progress)?;
+    handle_initial_commits(&mut commits, args);
+    handle_line_endings(&mut commits, args);
+    handle_formatting(&mut commits, args);
+    handle_imports(&mut commits, args);
+    let mapping = fold_others(&commits, author_mapping, args);
+    let stats = contributor_stats(&commits, &mapping);
+    let sorted_stats = ranked_contributors(&stats, args);
+    let label = revision_label(&sources);
+    match render {
+        Render::Json => json_report(args, &label, &commits, &sorted_stats, &mapping, None, None),
+        Render::Html => report_html(args, &label, &commits, &sorted_stats, &mapping, extra_scripts),
+    }
+}
+
+/// Rendered reports kept in memory, the least recently requested being dropped first.
+const CACHED_REPORTS: usize = 16;
+
+/// What a rendered report depends on: the page or JSON, and the options a request can
+/// override, once defaults are applied. Other query parameters don't split the cache.
+#[derive(PartialEq, Eq)]
+struct ReportKey {
+    render: Render,
+    since: Option<String>,
+    until: Option<String>,
+    sort: Option<String>,
+    branch: Option<String>,
+    repo: Option<String>,
+}
+
+impl ReportKey {
+    fn of(render: Render, args: &Args, branch: Option<&str>) -> ReportKey {
+        ReportKey {
+            render,
+            since: args.since.clone(),
+            until: args.until.clone(),
+            sort: args.sort.clone(),
+            branch: branch.map(String::from),
+            repo: args.only_repo.clone(),
+        }
+    }
+}
+
+/// Rendered reports with the branch tips they were built from, most recently used last.
+#[derive(Default)]
+struct ReportCache {
+    entries: VecDeque<(ReportKey, String, String)>,
+}
+
+impl ReportCache {
+    /// The report for `key` if it was built from `version`.
+    fn get(&mut self, key: &ReportKey, version: &str) -> Option<String> {
+        let i = self.entries.iter().position(|(k, built_from, _)| k == key && built_from == version)?;
+        let entry = self.entries.remove(i)?;
+        let body = entry.2.clone();
+        self.entries.push_back(entry);
+        Some(body)
+    }
+
+    fn insert(&mut self, key: ReportKey, version: String, body: String) {
+        self.entries.retain(|(k, _, _)| *k != key);
+        if self.entries.len() >= CACHED_REPORTS {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, version, body));
+    }
+}
+
+/// The parameters of `query` that `/api/version` reads, for the page's reload script.
+fn version_query(query: &str) -> String {
+    let pairs: Vec<&str> = query
+        .split('&')
+        .filter(|pair| pair.starts_with("branch=") || pair.starts_with("repo="))
+        .collect();
+    if pairs.is_empty() { String::new() } else { format!("?{}", pairs.join("&")) }
+}
+
+/// Where the reload script goes in cached pages, filled in for each response since the
+/// script depends on the request's query. It follows the report's data, so the last
+/// occurrence in a page is the slot even if a commit message contains the same text.
+const RELOAD_SLOT: &str = "<!-- reload -->";
+
+/// `page` with its reload slot replaced by `script`.
+fn fill_reload_slot(mut page: String, script: &str) -> String {
+    if let Some(at) = page.rfind(RELOAD_SLOT) {
+        page.replace_range(at..at + RELOAD_SLOT.len(), script);
+    }
+    page
+}
+
+/// Script for `--reload-every`: polls `/api/version` and reloads the page once it no
+/// longer matches `version`, the one the page was built from.
+fn reload_script(seconds: u64, query: &str, version: &str) -> String {
+    format!(
+        "<script>\nsetInterval(function () {{\n  fetch('/api/version{}').then(function (r) {{ return r.ok ? r.text() : null; }}).then(function (version) {{\n    if (version !== null && version !== {}) location.reload();\n  }}).catch(function () {{}});\n}}, {});\n    </script>\n",
+        query,
+        serde_json::to_string(version).unwrap_or_default(),
+        seconds * 1000
+    )
+}
+
+/// The address to listen on: `--listen`, with its port replaced by `--port` if given.
+fn listen_addr(listen: &str, port: Option<u16>) -> SocketAddr {
+    let mut addr: SocketAddr = listen
+        .parse()
+        .map_err(|_| Error::Usage(format!("invalid --listen address '{}' (expected IP:PORT)", listen)))
+        .or_exit();
+    if let Some(port) = port {
+        addr.set_port(port);
+    }
+    addr
+}
+
+/// Serves reports until interrupted, handling one request at a time: the HTML report at
+/// `GET /`, JSON at `GET /api/report` (or `/api/stats.json`), plus `/api/repositories`
+/// and `/api/version`. Reports are generated in memory and kept until a new commit lands
+/// on an analyzed branch. Without a token, only a loopback address may be used; browsers
+/// pass it once as `?token=`.
+pub fn serve(
+    args: &Args,
+    listen: &str,
+    port: Option<u16>,
+    token_file: Option<&str>,
+    reload_every: Option<u64>,
+    author_mapping: &HashMap<String, String>,
+) {
+    let tokens = load_tokens(token_file);
+    let addr = listen_addr(listen, port);
+    if tokens.is_empty() && !addr.ip().is_loopback() {
+        Error::Usage(format!(
+            "refusing to serve {} without authentication; pass --token-file or set {}",
+            addr, TOKEN_ENV
+        ))
+        .exit();
+    }
+    let server = Server::http(addr)
+        .map_err(|e| Error::Listen {
+            addr: addr.to_string(),
+            message: e.to_string(),
+        })
+        .or_exit();
+    eprintln!("Serving the report on http://{}/ (JSON at /api/stats.json)", addr);
+
+    let names: Vec<String> = open_repos(args, false).or_exit().iter().map(|(_, repo)| repo.name().to_string()).collect();
+    let mut cache = ReportCache::default();
+    for request in server.incoming_requests() {
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+        let render = match path {
+            "/" | "/index.html" => Some(Render::Html),
+            "/api/report" | "/api/stats.json" => Some(Render::Json),
+            _ => None,
+        };
+        let login_token = query_params(&url).remove("token").filter(|token| accepted(&tokens, token));
+        let response = if let Some(token) = login_token {
+            token_login(path, query, &token)
+        } else if !authorized(&request, &tokens) {
+            error_response(401, "missing or invalid bearer token")
+        } else if *request.method() != Method::Get {
+            error_response(405, "only GET is supported")
+        } else if path == "/api/repositories" {
+            json_response(200, serde_json::to_string(&names).unwrap_or_default())
+        } else if path == "/api/version" || render.is_some() {
+            let version = request_args(args, &query_params(&url))
+                .and_then(|(args, branch)| Ok((revision_tips(&args, branch.as_slice()).map_err(failure)?, args, branch)));
+            match (version, render) {
+                (Err((status, message)), _) => error_response(status, &message),
+                (Ok((version, _, _)), None) => typed_response(200, "text/plain", version),
+                (Ok((version, args, branch)), Some(render)) => {
+                    let key = ReportKey::of(render, &args, branch.as_deref());
+                    let body = match cache.get(&key, &version) {
+                        Some(body) => Ok(body),
+                        None => {
+                            let slot = if reload_every.is_some() { RELOAD_SLOT } else { "" };
+                            let body = report(&args, branch.as_deref(), author_mapping, render, slot);
+                            if let Ok(body) = &body {
+                                cache.insert(key, version.clone(), body.clone());
+                            }
+                            body
+                        }
+                    };
+                    match (render, body) {
+                        (_, Err(e)) => error_response(500, &e.to_string()),
+                        (Render::Json, Ok(body)) => json_response(200, body),
+                        (Render::Html, Ok(body)) => {
+                            let script = reload_every
+                                .map(|seconds| reload_script(seconds, &version_query(query), &version))
+                                .unwrap_or_default();
+                            typed_response(200, "text/html; charset=utf-8", fill_reload_slot(body, &script))
+                        }
+                    }
+                }
+            }
+        } else {
+            error_response(404, "not found")
+        };
+        let _ = request.respond(response);
+    }
+}
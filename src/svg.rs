@@ -0,0 +1,200 @@
+use crate::html::{contributor_color, OTHERS_COLOR};
+use crate::{
+    bucket_label, bucket_series, capitalize, commits_by_contributor, get_commits_by_date, get_lines_by_date, is_others, share_metric, week_start_day, Args,
+    CommitRecord, ContributorStats,
+};
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+
+const WIDTH: f64 = 900.0;
+const MARGIN: f64 = 60.0;
+const TIMELINE_HEIGHT: f64 = 240.0;
+const BAR_HEIGHT: f64 = 18.0;
+const BAR_GAP: f64 = 6.0;
+const LEGEND_ROW: f64 = 20.0;
+const FONT: &str = "font-family=\"-apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif\" font-size=\"12\" fill=\"#24292f\"";
+
+/// Escapes text and attribute values.
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `--by`, or a bucket size keeping the timeline to about a hundred bars.
+fn svg_granularity<'a>(args: &'a Args, dates: &BTreeSet<NaiveDate>) -> &'a str {
+    if let Some(by) = args.by.as_deref() {
+        return by;
+    }
+    let span = match (dates.first(), dates.last()) {
+        (Some(first), Some(last)) => (*last - *first).num_days(),
+        _ => 0,
+    };
+    match span {
+        0..=100 => "day",
+        101..=700 => "week",
+        701..=3000 => "month",
+        _ => "quarter",
+    }
+}
+
+/// Renders a standalone SVG with the contributors' stacked activity over time and a bar
+/// per contributor, both in commits or, with `--sort lines`, lines per `--lines-metric`.
+/// Colors match the HTML report.
+pub fn render_svg(
+    args: &Args,
+    branch: &str,
+    commits: &[CommitRecord],
+    sorted_stats: &[(&String, &ContributorStats)],
+    author_mapping: &HashMap<String, String>,
+) -> String {
+    let lines = share_metric(args);
+    let metric = lines.map_or("Commits".to_string(), |m| capitalize(m.label()));
+    let color = |name: &str| if is_others(args, name) { OTHERS_COLOR.to_string() } else { contributor_color(name) };
+
+    let dates: BTreeSet<NaiveDate> = commits
+        .iter()
+        .filter_map(|c| NaiveDate::parse_from_str(&c.date, "%Y-%m-%d").ok())
+        .collect();
+    let by = svg_granularity(args, &dates);
+    // Stacked bars can't go below the axis, so days losing net lines count as zero
+    let daily = |own: &[&CommitRecord]| match lines {
+        Some(m) => get_lines_by_date(own.iter().copied(), m)
+            .into_iter()
+            .map(|(date, n)| (date, n.max(0) as u64))
+            .collect(),
+        None => get_commits_by_date(own.iter().copied()),
+    };
+    let grouped = commits_by_contributor(commits, author_mapping);
+    let series: Vec<BTreeMap<String, u64>> = sorted_stats
+        .iter()
+        .map(|(name, _)| {
+            let own = grouped.get(*name).map_or(&[][..], Vec::as_slice);
+            bucket_series(&daily(own), by, week_start_day(args), false)
+        })
+        .collect();
+    let all: Vec<&CommitRecord> = commits.iter().collect();
+    let buckets: Vec<String> = bucket_series(&daily(&all), by, week_start_day(args), true).into_keys().collect();
+    let totals: Vec<u64> = buckets
+        .iter()
+        .map(|bucket| series.iter().filter_map(|s| s.get(bucket)).sum())
+        .collect();
+    let max_total = totals.iter().copied().max().unwrap_or(0).max(1);
+
+    let legend_columns = 4;
+    let legend_rows = sorted_stats.len().div_ceil(legend_columns) as f64;
+    let timeline_top = 70.0;
+    let legend_top = timeline_top + TIMELINE_HEIGHT + 36.0;
+    let bars_top = legend_top + legend_rows * LEGEND_ROW + 50.0;
+    let height = bars_top + sorted_stats.len() as f64 * (BAR_HEIGHT + BAR_GAP) + MARGIN / 2.0;
+    let plot_width = WIDTH - 2.0 * MARGIN;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" role="img" aria-label="{m} per contributor on {b}">"#,
+        w = WIDTH,
+        h = height,
+        m = metric,
+        b = escape(branch)
+    );
+    let _ = writeln!(out, r##"<rect width="100%" height="100%" fill="#ffffff"/>"##);
+    let _ = writeln!(out, r#"<g {}>"#, FONT);
+    let _ = writeln!(
+        out,
+        r#"<text x="{}" y="30" font-size="16" font-weight="600">{} over time ({})</text>"#,
+        MARGIN,
+        metric,
+        escape(branch)
+    );
+
+    // Stacked timeline, one column per bucket
+    let baseline = timeline_top + TIMELINE_HEIGHT;
+    let _ = writeln!(out, r##"<line x1="{0}" y1="{1}" x2="{2}" y2="{1}" stroke="#d0d7de"/>"##, MARGIN, baseline, MARGIN + plot_width);
+    let _ = writeln!(out, r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#, MARGIN - 6.0, timeline_top + 4.0, max_total);
+    let _ = writeln!(out, r#"<text x="{}" y="{}" text-anchor="end">0</text>"#, MARGIN - 6.0, baseline + 4.0);
+    let column = plot_width / buckets.len().max(1) as f64;
+    let bar_width = (column * 0.8).max(1.0);
+    for (i, bucket) in buckets.iter().enumerate() {
+        let x = MARGIN + i as f64 * column + (column - bar_width) / 2.0;
+        let mut y = baseline;
+        for ((name, _), values) in sorted_stats.iter().zip(&series) {
+            let Some(&value) = values.get(bucket).filter(|v| **v > 0) else { continue };
+            let h = value as f64 / max_total as f64 * TIMELINE_HEIGHT;
+            y -= h;
+            let _ = writeln!(
+                out,
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"><title>{}: {} {}</title></rect>"#,
+                x,
+                y,
+                bar_width,
+                h,
+                color(name),
+                escape(name),
+                value,
+                metric.to_lowercase()
+            );
+        }
+    }
+    let label = |key: &String| {
+        NaiveDate::parse_from_str(key, "%Y-%m-%d")
+            .map(|d| bucket_label(d, by))
+            .unwrap_or_else(|_| key.clone())
+    };
+    if let (Some(first), Some(last)) = (buckets.first(), buckets.last()) {
+        let _ = writeln!(out, r#"<text x="{}" y="{}">{}</text>"#, MARGIN, baseline + 18.0, label(first));
+        if buckets.len() > 1 {
+            let _ = writeln!(out, r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#, MARGIN + plot_width, baseline + 18.0, label(last));
+        }
+    }
+
+    // Legend
+    let legend_width = plot_width / legend_columns as f64;
+    for (i, (name, _)) in sorted_stats.iter().enumerate() {
+        let x = MARGIN + (i % legend_columns) as f64 * legend_width;
+        let y = legend_top + (i / legend_columns) as f64 * LEGEND_ROW;
+        let _ = writeln!(out, r#"<rect x="{}" y="{}" width="12" height="12" rx="2" fill="{}"/>"#, x, y - 10.0, color(name));
+        let _ = writeln!(out, r#"<text x="{}" y="{}">{}</text>"#, x + 18.0, y, escape(name));
+    }
+
+    // One horizontal bar per contributor
+    let _ = writeln!(
+        out,
+        r#"<text x="{}" y="{}" font-size="16" font-weight="600">{} per contributor</text>"#,
+        MARGIN,
+        bars_top - 20.0,
+        metric
+    );
+    let value_of = |stats: &ContributorStats| lines.map_or(stats.commits, |m| m.of(stats).max(0) as u64);
+    let max_value = sorted_stats.iter().map(|(_, s)| value_of(s)).max().unwrap_or(0).max(1);
+    let name_width = 160.0;
+    let bar_span = plot_width - name_width - 60.0;
+    for (i, (name, stats)) in sorted_stats.iter().enumerate() {
+        let y = bars_top + i as f64 * (BAR_HEIGHT + BAR_GAP);
+        let value = value_of(stats);
+        let w = (value as f64 / max_value as f64 * bar_span).max(1.0);
+        let _ = writeln!(
+            out,
+            r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#,
+            MARGIN + name_width - 8.0,
+            y + BAR_HEIGHT - 5.0,
+            escape(name)
+        );
+        let _ = writeln!(
+            out,
+            r#"<rect x="{}" y="{}" width="{:.1}" height="{}" rx="2" fill="{}"/>"#,
+            MARGIN + name_width,
+            y,
+            w,
+            BAR_HEIGHT,
+            color(name)
+        );
+        let _ = writeln!(out, r#"<text x="{:.1}" y="{}">{}</text>"#, MARGIN + name_width + w + 6.0, y + BAR_HEIGHT - 5.0, value);
+    }
+
+    out.push_str("</g>\n</svg>\n");
+    out
+}
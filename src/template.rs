@@ -0,0 +1,38 @@
+/// Fills a Handlebars-style page template: `{{name}}` inserts the value HTML-escaped and
+/// `{{{name}}}` inserts it as is. Only plain placeholders are supported (no helpers,
+/// conditionals or loops); each group in `required` needs one of its names to appear.
+pub fn render(template: &str, values: &[(&str, &str)], required: &[&[&str]]) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut used: Vec<&str> = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let raw = rest[start..].starts_with("{{{");
+        let (open, close) = if raw { ("{{{", "}}}") } else { ("{{", "}}") };
+        let after = &rest[start + open.len()..];
+        let name = after.find(close).map(|end| (end, after[..end].trim())).filter(|(_, name)| !name.contains(['{', '}']));
+        let Some((end, name)) = name else {
+            let line = template[..template.len() - rest.len() + start].matches('\n').count() + 1;
+            return Err(format!("unclosed '{}' on line {}", open, line));
+        };
+        let Some((key, value)) = values.iter().find(|(key, _)| *key == name) else {
+            let known: Vec<&str> = values.iter().map(|(key, _)| *key).collect();
+            return Err(format!("unknown placeholder '{}' (expected one of: {})", name, known.join(", ")));
+        };
+        if raw {
+            out.push_str(value);
+        } else {
+            out.push_str(&crate::html::escape_html(value));
+        }
+        used.push(key);
+        rest = &after[end + close.len()..];
+    }
+    out.push_str(rest);
+    match required.iter().find(|names| !names.iter().any(|name| used.contains(name))) {
+        Some(names) => {
+            let names: Vec<String> = names.iter().map(|name| format!("{{{{{{{}}}}}}}", name)).collect();
+            Err(format!("the template must contain {}", names.join(" or ")))
+        }
+        None => Ok(out),
+    }
+}
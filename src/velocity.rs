@@ -0,0 +1,137 @@
+use crate::repo::parse_date;
+use crate::{capitalize, display_width, is_others, pad_display, Args, CommitRecord, ContributorStats, LinesMetric};
+use chrono::{Datelike, Local, NaiveDate, TimeZone, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Availability of one contributor, from a `[contributors."Name"]` table in the config file.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ContributorMeta {
+    /// Percentage of a full-time week worked
+    pub part_time: Option<f64>,
+    /// First working day; earlier days of the period don't count
+    pub start: Option<String>,
+    /// Leave as `YYYY-MM-DD..YYYY-MM-DD`, both days included
+    pub leave: Vec<String>,
+}
+
+fn day(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok()
+}
+
+fn leave_range(value: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let (first, last) = value.split_once("..")?;
+    Some((day(first)?, day(last)?)).filter(|(first, last)| first <= last)
+}
+
+impl ContributorMeta {
+    /// Describes the first invalid value, for the config file error.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(part) = self.part_time.filter(|p| !(*p > 0.0 && *p <= 100.0)) {
+            return Err(format!("part-time {} is not a percentage between 0 and 100", part));
+        }
+        if let Some(start) = self.start.as_deref().filter(|s| day(s).is_none()) {
+            return Err(format!("start '{}' is not a YYYY-MM-DD date", start));
+        }
+        if let Some(leave) = self.leave.iter().find(|l| leave_range(l).is_none()) {
+            return Err(format!("leave '{}' is not a YYYY-MM-DD..YYYY-MM-DD range", leave));
+        }
+        Ok(())
+    }
+}
+
+/// Checks a `holidays` entry from the config file.
+pub fn valid_holiday(value: &str) -> bool {
+    day(value).is_some()
+}
+
+/// Output per available working day, so part-timers and people who joined or were on
+/// leave during the period compare fairly with everyone else.
+#[derive(Clone, Copy, Serialize)]
+pub struct Velocity {
+    /// Weekdays in the period that aren't holidays, from the contributor's start date
+    /// and outside their leave, times their part-time share
+    pub available_days: f64,
+    pub commits_per_day: f64,
+    /// Lines per `--lines-metric`
+    pub lines_per_day: f64,
+}
+
+/// First and last day velocity is measured over: `--since`/`--until` when given,
+/// otherwise the dates of the first and last commit.
+fn period(args: &Args, commits: &[CommitRecord]) -> Option<(NaiveDate, NaiveDate)> {
+    let to_day = |value: &str, end_of_day: bool| {
+        parse_date(value, end_of_day).and_then(|ts| Local.timestamp_opt(ts, 0).single()).map(|dt| dt.date_naive())
+    };
+    let first = args.since.as_deref().and_then(|s| to_day(s, false)).or_else(|| commits.iter().filter_map(|c| day(&c.date)).min())?;
+    let last = args.until.as_deref().and_then(|u| to_day(u, true)).or_else(|| commits.iter().filter_map(|c| day(&c.date)).max())?;
+    Some((first, last)).filter(|(first, last)| first <= last)
+}
+
+fn available_days(meta: Option<&ContributorMeta>, first: NaiveDate, last: NaiveDate, holidays: &HashSet<NaiveDate>) -> f64 {
+    let start = meta.and_then(|m| m.start.as_deref()).and_then(day).map_or(first, |s| s.max(first));
+    let leave: Vec<(NaiveDate, NaiveDate)> = meta.map_or(&[][..], |m| m.leave.as_slice()).iter().filter_map(|l| leave_range(l)).collect();
+    let days = start
+        .iter_days()
+        .take_while(|d| *d <= last)
+        .filter(|d| !matches!(d.weekday(), Weekday::Sat | Weekday::Sun))
+        .filter(|d| !holidays.contains(d))
+        .filter(|d| !leave.iter().any(|(from, to)| from <= d && d <= to))
+        .count();
+    days as f64 * meta.and_then(|m| m.part_time).unwrap_or(100.0) / 100.0
+}
+
+/// Velocity of each contributor in `sorted_stats`, except the `--top` "Others" row,
+/// which stands for many people. Contributors with no available day are left out.
+pub fn velocities(args: &Args, commits: &[CommitRecord], sorted_stats: &[(&String, &ContributorStats)]) -> HashMap<String, Velocity> {
+    let Some((first, last)) = period(args, commits) else { return HashMap::new() };
+    let holidays: HashSet<NaiveDate> = args.holidays.iter().filter_map(|h| day(h)).collect();
+    let metric = LinesMetric::from_args(args);
+    sorted_stats
+        .iter()
+        .filter(|(name, _)| !is_others(args, name))
+        .filter_map(|(name, stats)| {
+            let available = available_days(args.contributors.get(*name), first, last, &holidays);
+            (available > 0.0).then(|| {
+                let velocity = Velocity {
+                    available_days: available,
+                    commits_per_day: stats.commits as f64 / available,
+                    lines_per_day: metric.of(stats) as f64 / available,
+                };
+                ((*name).clone(), velocity)
+            })
+        })
+        .collect()
+}
+
+pub fn print_velocity(sorted_stats: &[(&String, &ContributorStats)], velocities: &HashMap<String, Velocity>, metric: LinesMetric) {
+    println!();
+    println!("Velocity (per available working day)");
+    println!();
+    let rows: Vec<(&String, &Velocity)> = sorted_stats.iter().filter_map(|(name, _)| velocities.get(*name).map(|v| (*name, v))).collect();
+    if rows.is_empty() {
+        println!("No working days in the period");
+        return;
+    }
+    let name_width = rows.iter().map(|(name, _)| display_width(name)).max().unwrap_or(0).max(12);
+    let lines_header = format!("{}/day", capitalize(metric.label()));
+    let lines_width = lines_header.len();
+    println!(
+        "| {} | {:>11} | {:>11} | {:>lines_width$} |",
+        pad_display("Contributor", name_width),
+        "Avail. days",
+        "Commits/day",
+        lines_header
+    );
+    println!("|{:-<width$}|{:-<13}|{:-<13}|{:-<lines$}|", "", "", "", "", width = name_width + 2, lines = lines_width + 2);
+    for (name, velocity) in rows {
+        println!(
+            "| {} | {:>11.1} | {:>11.2} | {:>lines_width$.1} |",
+            pad_display(name, name_width),
+            velocity.available_days,
+            velocity.commits_per_day,
+            velocity.lines_per_day
+        );
+    }
+}
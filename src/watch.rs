@@ -0,0 +1,56 @@
+use crate::error::{Error, OrExit};
+use crate::{requested_revisions, revision_tips, Args, Commands};
+use chrono::Local;
+use std::io::IsTerminal;
+use std::process::Command;
+use std::time::Duration;
+
+/// Set on the runs `--watch` starts, to the run's number.
+const WATCH_RUN_ENV: &str = "GIT_CONTRIB_STATS_WATCH_RUN";
+
+/// Whether this process is a run started by `--watch`, rather than the watcher itself.
+pub fn is_watch_run() -> bool {
+    std::env::var_os(WATCH_RUN_ENV).is_some()
+}
+
+/// Whether this is a `--watch` run after the first, which shouldn't open the report again.
+pub fn is_repeat_run() -> bool {
+    std::env::var(WATCH_RUN_ENV).is_ok_and(|run| run != "1")
+}
+
+/// Runs this command again, as is, each time the analyzed branches get new commits,
+/// checking every `--watch-interval` seconds until interrupted. Each run is a child
+/// process so every report and exit code behaves exactly as without `--watch`; the
+/// terminal is cleared between runs.
+pub fn watch(args: &Args) -> ! {
+    if matches!(args.command, Some(Commands::Serve { .. } | Commands::Cache { .. })) {
+        Error::Usage("--watch can't be combined with serve or cache".to_string()).exit();
+    }
+    let exe = std::env::current_exe().unwrap_or_else(|e| Error::Usage(format!("could not locate this executable: {}", e)).exit());
+    let revisions = requested_revisions(args);
+    let interval = Duration::from_secs(args.watch_interval);
+    let clear = std::io::stdout().is_terminal();
+
+    for run in 1u64.. {
+        let tips = revision_tips(args, &revisions).or_exit();
+        if clear {
+            print!("\x1b[2J\x1b[H");
+        }
+        let status = Command::new(&exe)
+            .args(std::env::args_os().skip(1))
+            .env(WATCH_RUN_ENV, run.to_string())
+            .status()
+            .unwrap_or_else(|e| Error::Usage(format!("could not run {}: {}", exe.display(), e)).exit());
+        let failed = if status.success() { String::new() } else { format!(" (exited with {})", status) };
+        eprintln!(
+            "Updated {}{}; watching for new commits every {}s, Ctrl-C to stop",
+            Local::now().format("%H:%M:%S"),
+            failed,
+            args.watch_interval
+        );
+        while revision_tips(args, &revisions).or_exit() == tips {
+            std::thread::sleep(interval);
+        }
+    }
+    unreachable!("runs are counted without end")
+}